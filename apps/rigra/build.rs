@@ -0,0 +1,45 @@
+//! Embed build metadata for `rigra version --verbose`.
+//!
+//! Everything is best effort: a missing `git` or a source tarball without
+//! `.git` yields `unknown` rather than failing the build.
+
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let commit = run("git", &["rev-parse", "--short=12", "HEAD"]);
+    if let Some(git_dir) = run("git", &["rev-parse", "--absolute-git-dir"]) {
+        println!("cargo:rerun-if-changed={}/HEAD", git_dir);
+        println!("cargo:rerun-if-changed={}/refs", git_dir);
+    }
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = run(&rustc, &["--version"]);
+    let target = std::env::var("TARGET").ok();
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(k, _)| {
+            k.strip_prefix("CARGO_FEATURE_")
+                .map(|f| f.to_ascii_lowercase().replace('_', "-"))
+        })
+        .collect();
+    features.sort();
+
+    let or_unknown = |v: Option<String>| v.unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=RIGRA_GIT_COMMIT={}", or_unknown(commit));
+    println!(
+        "cargo:rustc-env=RIGRA_RUSTC_VERSION={}",
+        or_unknown(rustc_version)
+    );
+    println!("cargo:rustc-env=RIGRA_TARGET={}", or_unknown(target));
+    println!("cargo:rustc-env=RIGRA_FEATURES={}", features.join(","));
+}
+
+/// Trimmed stdout of a successful command, if any.
+fn run(cmd: &str, args: &[&str]) -> Option<String> {
+    let out = Command::new(cmd).args(args).output().ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let s = String::from_utf8(out.stdout).ok()?.trim().to_string();
+    (!s.is_empty()).then_some(s)
+}
@@ -0,0 +1,195 @@
+//! On-disk lint cache (`rigra lint --cache`).
+//!
+//! Results are stored per `(rule, file)` under `.rigra/cache/lint.json`.
+//! An entry is reused only when its key matches, and the key covers
+//! everything that can change the outcome:
+//! - the target file contents,
+//! - the resolved policy file contents for the rule,
+//! - the index contents (including inline policies) and the effective config (`rigra.toml`,
+//!   scope, pattern overrides, depth limit, working directory).
+//!
+//! Editing a policy, the index, or the config therefore busts affected
+//! entries even when target files are unchanged. Rules with `pathExists`
+//! checks look at other files and bypass the cache. Hashes use FNV-1a so
+//! keys are stable across toolchains.
+
+use crate::models::Issue;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const CACHE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Default)]
+struct CacheFile {
+    version: u32,
+    entries: HashMap<String, Vec<Issue>>,
+}
+
+/// Lint cache loaded for one run; entries not touched by the run are
+/// dropped on save so the file does not grow without bound.
+pub struct LintCache {
+    path: PathBuf,
+    previous: HashMap<String, Vec<Issue>>,
+    current: Mutex<HashMap<String, Vec<Issue>>>,
+}
+
+impl LintCache {
+    /// Load the cache for `root`, starting empty when missing or unreadable.
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(".rigra").join("cache").join("lint.json");
+        let previous = fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<CacheFile>(&s).ok())
+            .filter(|c| c.version == CACHE_VERSION)
+            .map(|c| c.entries)
+            .unwrap_or_default();
+        LintCache {
+            path,
+            previous,
+            current: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Cached issues for `key`, marking the entry as live for this run.
+    pub fn get(&self, key: &str) -> Option<Vec<Issue>> {
+        let hit = self.previous.get(key)?.clone();
+        if let Ok(mut cur) = self.current.lock() {
+            cur.insert(key.to_string(), hit.clone());
+        }
+        Some(hit)
+    }
+
+    /// Record freshly computed issues for `key`.
+    pub fn put(&self, key: &str, issues: &[Issue]) {
+        if let Ok(mut cur) = self.current.lock() {
+            cur.insert(key.to_string(), issues.to_vec());
+        }
+    }
+
+    /// Persist entries touched during this run.
+    pub fn save(self) -> std::io::Result<()> {
+        let entries = self.current.into_inner().unwrap_or_default();
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = CacheFile {
+            version: CACHE_VERSION,
+            entries,
+        };
+        let s = serde_json::to_string(&file).map_err(std::io::Error::other)?;
+        fs::write(&self.path, s)
+    }
+}
+
+/// Incremental FNV-1a (64-bit) hasher for cache keys.
+#[derive(Clone, Copy)]
+pub struct KeyHasher(u64);
+
+impl Default for KeyHasher {
+    fn default() -> Self {
+        KeyHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl KeyHasher {
+    /// Feed a length-delimited chunk so adjacent parts cannot collide.
+    pub fn part(mut self, bytes: &[u8]) -> Self {
+        for b in (bytes.len() as u64).to_le_bytes().iter().chain(bytes) {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        self
+    }
+
+    pub fn finish(self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+/// Hash of the run-wide inputs: index text, `rigra.toml`, and effective options.
+pub fn global_key(
+    root: &Path,
+    index_text: &str,
+    scope: &str,
+    max_depth: Option<usize>,
+    patterns_override: &HashMap<String, Vec<String>>,
+) -> KeyHasher {
+    let mut overrides: Vec<_> = patterns_override.iter().collect();
+    overrides.sort();
+    let cwd = std::env::current_dir().unwrap_or_default();
+    KeyHasher::default()
+        .part(index_text.as_bytes())
+        .part(&fs::read(root.join("rigra.toml")).unwrap_or_default())
+        .part(scope.as_bytes())
+        .part(format!("{:?}", max_depth).as_bytes())
+        .part(format!("{:?}", overrides).as_bytes())
+        .part(cwd.to_string_lossy().as_bytes())
+        .part(env!("CARGO_PKG_VERSION").as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn issue(msg: &str) -> Issue {
+        Issue {
+            file: "a.json".into(),
+            rule: "r".into(),
+            severity: "error".into(),
+            fixable: false,
+            path: "$.a".into(),
+            message: msg.into(),
+            code: None,
+        }
+    }
+
+    #[test]
+    fn test_key_changes_with_any_part() {
+        let base = KeyHasher::default().part(b"policy").part(b"target");
+        assert_eq!(
+            base.finish(),
+            KeyHasher::default()
+                .part(b"policy")
+                .part(b"target")
+                .finish()
+        );
+        assert_ne!(
+            base.finish(),
+            KeyHasher::default()
+                .part(b"policy2")
+                .part(b"target")
+                .finish()
+        );
+        // Length-delimited: moving bytes between parts changes the key
+        assert_ne!(
+            base.finish(),
+            KeyHasher::default()
+                .part(b"policyt")
+                .part(b"arget")
+                .finish()
+        );
+    }
+
+    #[test]
+    fn test_save_keeps_only_live_entries() {
+        let tmp = tempdir().unwrap();
+        let cache = LintCache::load(tmp.path());
+        cache.put("k1", &[issue("one")]);
+        cache.put("k2", &[]);
+        cache.save().unwrap();
+
+        let cache = LintCache::load(tmp.path());
+        assert_eq!(cache.get("k1").unwrap()[0].message, "one");
+        assert!(cache.get("missing").is_none());
+        cache.save().unwrap();
+
+        // k2 was not touched in the second run and is pruned
+        let cache = LintCache::load(tmp.path());
+        assert!(cache.get("k1").is_some());
+        assert!(cache.get("k2").is_none());
+    }
+}
@@ -1,35 +1,346 @@
 //! Implementation of policy-driven validation checks.
 //!
-//! Supported check kinds: `required`, `type`, `const`, `pattern`, `enum`,
-//! `minLength`, `maxLength`. Paths accept a simple `$.a.b` or `a.b` syntax.
+//! Supported check kinds: `required`, `forbidden`, `nonEmpty`, `type`, `const`,
+//! `pattern`, `enum`, `minLength`, `maxLength`, `minItems`, `maxItems`,
+//! `uniqueItems`, `objectValues`, `minimum`, `maximum`, `multipleOf`, `format`,
+//! `url`, `semverGt`, `noAdditionalProperties`, `propertyNames`, `sameAs`,
+//! `pathExists`, `dependency`, `consistentField`, `each`, `discriminated`, and
+//! the combinators `anyOf`, `allOf`, `not`. Paths accept a simple `$.a.b` or
+//! `a.b` syntax, with `[n]` array indices and `*` wildcards that fan a check
+//! out over every element.
+//!
+//! Cross-file kinds (`consistentField`) are skipped by `run_checks` and
+//! evaluated once per rule by `run_aggregate_checks`. Checks with
+//! `level = "off"` (or `"ignore"`) are skipped by both.
 
 use crate::models::policy::Check;
 use crate::models::Issue;
-use crate::utils::{get_json_path, rel_to_wd};
-use regex::Regex;
+use crate::utils::{get_json_path, get_json_paths, path_key, path_segments, rel_to_wd};
+use regex::{Regex, RegexBuilder};
 use serde_json::Value as Json;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Pattern checks must set exactly one of `regex` and `regex_from`; a
+/// missing regex would otherwise compile as `""` and match everything.
+pub fn validate_pattern_sources(checks: &[Check]) -> Result<(), String> {
+    for chk in checks.iter().flat_map(Check::walk) {
+        if let Check::Pattern {
+            field,
+            regex,
+            regex_from,
+            ..
+        } = chk
+        {
+            match (regex.is_empty(), regex_from.is_some()) {
+                (false, true) => {
+                    return Err(format!(
+                        "Pattern check for '{}' sets both regex and regex_from",
+                        field
+                    ))
+                }
+                (true, false) => {
+                    return Err(format!(
+                        "Pattern check for '{}' sets neither regex nor regex_from",
+                        field
+                    ))
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Resolve `regex_from` on pattern checks by loading the regex from a file
+/// relative to `base` (the policy's directory).
+///
+/// Loaded files are cached in `files` for the run. Errors name the file when
+/// it is missing or its contents are not a valid regex.
+pub fn resolve_regex_files(
+    checks: &mut [Check],
+    base: &Path,
+    files: &mut HashMap<PathBuf, String>,
+) -> Result<(), String> {
+    for chk in checks.iter_mut() {
+        if let Check::Pattern {
+            regex,
+            regex_from: Some(from),
+            ..
+        } = chk
+        {
+            let file = base.join(&from);
+            let pattern = match files.get(&file) {
+                Some(p) => p.clone(),
+                None => {
+                    let raw = fs::read_to_string(&file).map_err(|e| {
+                        format!(
+                            "regex_from file not found: {} — {}",
+                            file.to_string_lossy(),
+                            e
+                        )
+                    })?;
+                    let p = raw.trim_end_matches(['\n', '\r']).to_string();
+                    files.insert(file.clone(), p.clone());
+                    p
+                }
+            };
+            if let Err(e) = Regex::new(&pattern) {
+                return Err(format!(
+                    "Invalid regex in {}: {}",
+                    file.to_string_lossy(),
+                    e
+                ));
+            }
+            *regex = pattern;
+        }
+        match chk {
+            Check::Each { checks, .. }
+            | Check::AnyOf { checks, .. }
+            | Check::AllOf { checks, .. } => resolve_regex_files(checks, base, files)?,
+            Check::Not { check, .. } => {
+                resolve_regex_files(std::slice::from_mut(check.as_mut()), base, files)?
+            }
+            Check::Discriminated { variants, .. } => {
+                for checks in variants.values_mut() {
+                    resolve_regex_files(checks, base, files)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Expand a field containing `*` segments into the concrete paths present in
+/// `json`. Segments after the last `*` are kept literally, so
+/// `$.contributors.*.email` yields one path per contributor even when the
+/// email is missing. Fields without `*` are returned unchanged.
+fn expand_field(json: &Json, field: &str) -> Vec<String> {
+    let segs = path_segments(field);
+    let Some(last) = segs.iter().rposition(|s| *s == "*") else {
+        return vec![field.to_string()];
+    };
+    let suffix: String = segs[last + 1..].iter().map(|s| path_key(s)).collect();
+    get_json_paths(json, &segs[..=last])
+        .into_iter()
+        .map(|(at, _)| format!("{}{}", at, suffix))
+        .collect()
+}
+
+/// Replace checks on wildcard fields with one check per concrete path so
+/// each match is reported (and fixed) on its own.
+pub fn expand_wildcards(checks: &[Check], json: &Json) -> Vec<Check> {
+    let mut out = Vec::with_capacity(checks.len());
+    for chk in checks {
+        match chk {
+            Check::Required {
+                fields,
+                message,
+                messages,
+                level,
+                code,
+            } if fields.iter().any(|f| f.contains('*')) => {
+                let mut concrete = Vec::new();
+                let mut per_field = messages.clone();
+                for f in fields {
+                    for c in expand_field(json, f) {
+                        // Per-field messages written for the wildcard apply to each match
+                        if let Some(m) = messages.as_ref().and_then(|m| m.get(f)) {
+                            per_field
+                                .get_or_insert_with(HashMap::new)
+                                .insert(c.clone(), m.clone());
+                        }
+                        concrete.push(c);
+                    }
+                }
+                out.push(Check::Required {
+                    fields: concrete,
+                    message: message.clone(),
+                    messages: per_field,
+                    level: level.clone(),
+                    code: code.clone(),
+                });
+            }
+            Check::Forbidden {
+                fields,
+                message,
+                level,
+                code,
+            } if fields.iter().any(|f| f.contains('*')) => {
+                out.push(Check::Forbidden {
+                    fields: fields.iter().flat_map(|f| expand_field(json, f)).collect(),
+                    message: message.clone(),
+                    level: level.clone(),
+                    code: code.clone(),
+                });
+            }
+            Check::NonEmpty {
+                fields,
+                message,
+                level,
+                code,
+            } if fields.iter().any(|f| f.contains('*')) => {
+                out.push(Check::NonEmpty {
+                    fields: fields.iter().flat_map(|f| expand_field(json, f)).collect(),
+                    message: message.clone(),
+                    level: level.clone(),
+                    code: code.clone(),
+                });
+            }
+            Check::Type {
+                fields,
+                message,
+                level,
+                code,
+            } if fields.keys().any(|f| f.contains('*')) => {
+                let mut concrete = HashMap::new();
+                for (f, kind) in fields {
+                    for c in expand_field(json, f) {
+                        concrete.insert(c, kind.clone());
+                    }
+                }
+                out.push(Check::Type {
+                    fields: concrete,
+                    message: message.clone(),
+                    level: level.clone(),
+                    code: code.clone(),
+                });
+            }
+            _ => match single_field(chk) {
+                Some(f) if f.contains('*') => {
+                    for c in expand_field(json, f) {
+                        let mut one = chk.clone();
+                        if let Some(slot) = single_field_mut(&mut one) {
+                            *slot = c;
+                        }
+                        out.push(one);
+                    }
+                }
+                _ => out.push(chk.clone()),
+            },
+        }
+    }
+    out
+}
+
+/// Path of `key` inside the object at `norm` (a field without its `$.`
+/// prefix; empty for the document root). Keys holding `.`, `[` or `]` are
+/// quoted so the path resolves back through `get_json_path`.
+fn child_path(norm: &str, key: &str) -> String {
+    if norm.is_empty() {
+        format!("${}", path_key(key))
+    } else {
+        format!("$.{}{}", norm, path_key(key))
+    }
+}
+
+/// The `field` of checks that target a single path.
+fn single_field(chk: &Check) -> Option<&String> {
+    match chk {
+        Check::Const { field, .. }
+        | Check::Pattern { field, .. }
+        | Check::Enum { field, .. }
+        | Check::MinLength { field, .. }
+        | Check::MaxLength { field, .. }
+        | Check::MinItems { field, .. }
+        | Check::MaxItems { field, .. }
+        | Check::UniqueItems { field, .. }
+        | Check::ObjectValues { field, .. }
+        | Check::Format { field, .. }
+        | Check::Url { field, .. }
+        | Check::Minimum { field, .. }
+        | Check::Maximum { field, .. }
+        | Check::MultipleOf { field, .. }
+        | Check::NoAdditionalProperties { field, .. }
+        | Check::PropertyNames { field, .. }
+        | Check::Each { field, .. }
+        | Check::SemverGt { field, .. }
+        | Check::SameAs { field, .. }
+        | Check::PathExists { field, .. } => Some(field),
+        Check::Required { .. }
+        | Check::Forbidden { .. }
+        | Check::NonEmpty { .. }
+        | Check::Type { .. }
+        | Check::Dependency { .. }
+        | Check::ConsistentField { .. }
+        | Check::AnyOf { .. }
+        | Check::AllOf { .. }
+        | Check::Not { .. }
+        | Check::Discriminated { .. } => None,
+    }
+}
+
+fn single_field_mut(chk: &mut Check) -> Option<&mut String> {
+    match chk {
+        Check::Const { field, .. }
+        | Check::Pattern { field, .. }
+        | Check::Enum { field, .. }
+        | Check::MinLength { field, .. }
+        | Check::MaxLength { field, .. }
+        | Check::MinItems { field, .. }
+        | Check::MaxItems { field, .. }
+        | Check::UniqueItems { field, .. }
+        | Check::ObjectValues { field, .. }
+        | Check::Format { field, .. }
+        | Check::Url { field, .. }
+        | Check::Minimum { field, .. }
+        | Check::Maximum { field, .. }
+        | Check::MultipleOf { field, .. }
+        | Check::NoAdditionalProperties { field, .. }
+        | Check::PropertyNames { field, .. }
+        | Check::Each { field, .. }
+        | Check::SemverGt { field, .. }
+        | Check::SameAs { field, .. }
+        | Check::PathExists { field, .. } => Some(field),
+        Check::Required { .. }
+        | Check::Forbidden { .. }
+        | Check::NonEmpty { .. }
+        | Check::Type { .. }
+        | Check::Dependency { .. }
+        | Check::ConsistentField { .. }
+        | Check::AnyOf { .. }
+        | Check::AllOf { .. }
+        | Check::Not { .. }
+        | Check::Discriminated { .. } => None,
+    }
+}
 
 /// Execute all checks against a JSON value, producing `Issue`s.
+///
+/// Fields with `*` segments are expanded first (see `expand_wildcards`).
 pub fn run_checks(checks: &[Check], json: &Json, path: &PathBuf, rule_id: &str) -> Vec<Issue> {
     let mut issues = Vec::new();
     // Cache compiled regex per unique pattern to avoid recompilation within a run
-    let mut re_cache: HashMap<String, Regex> = HashMap::new();
-    for chk in checks.iter().cloned() {
+    let mut re_cache: HashMap<(String, String), Regex> = HashMap::new();
+    for chk in expand_wildcards(checks, json) {
+        if chk.is_off() {
+            continue;
+        }
+        let _span = crate::profile::span("check", || chk.kind());
+        let code = chk.code().to_string();
         match chk {
             Check::Required {
                 fields,
                 message,
+                messages,
                 level,
+                ..
             } => {
                 let sev = level.unwrap_or_else(|| "error".to_string());
                 for f in fields {
                     let missing = get_json_path(json, &f).is_none();
                     if missing {
                         let norm = f.trim_start_matches('$').trim_start_matches('.');
-                        let msg = message
-                            .clone()
+                        // Per-field message (keyed as written or normalized) wins over shared one
+                        let per_field = messages
+                            .as_ref()
+                            .and_then(|m| m.get(&f).or_else(|| m.get(norm)))
+                            .cloned();
+                        let msg = per_field
+                            .or_else(|| message.clone())
                             .unwrap_or_else(|| {
                                 "Field '{{field}}' is required at $.{{field}}".to_string()
                             })
@@ -39,39 +350,117 @@ pub fn run_checks(checks: &[Check], json: &Json, path: &PathBuf, rule_id: &str)
                             file: rel_to_wd(path),
                             rule: rule_id.to_string(),
                             severity: sev.clone(),
+                            fixable: false,
                             path: format!(
                                 "$.{}",
                                 f.trim_start_matches('$').trim_start_matches('.')
                             ),
                             message: msg,
+                            code: Some(code.clone()),
                         });
                     }
                 }
             }
+            Check::Forbidden {
+                fields,
+                message,
+                level,
+                ..
+            } => {
+                let sev = level.unwrap_or_else(|| "error".to_string());
+                for f in fields {
+                    if get_json_path(json, &f).is_none() {
+                        continue;
+                    }
+                    let norm = f.trim_start_matches('$').trim_start_matches('.');
+                    let msg = message
+                        .clone()
+                        .unwrap_or_else(|| {
+                            "Field '{{field}}' is not allowed at {{path}}".to_string()
+                        })
+                        .replace("{{field}}", norm)
+                        .replace("{{path}}", &format!("$.{}", norm));
+                    issues.push(Issue {
+                        file: rel_to_wd(path),
+                        rule: rule_id.to_string(),
+                        severity: sev.clone(),
+                        fixable: false,
+                        path: format!("$.{}", norm),
+                        message: msg,
+                        code: Some(code.clone()),
+                    });
+                }
+            }
+            Check::NonEmpty {
+                fields,
+                message,
+                level,
+                ..
+            } => {
+                let sev = level.unwrap_or_else(|| "error".to_string());
+                for f in fields {
+                    let blank = get_json_path(json, &f)
+                        .and_then(|v| v.as_str())
+                        .is_some_and(|s| s.trim().is_empty());
+                    if !blank {
+                        continue;
+                    }
+                    let norm = f.trim_start_matches('$').trim_start_matches('.');
+                    let msg = message
+                        .clone()
+                        .unwrap_or_else(|| {
+                            "Field '{{field}}' must not be empty at {{path}}".to_string()
+                        })
+                        .replace("{{field}}", norm)
+                        .replace("{{path}}", &format!("$.{}", norm));
+                    issues.push(Issue {
+                        file: rel_to_wd(path),
+                        rule: rule_id.to_string(),
+                        severity: sev.clone(),
+                        fixable: false,
+                        path: format!("$.{}", norm),
+                        message: msg,
+                        code: Some(code.clone()),
+                    });
+                }
+            }
             Check::Type {
                 fields,
                 message,
                 level,
+                ..
             } => {
                 let sev = level.unwrap_or_else(|| "error".to_string());
-                let base = message
-                    .clone()
-                    .unwrap_or_else(|| "Expected {{kind}} at $.{{path}}".to_string());
 
                 // Recommended path->kind checks
                 for (p, kind) in fields.iter() {
                     if let Some(v) = get_json_path(json, p) {
                         if !is_type(v, kind) {
                             let norm = p.trim_start_matches('$').trim_start_matches('.');
+                            // Sign-constrained kinds default to showing the offending value
+                            let base = message.clone().unwrap_or_else(|| {
+                                let signed = kind.split('|').any(|k| {
+                                    let k = k.trim();
+                                    k.starts_with("positive-") || k.starts_with("nonnegative-")
+                                });
+                                if signed {
+                                    "Expected {{kind}} at {{path}}, got {{value}}".to_string()
+                                } else {
+                                    "Expected {{kind}} at {{path}}".to_string()
+                                }
+                            });
                             issues.push(Issue {
                                 file: rel_to_wd(path),
                                 rule: rule_id.to_string(),
                                 severity: sev.clone(),
+                                fixable: false,
                                 path: format!("$.{}", norm),
                                 message: base
                                     .replace("{{kind}}", kind)
                                     .replace("{{path}}", &format!("$.{}", norm))
-                                    .replace("{{actual}}", json_kind(v)),
+                                    .replace("{{actual}}", json_kind(v))
+                                    .replace("{{value}}", &v.to_string()),
+                                code: Some(code.clone()),
                             });
                         }
                     }
@@ -80,12 +469,15 @@ pub fn run_checks(checks: &[Check], json: &Json, path: &PathBuf, rule_id: &str)
             Check::Const {
                 field,
                 value,
+                ignore_case,
                 message,
                 level,
+                ..
             } => {
                 let sev = level.unwrap_or_else(|| "error".to_string());
                 let got = get_json_path(json, &field);
-                if got != Some(&value) {
+                let fold = ignore_case.unwrap_or(false);
+                if !got.is_some_and(|g| json_eq(g, &value, fold)) {
                     let norm = field.trim_start_matches('$').trim_start_matches('.');
                     let msg = message
                         .clone()
@@ -101,26 +493,29 @@ pub fn run_checks(checks: &[Check], json: &Json, path: &PathBuf, rule_id: &str)
                         file: rel_to_wd(path),
                         rule: rule_id.to_string(),
                         severity: sev,
+                        fixable: true,
                         path: format!(
                             "$.{}",
                             field.trim_start_matches('$').trim_start_matches('.')
                         ),
                         message: msg,
+                        code: Some(code.clone()),
                     });
                 }
             }
             Check::Pattern {
                 field,
                 regex,
+                flags,
                 message,
                 level,
+                ..
             } => {
                 let sev = level.unwrap_or_else(|| "error".to_string());
                 if let Some(v) = get_json_path(json, &field) {
                     if let Some(s) = v.as_str() {
-                        let re = re_cache.entry(regex.clone()).or_insert_with(|| {
-                            Regex::new(&regex).unwrap_or_else(|_| Regex::new("^$").unwrap())
-                        });
+                        let re =
+                            cached_regex(&mut re_cache, &regex, flags.as_deref().unwrap_or(""));
                         if !re.is_match(s) {
                             let norm = field.trim_start_matches('$').trim_start_matches('.');
                             let msg = message
@@ -133,11 +528,13 @@ pub fn run_checks(checks: &[Check], json: &Json, path: &PathBuf, rule_id: &str)
                                 file: rel_to_wd(path),
                                 rule: rule_id.to_string(),
                                 severity: sev,
+                                fixable: false,
                                 path: format!(
                                     "$.{}",
                                     field.trim_start_matches('$').trim_start_matches('.')
                                 ),
                                 message: msg,
+                                code: Some(code.clone()),
                             });
                         }
                     }
@@ -146,12 +543,39 @@ pub fn run_checks(checks: &[Check], json: &Json, path: &PathBuf, rule_id: &str)
             Check::Enum {
                 field,
                 values,
+                deprecated,
+                prefer,
+                ignore_case,
                 message,
                 level,
+                ..
             } => {
                 let sev = level.unwrap_or_else(|| "error".to_string());
+                let fold = ignore_case.unwrap_or(false);
                 if let Some(actual) = get_json_path(json, &field) {
-                    if !values.iter().any(|v| v == actual) {
+                    if deprecated.iter().any(|v| json_eq(actual, v, fold)) {
+                        let norm = field.trim_start_matches('$').trim_start_matches('.');
+                        let key = actual
+                            .as_str()
+                            .map(String::from)
+                            .unwrap_or_else(|| actual.to_string());
+                        let msg = match prefer.get(&key) {
+                            Some(p) => format!(
+                                "Value {} at $.{} is deprecated; prefer {}",
+                                actual, norm, p
+                            ),
+                            None => format!("Value {} at $.{} is deprecated", actual, norm),
+                        };
+                        issues.push(Issue {
+                            file: rel_to_wd(path),
+                            rule: rule_id.to_string(),
+                            severity: "warn".to_string(),
+                            fixable: false,
+                            path: format!("$.{}", norm),
+                            message: msg,
+                            code: Some(code.clone()),
+                        });
+                    } else if !values.iter().any(|v| json_eq(actual, v, fold)) {
                         let norm = field.trim_start_matches('$').trim_start_matches('.');
                         let msg = message
                             .clone()
@@ -163,11 +587,13 @@ pub fn run_checks(checks: &[Check], json: &Json, path: &PathBuf, rule_id: &str)
                             file: rel_to_wd(path),
                             rule: rule_id.to_string(),
                             severity: sev,
+                            fixable: false,
                             path: format!(
                                 "$.{}",
                                 field.trim_start_matches('$').trim_start_matches('.')
                             ),
                             message: msg,
+                            code: Some(code.clone()),
                         });
                     }
                 }
@@ -177,16 +603,17 @@ pub fn run_checks(checks: &[Check], json: &Json, path: &PathBuf, rule_id: &str)
                 min,
                 message,
                 level,
+                ..
             } => {
                 let sev = level.unwrap_or_else(|| "error".to_string());
                 if let Some(v) = get_json_path(json, &field) {
-                    if let Some(s) = v.as_str() {
-                        if s.len() < min {
+                    if let Some((len, noun)) = measure_length(v) {
+                        if len < min {
                             let msg = message
                                 .clone()
-                                .unwrap_or_else(|| "String shorter than minimum".to_string())
+                                .unwrap_or_else(|| format!("{} shorter than minimum", noun))
                                 .replace("{{expected}}", &min.to_string())
-                                .replace("{{actual}}", &s.len().to_string())
+                                .replace("{{actual}}", &len.to_string())
                                 .replace(
                                     "{{path}}",
                                     &format!(
@@ -198,11 +625,13 @@ pub fn run_checks(checks: &[Check], json: &Json, path: &PathBuf, rule_id: &str)
                                 file: rel_to_wd(path),
                                 rule: rule_id.to_string(),
                                 severity: sev,
+                                fixable: false,
                                 path: format!(
                                     "$.{}",
                                     field.trim_start_matches('$').trim_start_matches('.')
                                 ),
                                 message: msg,
+                                code: Some(code.clone()),
                             });
                         }
                     }
@@ -213,16 +642,17 @@ pub fn run_checks(checks: &[Check], json: &Json, path: &PathBuf, rule_id: &str)
                 max,
                 message,
                 level,
+                ..
             } => {
                 let sev = level.unwrap_or_else(|| "error".to_string());
                 if let Some(v) = get_json_path(json, &field) {
-                    if let Some(s) = v.as_str() {
-                        if s.len() > max {
+                    if let Some((len, noun)) = measure_length(v) {
+                        if len > max {
                             let msg = message
                                 .clone()
-                                .unwrap_or_else(|| "String longer than maximum".to_string())
+                                .unwrap_or_else(|| format!("{} longer than maximum", noun))
                                 .replace("{{expected}}", &max.to_string())
-                                .replace("{{actual}}", &s.len().to_string())
+                                .replace("{{actual}}", &len.to_string())
                                 .replace(
                                     "{{path}}",
                                     &format!(
@@ -234,350 +664,2827 @@ pub fn run_checks(checks: &[Check], json: &Json, path: &PathBuf, rule_id: &str)
                                 file: rel_to_wd(path),
                                 rule: rule_id.to_string(),
                                 severity: sev,
+                                fixable: false,
                                 path: format!(
                                     "$.{}",
                                     field.trim_start_matches('$').trim_start_matches('.')
                                 ),
                                 message: msg,
+                                code: Some(code.clone()),
                             });
                         }
                     }
                 }
             }
-        }
-    }
-    issues
-}
-
-fn is_type(v: &Json, kind: &str) -> bool {
-    match kind {
-        "string" => v.is_string(),
-        "number" => v.is_number(),
-        "integer" => v.as_i64().is_some(),
-        "boolean" => v.is_boolean(),
-        "array" => v.is_array(),
-        "object" => v.is_object(),
-        "null" => v.is_null(),
-        _ => false,
-    }
-}
-
-fn json_kind(v: &Json) -> &'static str {
-    if v.is_string() {
-        "string"
-    } else if v.is_boolean() {
-        "boolean"
-    } else if v.is_array() {
-        "array"
-    } else if v.is_object() {
-        "object"
-    } else if v.is_null() {
-        "null"
-    } else if v.as_i64().is_some() {
-        "integer"
-    } else if v.is_number() {
-        "number"
-    } else {
-        "unknown"
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde_json::json;
-
-    #[test]
-    fn test_run_checks_various_and_nested() {
-        let json = json!({
-            "name": 123,
-            "version": "1.0.0",
-            "nested": { "x": "abc" },
-            "choice": "gamma",
-            "short": "a",
-            "long": "abcdef"
-        });
-        let path = PathBuf::from("package.json");
-        let checks = vec![
-            Check::Required {
-                fields: vec!["nested.x".into(), "missing.field".into()],
-                message: None,
-                level: None,
-            },
-            Check::Type {
-                fields: vec![
-                    ("name".into(), "string".into()),
-                    ("version".into(), "string".into()),
-                ]
-                .into_iter()
-                .collect(),
-                message: None,
-                level: None,
-            },
-            Check::Const {
-                field: "version".into(),
-                value: json!("2.0.0"),
-                message: None,
-                level: None,
-            },
-            Check::Pattern {
-                field: "nested.x".into(),
-                regex: "^xyz$".into(),
-                message: None,
-                level: None,
-            },
-            Check::Enum {
-                field: "choice".into(),
-                values: vec![json!("alpha"), json!("beta")],
-                message: None,
-                level: None,
-            },
-            Check::MinLength {
-                field: "short".into(),
-                min: 2,
-                message: None,
-                level: None,
-            },
-            Check::MaxLength {
-                field: "long".into(),
-                max: 5,
-                message: None,
-                level: None,
-            },
-        ];
-        let issues = run_checks(&checks, &json, &path, "t");
-        // Expect errors for: required(missing.field), type(name not string), const(version), pattern(nested.x), enum(choice), minLength(short), maxLength(long)
-        assert!(issues.iter().any(|i| i.path == "$.missing.field"));
-        assert!(issues.iter().any(|i| i.path == "$.name"));
-        assert!(issues.iter().any(|i| i.path == "$.version"));
-        assert!(issues.iter().any(|i| i.path == "$.nested.x"));
-        assert!(issues.iter().any(|i| i.path == "$.choice"));
-        assert!(issues.iter().any(|i| i.path == "$.short"));
-        assert!(issues.iter().any(|i| i.path == "$.long"));
-    }
-
-    #[test]
-    fn test_type_fields_all_kinds_match() {
-        let json = json!({
-            "s": "str",
-            "n": 1.5,
-            "i": 2,
-            "b": true,
-            "a": [1,2,3],
-            "o": {"k":"v"},
-            "z": null
-        });
-        let path = PathBuf::from("file.json");
-        let mut fields = HashMap::new();
-        fields.insert("s".into(), "string".into());
-        fields.insert("n".into(), "number".into());
-        fields.insert("i".into(), "integer".into());
-        fields.insert("b".into(), "boolean".into());
-        fields.insert("a".into(), "array".into());
-        fields.insert("o".into(), "object".into());
-        fields.insert("z".into(), "null".into());
-        let checks = vec![Check::Type {
-            fields,
-            message: None,
-            level: None,
-        }];
-        let issues = run_checks(&checks, &json, &path, "rule");
-        assert!(issues.is_empty());
-    }
-
-    #[test]
-    fn test_type_fields_all_kinds_mismatch() {
+            Check::MinItems {
+                field,
+                min,
+                message,
+                level,
+                ..
+            } => {
+                let count = get_json_path(json, &field).and_then(|v| v.as_array().map(|a| a.len()));
+                if let Some(n) = count.filter(|n| *n < min) {
+                    let norm = field.trim_start_matches('$').trim_start_matches('.');
+                    let msg = message
+                        .clone()
+                        .unwrap_or_else(|| {
+                            "Array at {{path}} must have at least {{expected}} items, got {{actual}}"
+                                .to_string()
+                        })
+                        .replace("{{expected}}", &min.to_string())
+                        .replace("{{actual}}", &n.to_string())
+                        .replace("{{path}}", &format!("$.{}", norm));
+                    issues.push(Issue {
+                        file: rel_to_wd(path),
+                        rule: rule_id.to_string(),
+                        severity: level.unwrap_or_else(|| "error".to_string()),
+                        fixable: false,
+                        path: format!("$.{}", norm),
+                        message: msg,
+                        code: Some(code.clone()),
+                    });
+                }
+            }
+            Check::MaxItems {
+                field,
+                max,
+                message,
+                level,
+                ..
+            } => {
+                let count = get_json_path(json, &field).and_then(|v| v.as_array().map(|a| a.len()));
+                if let Some(n) = count.filter(|n| *n > max) {
+                    let norm = field.trim_start_matches('$').trim_start_matches('.');
+                    let msg = message
+                        .clone()
+                        .unwrap_or_else(|| {
+                            "Array at {{path}} must have at most {{expected}} items, got {{actual}}"
+                                .to_string()
+                        })
+                        .replace("{{expected}}", &max.to_string())
+                        .replace("{{actual}}", &n.to_string())
+                        .replace("{{path}}", &format!("$.{}", norm));
+                    issues.push(Issue {
+                        file: rel_to_wd(path),
+                        rule: rule_id.to_string(),
+                        severity: level.unwrap_or_else(|| "error".to_string()),
+                        fixable: false,
+                        path: format!("$.{}", norm),
+                        message: msg,
+                        code: Some(code.clone()),
+                    });
+                }
+            }
+            Check::UniqueItems {
+                field,
+                message,
+                level,
+                ..
+            } => {
+                let items = get_json_path(json, &field).and_then(|v| v.as_array());
+                // First element that equals an earlier one
+                let dup = items.and_then(|a| {
+                    a.iter()
+                        .enumerate()
+                        .find(|(i, v)| a[..*i].contains(v))
+                        .map(|(_, v)| v)
+                });
+                if let Some(v) = dup {
+                    let norm = field.trim_start_matches('$').trim_start_matches('.');
+                    let actual = v
+                        .as_str()
+                        .map(String::from)
+                        .unwrap_or_else(|| v.to_string());
+                    let msg = message
+                        .clone()
+                        .unwrap_or_else(|| "Duplicate item at {{path}}: {{actual}}".to_string())
+                        .replace("{{actual}}", &actual)
+                        .replace("{{path}}", &format!("$.{}", norm));
+                    issues.push(Issue {
+                        file: rel_to_wd(path),
+                        rule: rule_id.to_string(),
+                        severity: level.unwrap_or_else(|| "error".to_string()),
+                        fixable: false,
+                        path: format!("$.{}", norm),
+                        message: msg,
+                        code: Some(code.clone()),
+                    });
+                }
+            }
+            Check::ObjectValues {
+                field,
+                value_kind: kind,
+                message,
+                level,
+                ..
+            } => {
+                let Some(obj) = get_json_path(json, &field).and_then(|v| v.as_object()) else {
+                    continue;
+                };
+                let norm = field.trim_start_matches('$').trim_start_matches('.');
+                let sev = level.unwrap_or_else(|| "error".to_string());
+                for (key, v) in obj {
+                    if is_type(v, &kind) {
+                        continue;
+                    }
+                    let at = child_path(norm, key);
+                    let msg = message
+                        .clone()
+                        .unwrap_or_else(|| {
+                            "Expected {{kind}} at {{path}}, got {{actual}}".to_string()
+                        })
+                        .replace("{{kind}}", &kind)
+                        .replace("{{path}}", &at)
+                        .replace("{{actual}}", json_kind(v))
+                        .replace("{{value}}", &v.to_string());
+                    issues.push(Issue {
+                        file: rel_to_wd(path),
+                        rule: rule_id.to_string(),
+                        severity: sev.clone(),
+                        fixable: false,
+                        path: at,
+                        message: msg,
+                        code: Some(code.clone()),
+                    });
+                }
+            }
+            Check::Format {
+                field,
+                format,
+                message,
+                level,
+                ..
+            } => {
+                let Some(raw) = get_json_path(json, &field).and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                match matches_format(raw, &format) {
+                    Some(true) => {}
+                    Some(false) => {
+                        let norm = field.trim_start_matches('$').trim_start_matches('.');
+                        let msg = message
+                            .clone()
+                            .unwrap_or_else(|| {
+                                "Value at {{path}} is not a valid {{expected}}: {{actual}}"
+                                    .to_string()
+                            })
+                            .replace("{{expected}}", &format)
+                            .replace("{{actual}}", raw)
+                            .replace("{{path}}", &format!("$.{}", norm));
+                        issues.push(Issue {
+                            file: rel_to_wd(path),
+                            rule: rule_id.to_string(),
+                            severity: level.unwrap_or_else(|| "error".to_string()),
+                            fixable: false,
+                            path: format!("$.{}", norm),
+                            message: msg,
+                            code: Some(code.clone()),
+                        });
+                    }
+                    None => warn_unknown_format(&format),
+                }
+            }
+            Check::Url {
+                field,
+                schemes,
+                hosts,
+                message,
+                level,
+                ..
+            } => {
+                if let Some(raw) = get_json_path(json, &field).and_then(|v| v.as_str()) {
+                    let norm = field.trim_start_matches('$').trim_start_matches('.');
+                    let schemes = if schemes.is_empty() {
+                        vec!["https".to_string()]
+                    } else {
+                        schemes
+                    };
+                    // Default message and {{expected}} for the first failing rule
+                    let failure = match url::Url::parse(raw) {
+                        Err(e) => Some((
+                            format!("Invalid URL at {{{{path}}}}: {}", e),
+                            "a valid URL".to_string(),
+                        )),
+                        Ok(u) if !schemes.iter().any(|s| s.eq_ignore_ascii_case(u.scheme())) => {
+                            Some((
+                                "URL at {{path}} must use scheme {{expected}}, got {{actual}}"
+                                    .to_string(),
+                                schemes.join("|"),
+                            ))
+                        }
+                        Ok(u)
+                            if !hosts.is_empty()
+                                && !u.host_str().is_some_and(|h| {
+                                    hosts.iter().any(|a| a.eq_ignore_ascii_case(h))
+                                }) =>
+                        {
+                            Some((
+                                "URL at {{path}} must be on host {{expected}}, got {{actual}}"
+                                    .to_string(),
+                                hosts.join("|"),
+                            ))
+                        }
+                        Ok(_) => None,
+                    };
+                    if let Some((default_msg, expected)) = failure {
+                        let msg = message
+                            .clone()
+                            .unwrap_or(default_msg)
+                            .replace("{{expected}}", &expected)
+                            .replace("{{actual}}", raw)
+                            .replace("{{path}}", &format!("$.{}", norm));
+                        issues.push(Issue {
+                            file: rel_to_wd(path),
+                            rule: rule_id.to_string(),
+                            severity: level.unwrap_or_else(|| "error".to_string()),
+                            fixable: false,
+                            path: format!("$.{}", norm),
+                            message: msg,
+                            code: Some(code.clone()),
+                        });
+                    }
+                }
+            }
+            Check::Minimum {
+                field,
+                min,
+                exclusive,
+                message,
+                level,
+                ..
+            } => {
+                let strict = exclusive.unwrap_or(false);
+                if let Some(v) = get_json_path(json, &field) {
+                    // Non-numbers are left to `type` checks
+                    if let Some(n) = v.as_f64() {
+                        if n < min || (strict && n == min) {
+                            let norm = field.trim_start_matches('$').trim_start_matches('.');
+                            let op = if strict { ">" } else { ">=" };
+                            let msg = message
+                                .clone()
+                                .unwrap_or_else(|| {
+                                    format!("Value at {{{{path}}}} must be {} {{{{expected}}}}, got {{{{actual}}}}", op)
+                                })
+                                .replace("{{expected}}", &min.to_string())
+                                .replace("{{actual}}", &v.to_string())
+                                .replace("{{path}}", &format!("$.{}", norm));
+                            issues.push(Issue {
+                                file: rel_to_wd(path),
+                                rule: rule_id.to_string(),
+                                severity: level.unwrap_or_else(|| "error".to_string()),
+                                fixable: false,
+                                path: format!("$.{}", norm),
+                                message: msg,
+                                code: Some(code.clone()),
+                            });
+                        }
+                    }
+                }
+            }
+            Check::Maximum {
+                field,
+                max,
+                exclusive,
+                message,
+                level,
+                ..
+            } => {
+                let strict = exclusive.unwrap_or(false);
+                if let Some(v) = get_json_path(json, &field) {
+                    // Non-numbers are left to `type` checks
+                    if let Some(n) = v.as_f64() {
+                        if n > max || (strict && n == max) {
+                            let norm = field.trim_start_matches('$').trim_start_matches('.');
+                            let op = if strict { "<" } else { "<=" };
+                            let msg = message
+                                .clone()
+                                .unwrap_or_else(|| {
+                                    format!("Value at {{{{path}}}} must be {} {{{{expected}}}}, got {{{{actual}}}}", op)
+                                })
+                                .replace("{{expected}}", &max.to_string())
+                                .replace("{{actual}}", &v.to_string())
+                                .replace("{{path}}", &format!("$.{}", norm));
+                            issues.push(Issue {
+                                file: rel_to_wd(path),
+                                rule: rule_id.to_string(),
+                                severity: level.unwrap_or_else(|| "error".to_string()),
+                                fixable: false,
+                                path: format!("$.{}", norm),
+                                message: msg,
+                                code: Some(code.clone()),
+                            });
+                        }
+                    }
+                }
+            }
+            Check::MultipleOf {
+                field,
+                divisor,
+                message,
+                level,
+                ..
+            } => {
+                // Non-numbers and a zero divisor are skipped rather than reported
+                if let Some(n) = get_json_path(json, &field).and_then(|v| v.as_f64()) {
+                    if divisor != 0.0 && !is_multiple_of(n, divisor) {
+                        let norm = field.trim_start_matches('$').trim_start_matches('.');
+                        let msg = message
+                            .clone()
+                            .unwrap_or_else(|| {
+                                "Value at {{path}} must be a multiple of {{expected}}, got {{actual}}"
+                                    .to_string()
+                            })
+                            .replace("{{expected}}", &divisor.to_string())
+                            .replace("{{actual}}", &n.to_string())
+                            .replace("{{path}}", &format!("$.{}", norm));
+                        issues.push(Issue {
+                            file: rel_to_wd(path),
+                            rule: rule_id.to_string(),
+                            severity: level.unwrap_or_else(|| "error".to_string()),
+                            fixable: false,
+                            path: format!("$.{}", norm),
+                            message: msg,
+                            code: Some(code.clone()),
+                        });
+                    }
+                }
+            }
+            Check::NoAdditionalProperties {
+                field,
+                allowed,
+                pattern_properties,
+                message,
+                level,
+                ..
+            } => {
+                let sev = level.unwrap_or_else(|| "error".to_string());
+                if let Some(Json::Object(obj)) = get_json_path(json, &field) {
+                    let norm = field.trim_start_matches('$').trim_start_matches('.');
+                    for key in obj.keys() {
+                        if allowed.iter().any(|a| a == key) {
+                            continue;
+                        }
+                        let by_pattern = pattern_properties
+                            .iter()
+                            .any(|pat| cached_regex(&mut re_cache, pat, "").is_match(key));
+                        if by_pattern {
+                            continue;
+                        }
+                        let key_path = child_path(norm, key);
+                        let msg = message
+                            .clone()
+                            .unwrap_or_else(|| "Unknown property '{{key}}' at {{path}}".to_string())
+                            .replace("{{key}}", key)
+                            .replace("{{expected}}", &format!("{:?}", allowed))
+                            .replace("{{path}}", &key_path);
+                        issues.push(Issue {
+                            file: rel_to_wd(path),
+                            rule: rule_id.to_string(),
+                            severity: sev.clone(),
+                            fixable: false,
+                            path: key_path,
+                            message: msg,
+                            code: Some(code.clone()),
+                        });
+                    }
+                }
+            }
+            Check::PropertyNames {
+                field,
+                regex,
+                message,
+                level,
+                ..
+            } => {
+                let sev = level.unwrap_or_else(|| "error".to_string());
+                if let Some(Json::Object(obj)) = get_json_path(json, &field) {
+                    let norm = field.trim_start_matches('$').trim_start_matches('.');
+                    let re = cached_regex(&mut re_cache, &regex, "");
+                    for key in obj.keys().filter(|k| !re.is_match(k)) {
+                        let key_path = child_path(norm, key);
+                        let msg = message
+                            .clone()
+                            .unwrap_or_else(|| {
+                                "Property name '{{key}}' at {{path}} does not match {{pattern}}"
+                                    .to_string()
+                            })
+                            .replace("{{key}}", key)
+                            .replace("{{pattern}}", &regex)
+                            .replace("{{path}}", &key_path);
+                        issues.push(Issue {
+                            file: rel_to_wd(path),
+                            rule: rule_id.to_string(),
+                            severity: sev.clone(),
+                            fixable: false,
+                            path: key_path,
+                            message: msg,
+                            code: Some(code.clone()),
+                        });
+                    }
+                }
+            }
+            Check::Dependency {
+                if_field,
+                if_equals,
+                require,
+                message,
+                level,
+                ..
+            } => {
+                let trigger = get_json_path(json, &if_field);
+                let triggered = match (&if_equals, trigger) {
+                    (_, None) => false,
+                    (None, Some(_)) => true,
+                    (Some(want), Some(got)) => want == got,
+                };
+                if !triggered {
+                    continue;
+                }
+                let if_norm = if_field.trim_start_matches('$').trim_start_matches('.');
+                let sev = level.unwrap_or_else(|| "error".to_string());
+                for f in require {
+                    if get_json_path(json, &f).is_some() {
+                        continue;
+                    }
+                    let norm = f.trim_start_matches('$').trim_start_matches('.');
+                    let msg = message
+                        .clone()
+                        .unwrap_or_else(|| {
+                            "Field '{{field}}' is required at {{path}} when $.{{if_field}} is set"
+                                .to_string()
+                        })
+                        .replace("{{if_field}}", if_norm)
+                        .replace("{{field}}", norm)
+                        .replace("{{path}}", &format!("$.{}", norm));
+                    issues.push(Issue {
+                        file: rel_to_wd(path),
+                        rule: rule_id.to_string(),
+                        severity: sev.clone(),
+                        fixable: false,
+                        path: format!("$.{}", norm),
+                        message: msg,
+                        code: Some(code.clone()),
+                    });
+                }
+            }
+            // Cross-file; see `run_aggregate_checks`
+            Check::ConsistentField { .. } => {}
+            Check::Each {
+                field,
+                checks: nested,
+                message,
+                level,
+                ..
+            } => {
+                let norm = field.trim_start_matches('$').trim_start_matches('.');
+                match get_json_path(json, &field) {
+                    None => {}
+                    Some(Json::Array(items)) => {
+                        for (i, item) in items.iter().enumerate() {
+                            for mut is in run_checks(&nested, item, path, rule_id) {
+                                let rest = is.path.trim_start_matches('$').to_string();
+                                let at = format!("$.{}[{}]{}", norm, i, rest);
+                                is.message = reroot_message(&is.message, &is.path, &at);
+                                is.path = at;
+                                // `--fix` only plans top-level edits
+                                is.fixable = false;
+                                issues.push(is);
+                            }
+                        }
+                    }
+                    Some(v) => {
+                        let msg = message
+                            .clone()
+                            .unwrap_or_else(|| {
+                                "Expected array at {{path}}, got {{actual}}".to_string()
+                            })
+                            .replace("{{actual}}", json_kind(v))
+                            .replace("{{path}}", &format!("$.{}", norm));
+                        issues.push(Issue {
+                            file: rel_to_wd(path),
+                            rule: rule_id.to_string(),
+                            severity: level.unwrap_or_else(|| "error".to_string()),
+                            fixable: false,
+                            path: format!("$.{}", norm),
+                            message: msg,
+                            code: Some(code.clone()),
+                        });
+                    }
+                }
+            }
+            Check::AnyOf {
+                checks: alternatives,
+                message,
+                level,
+                ..
+            } => {
+                // Disabled alternatives neither pass nor fail the group
+                let active: Vec<&Check> = alternatives.iter().filter(|c| !c.is_off()).collect();
+                let mut reported: Vec<String> = Vec::new();
+                let mut passed = active.is_empty();
+                for alt in active.iter().copied() {
+                    let alt_issues = run_checks(std::slice::from_ref(alt), json, path, rule_id);
+                    if alt_issues.is_empty() {
+                        passed = true;
+                        break;
+                    }
+                    reported.extend(alt_issues.into_iter().map(|i| i.message));
+                }
+                if !passed {
+                    let msg = message
+                        .clone()
+                        .unwrap_or_else(|| {
+                            "None of {{count}} alternatives matched: {{details}}".to_string()
+                        })
+                        .replace("{{count}}", &active.len().to_string())
+                        .replace("{{details}}", &reported.join("; "));
+                    issues.push(Issue {
+                        file: rel_to_wd(path),
+                        rule: rule_id.to_string(),
+                        severity: level.unwrap_or_else(|| "error".to_string()),
+                        fixable: false,
+                        path: "$".into(),
+                        message: msg,
+                        code: Some(code.clone()),
+                    });
+                }
+            }
+            Check::AllOf {
+                checks: all,
+                message,
+                level,
+                ..
+            } => {
+                let all_issues = run_checks(&all, json, path, rule_id);
+                match message {
+                    Some(msg) if !all_issues.is_empty() => issues.push(Issue {
+                        file: rel_to_wd(path),
+                        rule: rule_id.to_string(),
+                        severity: level.unwrap_or_else(|| "error".to_string()),
+                        fixable: false,
+                        path: "$".into(),
+                        message: msg,
+                        code: Some(code.clone()),
+                    }),
+                    // `--fix` only plans top-level edits
+                    _ => issues.extend(all_issues.into_iter().map(|mut i| {
+                        i.fixable = false;
+                        i
+                    })),
+                }
+            }
+            Check::Not {
+                check: inner,
+                message,
+                level,
+                ..
+            } => {
+                // A disabled inner check, or one whose field is absent, says
+                // nothing about the document
+                let absent = single_field(&inner).is_some_and(|f| get_json_path(json, f).is_none());
+                if inner.is_off() || absent {
+                    continue;
+                }
+                let inner_issues =
+                    run_checks(std::slice::from_ref(inner.as_ref()), json, path, rule_id);
+                if inner_issues.is_empty() {
+                    let at = match single_field(&inner) {
+                        Some(f) => {
+                            format!("$.{}", f.trim_start_matches('$').trim_start_matches('.'))
+                        }
+                        None => "$".to_string(),
+                    };
+                    let msg = message
+                        .clone()
+                        .unwrap_or_else(|| {
+                            "Value at {{path}} must not satisfy '{{kind}}'".to_string()
+                        })
+                        .replace("{{kind}}", inner.kind())
+                        .replace("{{path}}", &at);
+                    issues.push(Issue {
+                        file: rel_to_wd(path),
+                        rule: rule_id.to_string(),
+                        severity: level.unwrap_or_else(|| "error".to_string()),
+                        fixable: false,
+                        path: at,
+                        message: msg,
+                        code: Some(code.clone()),
+                    });
+                }
+            }
+            Check::Discriminated {
+                discriminator,
+                variants,
+                message,
+                level,
+                ..
+            } => {
+                let tag = get_json_path(json, &discriminator).map(|v| {
+                    v.as_str()
+                        .map(String::from)
+                        .unwrap_or_else(|| v.to_string())
+                });
+                match tag.as_ref().and_then(|t| variants.get(t)) {
+                    // `--fix` only plans top-level edits
+                    Some(selected) => {
+                        let nested = run_checks(selected, json, path, rule_id);
+                        issues.extend(nested.into_iter().map(|mut i| {
+                            i.fixable = false;
+                            i
+                        }));
+                    }
+                    None => {
+                        let norm = discriminator
+                            .trim_start_matches('$')
+                            .trim_start_matches('.');
+                        let known: Vec<&str> = variants.keys().map(String::as_str).collect();
+                        let msg = message
+                            .clone()
+                            .unwrap_or_else(|| {
+                                "Unknown variant at {{path}}: {{actual}} (expected one of {{expected}})"
+                                    .to_string()
+                            })
+                            .replace("{{actual}}", tag.as_deref().unwrap_or("(missing)"))
+                            .replace("{{expected}}", &known.join(", "))
+                            .replace("{{path}}", &format!("$.{}", norm));
+                        issues.push(Issue {
+                            file: rel_to_wd(path),
+                            rule: rule_id.to_string(),
+                            severity: level.unwrap_or_else(|| "error".to_string()),
+                            fixable: false,
+                            path: format!("$.{}", norm),
+                            message: msg,
+                            code: Some(code.clone()),
+                        });
+                    }
+                }
+            }
+            Check::SemverGt {
+                field,
+                than_field,
+                message,
+                level,
+                ..
+            } => {
+                let sev = level.unwrap_or_else(|| "error".to_string());
+                let norm = field.trim_start_matches('$').trim_start_matches('.');
+                let than_norm = than_field.trim_start_matches('$').trim_start_matches('.');
+                let cur = get_json_path(json, &field).and_then(|v| v.as_str());
+                let prev = get_json_path(json, &than_field).and_then(|v| v.as_str());
+                if let (Some(cur), Some(prev)) = (cur, prev) {
+                    match (parse_semver(cur), parse_semver(prev)) {
+                        (Ok(a), Ok(b)) => {
+                            if a <= b {
+                                let msg = message
+                                    .clone()
+                                    .unwrap_or_else(|| {
+                                        "Version {{actual}} at {{path}} must be greater than {{expected}}"
+                                            .to_string()
+                                    })
+                                    .replace("{{expected}}", prev)
+                                    .replace("{{actual}}", cur)
+                                    .replace("{{path}}", &format!("$.{}", norm))
+                                    .replace("{{than}}", &format!("$.{}", than_norm));
+                                issues.push(Issue {
+                                    file: rel_to_wd(path),
+                                    rule: rule_id.to_string(),
+                                    severity: sev,
+                                    fixable: false,
+                                    path: format!("$.{}", norm),
+                                    message: msg,
+                                    code: Some(code.clone()),
+                                });
+                            }
+                        }
+                        (a, _) => {
+                            // Unparseable versions are reported at the offending path
+                            let (bad_path, bad_val) = if a.is_err() {
+                                (norm, cur)
+                            } else {
+                                (than_norm, prev)
+                            };
+                            issues.push(Issue {
+                                file: rel_to_wd(path),
+                                rule: rule_id.to_string(),
+                                severity: sev,
+                                fixable: false,
+                                path: format!("$.{}", bad_path),
+                                message: format!(
+                                    "Invalid semantic version '{}' at $.{}",
+                                    bad_val, bad_path
+                                ),
+                                code: Some(code.clone()),
+                            });
+                        }
+                    }
+                }
+            }
+            Check::SameAs {
+                field,
+                reference,
+                message,
+                level,
+                ..
+            } => {
+                let sev = level.unwrap_or_else(|| "error".to_string());
+                let norm = field.trim_start_matches('$').trim_start_matches('.');
+                let ref_norm = reference.trim_start_matches('$').trim_start_matches('.');
+                let actual = get_json_path(json, &field);
+                let expected = get_json_path(json, &reference);
+                // serde_json equality is deep; two missing paths agree
+                if actual != expected {
+                    let show =
+                        |v: Option<&Json>| v.map_or("missing".to_string(), |v| v.to_string());
+                    let msg = message
+                        .clone()
+                        .unwrap_or_else(|| {
+                            "Value at {{path}} ({{actual}}) must match {{reference}} ({{expected}})"
+                                .to_string()
+                        })
+                        .replace("{{path}}", &format!("$.{}", norm))
+                        .replace("{{reference}}", &format!("$.{}", ref_norm))
+                        .replace("{{actual}}", &show(actual))
+                        .replace("{{expected}}", &show(expected));
+                    issues.push(Issue {
+                        file: rel_to_wd(path),
+                        rule: rule_id.to_string(),
+                        severity: sev,
+                        fixable: false,
+                        path: format!("$.{}", norm),
+                        message: msg,
+                        code: Some(code.clone()),
+                    });
+                }
+            }
+            Check::PathExists {
+                field,
+                base,
+                message,
+                level,
+                ..
+            } => {
+                let sev = level.unwrap_or_else(|| "error".to_string());
+                let norm = field.trim_start_matches('$').trim_start_matches('.');
+                let dir = path.parent().unwrap_or_else(|| Path::new("."));
+                let dir = match &base {
+                    Some(b) => dir.join(b),
+                    None => dir.to_path_buf(),
+                };
+                // Arrays and objects (e.g. package.json `bin`) check each entry
+                let entries: Vec<(String, &Json)> = match get_json_path(json, &field) {
+                    None => Vec::new(),
+                    Some(Json::Array(items)) => items
+                        .iter()
+                        .enumerate()
+                        .map(|(i, v)| (format!("$.{}[{}]", norm, i), v))
+                        .collect(),
+                    Some(Json::Object(map)) => {
+                        map.iter().map(|(k, v)| (child_path(norm, k), v)).collect()
+                    }
+                    Some(v) => vec![(format!("$.{}", norm), v)],
+                };
+                for (at, v) in entries {
+                    let Some(rel) = v.as_str() else {
+                        issues.push(Issue {
+                            file: rel_to_wd(path),
+                            rule: rule_id.to_string(),
+                            severity: sev.clone(),
+                            fixable: false,
+                            path: at.clone(),
+                            message: format!("Expected a path string at {}, got {}", at, v),
+                            code: Some(code.clone()),
+                        });
+                        continue;
+                    };
+                    if !dir.join(rel).exists() {
+                        let msg = message
+                            .clone()
+                            .unwrap_or_else(|| {
+                                "Path '{{value}}' at {{path}} does not exist".to_string()
+                            })
+                            .replace("{{path}}", &at)
+                            .replace("{{value}}", rel);
+                        issues.push(Issue {
+                            file: rel_to_wd(path),
+                            rule: rule_id.to_string(),
+                            severity: sev.clone(),
+                            fixable: false,
+                            path: at,
+                            message: msg,
+                            code: Some(code.clone()),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    issues
+}
+
+/// Replace the nested path `old` with `new` in an `each` item's message.
+/// Only whole paths are replaced, so `$` never matches the start of `$.a`.
+fn reroot_message(msg: &str, old: &str, new: &str) -> String {
+    let continues = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '[' | '$');
+    let mut out = String::with_capacity(msg.len());
+    let mut last = 0;
+    for (i, _) in msg.match_indices(old) {
+        let end = i + old.len();
+        if i < last || msg[end..].starts_with(continues) {
+            continue;
+        }
+        out.push_str(&msg[last..i]);
+        out.push_str(new);
+        last = end;
+    }
+    out.push_str(&msg[last..]);
+    out
+}
+
+/// Length used by `minLength`/`maxLength`: Unicode scalar values (`char`s)
+/// for strings, element count for arrays, key count for objects. Other values
+/// are not measured.
+fn measure_length(v: &Json) -> Option<(usize, &'static str)> {
+    match v {
+        Json::String(s) => Some((s.chars().count(), "String")),
+        Json::Array(a) => Some((a.len(), "Array")),
+        Json::Object(o) => Some((o.len(), "Object")),
+        _ => None,
+    }
+}
+
+/// Whether `n / divisor` is a whole number, within a relative epsilon so
+/// fractional divisors like `0.1` are not tripped up by float rounding.
+fn is_multiple_of(n: f64, divisor: f64) -> bool {
+    let q = n / divisor;
+    (q - q.round()).abs() <= 1e-9 * q.abs().max(1.0)
+}
+
+/// Fetch a compiled regex from the per-run cache, compiling on first use.
+/// Invalid patterns fall back to `^$` so a bad policy never panics; unknown
+/// flag characters fall back to compiling without flags.
+fn cached_regex<'a>(
+    cache: &'a mut HashMap<(String, String), Regex>,
+    pattern: &str,
+    flags: &str,
+) -> &'a Regex {
+    cache
+        .entry((pattern.to_string(), flags.to_string()))
+        .or_insert_with(|| {
+            let mut b = RegexBuilder::new(pattern);
+            if flags.chars().all(|c| "imsx".contains(c)) {
+                b.case_insensitive(flags.contains('i'))
+                    .multi_line(flags.contains('m'))
+                    .dot_matches_new_line(flags.contains('s'))
+                    .ignore_whitespace(flags.contains('x'));
+            }
+            b.build().unwrap_or_else(|_| Regex::new("^$").unwrap())
+        })
+}
+
+/// Whether any check needs every matched file at once (`run_aggregate_checks`).
+pub fn has_aggregate_checks(checks: &[Check]) -> bool {
+    checks
+        .iter()
+        .any(|c| matches!(c, Check::ConsistentField { .. }))
+}
+
+/// Evaluate cross-file checks over all documents matched by one rule.
+///
+/// For `consistentField`, the most common value among files that define the
+/// field is taken as expected (ties go to the first file in path order) and
+/// every file holding another value gets an issue.
+pub fn run_aggregate_checks(
+    checks: &[Check],
+    docs: &[(PathBuf, Json)],
+    rule_id: &str,
+) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for chk in checks.iter().filter(|c| !c.is_off()) {
+        let Check::ConsistentField {
+            field,
+            message,
+            level,
+            ..
+        } = chk
+        else {
+            continue;
+        };
+        let present: Vec<(&PathBuf, &Json)> = docs
+            .iter()
+            .filter_map(|(p, j)| get_json_path(j, field).map(|v| (p, v)))
+            .collect();
+        // Distinct values with their counts, in first-seen order
+        let mut tally: Vec<(&Json, usize)> = Vec::new();
+        for (_, v) in &present {
+            match tally.iter_mut().find(|(t, _)| t == v) {
+                Some((_, n)) => *n += 1,
+                None => tally.push((v, 1)),
+            }
+        }
+        let Some(max) = tally.iter().map(|(_, n)| *n).max() else {
+            continue;
+        };
+        let Some(&(expected, _)) = tally.iter().find(|(_, n)| *n == max) else {
+            continue;
+        };
+        let norm = field.trim_start_matches('$').trim_start_matches('.');
+        for (p, v) in present.iter().filter(|(_, v)| *v != expected) {
+            let msg = message
+                .clone()
+                .unwrap_or_else(|| {
+                    "Value at {{path}} is inconsistent across files: expected {{expected}}, got {{actual}}"
+                        .to_string()
+                })
+                .replace("{{expected}}", &expected.to_string())
+                .replace("{{actual}}", &v.to_string())
+                .replace("{{path}}", &format!("$.{}", norm));
+            issues.push(Issue {
+                file: rel_to_wd(p),
+                rule: rule_id.to_string(),
+                severity: level.clone().unwrap_or_else(|| "error".to_string()),
+                fixable: false,
+                path: format!("$.{}", norm),
+                message: msg,
+                code: Some(chk.code().to_string()),
+            });
+        }
+    }
+    issues
+}
+
+/// Equality for `const`/`enum`; with `ignore_case`, strings compare with
+/// ASCII case folding while other values must match exactly.
+pub fn json_eq(a: &Json, b: &Json, ignore_case: bool) -> bool {
+    match (a, b) {
+        (Json::String(x), Json::String(y)) if ignore_case => x.eq_ignore_ascii_case(y),
+        _ => a == b,
+    }
+}
+
+/// Validate `s` against a named format; `None` when the name is unknown.
+fn matches_format(s: &str, format: &str) -> Option<bool> {
+    let ok = match format {
+        "email" => {
+            static EMAIL: OnceLock<Regex> = OnceLock::new();
+            EMAIL
+                .get_or_init(|| Regex::new(r"^[^@\s]+@[^@\s.]+(\.[^@\s.]+)+$").unwrap())
+                .is_match(s)
+        }
+        "uri" => url::Url::parse(s).is_ok(),
+        "date" => is_date(s),
+        "date-time" => is_date_time(s),
+        "uuid" => {
+            static UUID: OnceLock<Regex> = OnceLock::new();
+            UUID.get_or_init(|| {
+                Regex::new(r"^[0-9a-fA-F]{8}-([0-9a-fA-F]{4}-){3}[0-9a-fA-F]{12}$").unwrap()
+            })
+            .is_match(s)
+        }
+        "semver" => semver::Version::parse(s).is_ok(),
+        _ => return None,
+    };
+    Some(ok)
+}
+
+/// Warn once per run for each unrecognized `format` name.
+fn warn_unknown_format(format: &str) {
+    static WARNED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    let mut seen = WARNED
+        .get_or_init(|| Mutex::new(HashSet::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if seen.insert(format.to_string()) {
+        crate::diag::warn(format!(
+            "Unknown format '{}' in format check; ignored",
+            format
+        ));
+    }
+}
+
+/// Parse a fixed-width run of ASCII digits.
+fn digits(s: &str) -> Option<u32> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// Full date `YYYY-MM-DD` with a real day-of-month (leap years included).
+pub fn is_date(s: &str) -> bool {
+    if s.len() != 10 || !s.is_ascii() || &s[4..5] != "-" || &s[7..8] != "-" {
+        return false;
+    }
+    let (Some(y), Some(m), Some(d)) = (digits(&s[0..4]), digits(&s[5..7]), digits(&s[8..10]))
+    else {
+        return false;
+    };
+    let leap = (y % 4 == 0 && y % 100 != 0) || y % 400 == 0;
+    let max_day = match m {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=max_day).contains(&d)
+}
+
+/// RFC 3339 date-time: `YYYY-MM-DDTHH:MM:SS[.frac](Z|±HH:MM)`.
+fn is_date_time(s: &str) -> bool {
+    if !s.is_ascii() || s.len() < 10 || !is_date(&s[..10]) {
+        return false;
+    }
+    let rest = &s[10..];
+    let Some(rest) = rest.strip_prefix(['T', 't']) else {
+        return false;
+    };
+    if rest.len() < 8 {
+        return false;
+    }
+    let (time, mut zone) = rest.split_at(8);
+    let ok_time = &time[2..3] == ":"
+        && &time[5..6] == ":"
+        && digits(&time[0..2]).is_some_and(|h| h < 24)
+        && digits(&time[3..5]).is_some_and(|m| m < 60)
+        // 60 allows a leap second
+        && digits(&time[6..8]).is_some_and(|s| s <= 60);
+    if !ok_time {
+        return false;
+    }
+    if let Some(frac) = zone.strip_prefix('.') {
+        let n = frac.bytes().take_while(|b| b.is_ascii_digit()).count();
+        if n == 0 {
+            return false;
+        }
+        zone = &frac[n..];
+    }
+    match zone {
+        "Z" | "z" => true,
+        _ => {
+            zone.len() == 6
+                && zone.starts_with(['+', '-'])
+                && &zone[3..4] == ":"
+                && digits(&zone[1..3]).is_some_and(|h| h < 24)
+                && digits(&zone[4..6]).is_some_and(|m| m < 60)
+        }
+    }
+}
+
+/// Parse a semantic version, tolerating a leading `v` as used in git tags.
+fn parse_semver(s: &str) -> Result<semver::Version, semver::Error> {
+    semver::Version::parse(s.trim().trim_start_matches('v'))
+}
+
+/// Whether `v` matches `kind`, which may be a `|`-separated union such as
+/// `string|object` (any member matching is enough).
+fn is_type(v: &Json, kind: &str) -> bool {
+    kind.split('|').any(|k| is_single_type(v, k.trim()))
+}
+
+fn is_single_type(v: &Json, kind: &str) -> bool {
+    match kind {
+        "string" => v.is_string(),
+        "number" => v.is_number(),
+        "integer" => v.as_i64().is_some(),
+        "boolean" => v.is_boolean(),
+        "array" => v.is_array(),
+        "object" => v.is_object(),
+        "null" => v.is_null(),
+        // Sign-constrained numeric kinds validate type and sign together
+        "positive-integer" => (v.is_i64() || v.is_u64()) && v.as_f64().is_some_and(|n| n > 0.0),
+        "nonnegative-integer" => v.is_u64(),
+        "positive-number" => v.as_f64().is_some_and(|n| n > 0.0),
+        "nonnegative-number" => v.as_f64().is_some_and(|n| n >= 0.0),
+        _ => false,
+    }
+}
+
+fn json_kind(v: &Json) -> &'static str {
+    if v.is_string() {
+        "string"
+    } else if v.is_boolean() {
+        "boolean"
+    } else if v.is_array() {
+        "array"
+    } else if v.is_object() {
+        "object"
+    } else if v.is_null() {
+        "null"
+    } else if v.as_i64().is_some() {
+        "integer"
+    } else if v.is_number() {
+        "number"
+    } else {
+        "unknown"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_run_checks_various_and_nested() {
+        let json = json!({
+            "name": 123,
+            "version": "1.0.0",
+            "nested": { "x": "abc" },
+            "choice": "gamma",
+            "short": "a",
+            "long": "abcdef"
+        });
+        let path = PathBuf::from("package.json");
+        let checks = vec![
+            Check::Required {
+                fields: vec!["nested.x".into(), "missing.field".into()],
+                message: None,
+                messages: None,
+                level: None,
+                code: None,
+            },
+            Check::Type {
+                fields: vec![
+                    ("name".into(), "string".into()),
+                    ("version".into(), "string".into()),
+                ]
+                .into_iter()
+                .collect(),
+                message: None,
+                level: None,
+                code: None,
+            },
+            Check::Const {
+                field: "version".into(),
+                value: json!("2.0.0"),
+                ignore_case: None,
+                message: None,
+                level: None,
+                code: None,
+            },
+            Check::Pattern {
+                field: "nested.x".into(),
+                regex: "^xyz$".into(),
+                regex_from: None,
+                flags: None,
+                message: None,
+                level: None,
+                code: None,
+            },
+            Check::Enum {
+                field: "choice".into(),
+                values: vec![json!("alpha"), json!("beta")],
+                deprecated: vec![],
+                prefer: HashMap::new(),
+                ignore_case: None,
+                message: None,
+                level: None,
+                code: None,
+            },
+            Check::MinLength {
+                field: "short".into(),
+                min: 2,
+                message: None,
+                level: None,
+                code: None,
+            },
+            Check::MaxLength {
+                field: "long".into(),
+                max: 5,
+                message: None,
+                level: None,
+                code: None,
+            },
+        ];
+        let issues = run_checks(&checks, &json, &path, "t");
+        // Expect errors for: required(missing.field), type(name not string), const(version), pattern(nested.x), enum(choice), minLength(short), maxLength(long)
+        assert!(issues.iter().any(|i| i.path == "$.missing.field"));
+        assert!(issues.iter().any(|i| i.path == "$.name"));
+        assert!(issues.iter().any(|i| i.path == "$.version"));
+        assert!(issues.iter().any(|i| i.path == "$.nested.x"));
+        assert!(issues.iter().any(|i| i.path == "$.choice"));
+        assert!(issues.iter().any(|i| i.path == "$.short"));
+        assert!(issues.iter().any(|i| i.path == "$.long"));
+    }
+
+    #[test]
+    fn test_type_fields_all_kinds_match() {
+        let json = json!({
+            "s": "str",
+            "n": 1.5,
+            "i": 2,
+            "b": true,
+            "a": [1,2,3],
+            "o": {"k":"v"},
+            "z": null
+        });
+        let path = PathBuf::from("file.json");
+        let mut fields = HashMap::new();
+        fields.insert("s".into(), "string".into());
+        fields.insert("n".into(), "number".into());
+        fields.insert("i".into(), "integer".into());
+        fields.insert("b".into(), "boolean".into());
+        fields.insert("a".into(), "array".into());
+        fields.insert("o".into(), "object".into());
+        fields.insert("z".into(), "null".into());
+        let checks = vec![Check::Type {
+            fields,
+            message: None,
+            level: None,
+            code: None,
+        }];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn test_type_fields_all_kinds_mismatch() {
+        let json = json!({
+            "s": 10,
+            "n": "not-number",
+            "i": 1.5,
+            "b": "true",
+            "a": {"not":"array"},
+            "o": [1,2,3],
+            "z": "not-null"
+        });
+        let path = PathBuf::from("file.json");
+        let mut fields = HashMap::new();
+        fields.insert("s".into(), "string".into());
+        fields.insert("n".into(), "number".into());
+        fields.insert("i".into(), "integer".into());
+        fields.insert("b".into(), "boolean".into());
+        fields.insert("a".into(), "array".into());
+        fields.insert("o".into(), "object".into());
+        fields.insert("z".into(), "null".into());
+        let checks = vec![Check::Type {
+            fields,
+            message: Some("Type mismatch at {{path}}, expected {{kind}}, got {{actual}}".into()),
+            level: None,
+            code: None,
+        }];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        // Expect 7 issues, one per path
+        assert_eq!(issues.len(), 7);
+        let paths: std::collections::HashSet<_> = issues.iter().map(|i| i.path.clone()).collect();
+        for p in ["$.s", "$.n", "$.i", "$.b", "$.a", "$.o", "$.z"].iter() {
+            assert!(paths.contains(&p.to_string()));
+        }
+        // spot-check a couple of messages include actual kind names
+        let msg_s = issues
+            .iter()
+            .find(|i| i.path == "$.s")
+            .unwrap()
+            .message
+            .clone();
+        assert!(msg_s.contains("got integer"));
+        let msg_a = issues
+            .iter()
+            .find(|i| i.path == "$.a")
+            .unwrap()
+            .message
+            .clone();
+        assert!(msg_a.contains("got object"));
+    }
+
+    #[test]
+    fn test_required_only_missing_reported() {
+        let json = json!({"a":1, "b":2});
+        let path = PathBuf::from("file.json");
+        let checks = vec![Check::Required {
+            fields: vec!["a".into(), "c".into()],
+            message: None,
+            messages: None,
+            level: None,
+            code: None,
+        }];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "$.c");
+    }
+
+    #[test]
+    fn test_const_match_and_mismatch() {
+        let json = json!({"x":"y", "n": 3});
+        let path = PathBuf::from("file.json");
+        let checks = vec![
+            Check::Const {
+                field: "x".into(),
+                value: json!("y"),
+                ignore_case: None,
+                message: Some("Field at {{path}} must equal {{expected}}, got {{actual}}".into()),
+                level: None,
+                code: None,
+            },
+            Check::Const {
+                field: "n".into(),
+                value: json!(4),
+                ignore_case: None,
+                message: Some("Field at {{path}} must equal {{expected}}, got {{actual}}".into()),
+                level: None,
+                code: None,
+            },
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "$.n");
+        assert!(issues[0].fixable);
+        // Message interpolation includes expected, actual, and path
+        assert!(issues[0].message.contains("must equal 4"));
+        assert!(issues[0].message.contains("got 3") || issues[0].message.contains("3"));
+        assert!(issues[0].message.contains("$.n"));
+    }
+
+    #[test]
+    fn test_pattern_match_and_mismatch() {
+        let json = json!({"v":"1.2.3", "w":"nope"});
+        let path = PathBuf::from("file.json");
+        let checks = vec![
+            Check::Pattern {
+                field: "v".into(),
+                regex: "^\\d+\\.\\d+\\.\\d+$".into(),
+                regex_from: None,
+                flags: None,
+                message: Some("Value '{{actual}}' at {{path}} must match {{pattern}}".into()),
+                level: None,
+                code: None,
+            },
+            Check::Pattern {
+                field: "w".into(),
+                regex: "^\\d+$".into(),
+                regex_from: None,
+                flags: None,
+                message: Some("Value '{{actual}}' at {{path}} must match {{pattern}}".into()),
+                level: None,
+                code: None,
+            },
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "$.w");
+        assert!(!issues[0].fixable);
+        assert_eq!(issues[0].message, "Value 'nope' at $.w must match ^\\d+$");
+    }
+
+    #[test]
+    fn test_enum_match_and_mismatch() {
+        let json = json!({"k":"b", "n": 2});
+        let path = PathBuf::from("file.json");
+        let checks = vec![
+            Check::Enum {
+                field: "k".into(),
+                values: vec![json!("a"), json!("b")],
+                deprecated: vec![],
+                prefer: HashMap::new(),
+                ignore_case: None,
+                message: Some("Value at {{path}} must be one of {{expected}}, got {{actual}}".into()),
+                level: None,
+                code: None,
+            },
+            Check::Enum {
+                field: "n".into(),
+                values: vec![json!(1), json!(3)],
+                deprecated: vec![],
+                prefer: HashMap::new(),
+                ignore_case: None,
+                message: Some("Value at {{path}} must be one of {{expected}}, got {{actual}}".into()),
+                level: None,
+                code: None,
+            },
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "$.n");
+        // Message interpolation includes expected set, actual value, and path
+        assert!(issues[0].message.contains("one of"));
+        assert!(issues[0].message.contains("2"));
+        assert!(issues[0].message.contains("$.n"));
+    }
+
+    #[test]
+    fn test_min_max_length_boundaries() {
+        let json = json!({"s1":"ab", "s2":"a", "s3":"abc", "s4":"abcdef"});
+        let path = PathBuf::from("file.json");
+        let checks = vec![
+            Check::MinLength {
+                field: "s1".into(),
+                min: 2,
+                message: Some("String at {{path}} length must be >= {{expected}}, got {{actual}}".into()),
+                level: None,
+                code: None,
+            }, // ok
+            Check::MinLength {
+                field: "s2".into(),
+                min: 2,
+                message: Some("String at {{path}} length must be >= {{expected}}, got {{actual}}".into()),
+                level: None,
+                code: None,
+            }, // fail
+            Check::MaxLength {
+                field: "s3".into(),
+                max: 3,
+                message: Some("String at {{path}} length must be <= {{expected}}, got {{actual}}".into()),
+                level: None,
+                code: None,
+            }, // ok
+            Check::MaxLength {
+                field: "s4".into(),
+                max: 5,
+                message: Some("String at {{path}} length must be <= {{expected}}, got {{actual}}".into()),
+                level: None,
+                code: None,
+            }, // fail
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let paths: std::collections::HashSet<_> = issues.iter().map(|i| i.path.clone()).collect();
+        assert_eq!(issues.len(), 2);
+        assert!(paths.contains("$.s2"));
+        assert!(paths.contains("$.s4"));
+        // Message interpolation includes expected, actual, and path in both issues
+        let m2 = issues.iter().find(|i| i.path == "$.s2").unwrap().message.clone();
+        assert!(m2.contains("$.s2"));
+        assert!(m2.contains(">= 2"));
+        let m4 = issues.iter().find(|i| i.path == "$.s4").unwrap().message.clone();
+        assert!(m4.contains("$.s4"));
+        assert!(m4.contains("<= 5"));
+    }
+
+    #[test]
+    fn test_min_max_items_boundaries() {
+        let json = json!({"k1": ["a"], "k0": [], "k3": [1, 2, 3], "k4": [1, 2, 3, 4], "s": "abc"});
+        let path = PathBuf::from("file.json");
+        let min = |field: &str, min: usize| Check::MinItems {
+            field: field.into(),
+            min,
+            message: None,
+            level: None,
+            code: None,
+        };
+        let max = |field: &str, max: usize| Check::MaxItems {
+            field: field.into(),
+            max,
+            message: None,
+            level: None,
+            code: None,
+        };
+        let checks = vec![
+            min("k1", 1), // ok
+            min("k0", 1), // fail
+            max("k3", 3), // ok
+            max("k4", 3), // fail
+            min("s", 10), // not an array: ignored
+            max("missing", 0),
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let msgs: Vec<_> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            msgs,
+            vec![
+                "Array at $.k0 must have at least 1 items, got 0",
+                "Array at $.k4 must have at most 3 items, got 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_semver_gt_ordering_and_invalid() {
+        let json = json!({"version":"1.2.0", "prev":"1.3.0", "next":"v2.0.0", "bad":"1.x"});
+        let path = PathBuf::from("file.json");
+        let checks = vec![
+            Check::SemverGt {
+                field: "version".into(),
+                than_field: "prev".into(),
+                message: None,
+                level: None,
+                code: None,
+            }, // fail: 1.2.0 <= 1.3.0
+            Check::SemverGt {
+                field: "next".into(),
+                than_field: "prev".into(),
+                message: None,
+                level: None,
+                code: None,
+            }, // ok: v2.0.0 > 1.3.0
+            Check::SemverGt {
+                field: "bad".into(),
+                than_field: "prev".into(),
+                message: None,
+                level: Some("warn".into()),
+                code: None,
+            }, // diagnostic: unparseable
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        assert_eq!(issues.len(), 2);
+        let v = issues.iter().find(|i| i.path == "$.version").unwrap();
+        assert!(v.message.contains("1.2.0"));
+        assert!(v.message.contains("1.3.0"));
+        let b = issues.iter().find(|i| i.path == "$.bad").unwrap();
+        assert_eq!(b.severity, "warn");
+        assert!(b.message.contains("Invalid semantic version"));
+    }
+
+    #[test]
+    fn test_required_per_field_messages_with_fallback() {
+        let json = json!({"a":1});
+        let path = PathBuf::from("file.json");
+        let mut messages = HashMap::new();
+        messages.insert(
+            "b".to_string(),
+            "Add '{{field}}', see docs/b.md".to_string(),
+        );
+        messages.insert("$.c".to_string(), "Missing {{path}}".to_string());
+        let checks = vec![Check::Required {
+            fields: vec!["b".into(), "$.c".into(), "d".into()],
+            message: Some("Shared: {{field}}".into()),
+            messages: Some(messages),
+            level: None,
+            code: None,
+        }];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        assert_eq!(issues.len(), 3);
+        let by_path = |p: &str| issues.iter().find(|i| i.path == p).unwrap().message.clone();
+        assert_eq!(by_path("$.b"), "Add 'b', see docs/b.md");
+        assert_eq!(by_path("$.c"), "Missing $.c");
+        assert_eq!(by_path("$.d"), "Shared: d");
+    }
+
+    #[test]
+    fn test_no_additional_properties_root_nested_and_patterns() {
         let json = json!({
-            "s": 10,
-            "n": "not-number",
-            "i": 1.5,
-            "b": "true",
-            "a": {"not":"array"},
-            "o": [1,2,3],
-            "z": "not-null"
+            "name": "x",
+            "nmae": "typo",
+            "x-vendor": true,
+            "config": {"port": 1, "prot": 2, "./p": 3}
         });
         let path = PathBuf::from("file.json");
-        let mut fields = HashMap::new();
-        fields.insert("s".into(), "string".into());
-        fields.insert("n".into(), "number".into());
-        fields.insert("i".into(), "integer".into());
-        fields.insert("b".into(), "boolean".into());
-        fields.insert("a".into(), "array".into());
-        fields.insert("o".into(), "object".into());
-        fields.insert("z".into(), "null".into());
-        let checks = vec![Check::Type {
-            fields,
-            message: Some("Type mismatch at {{path}}, expected {{kind}}, got {{actual}}".into()),
+        let checks = vec![
+            Check::NoAdditionalProperties {
+                field: String::new(),
+                allowed: vec!["name".into(), "config".into()],
+                pattern_properties: vec!["^x-".into()],
+                message: None,
+                level: None,
+                code: None,
+            },
+            Check::NoAdditionalProperties {
+                field: "config".into(),
+                allowed: vec!["port".into()],
+                pattern_properties: vec![],
+                message: Some("Key {{key}} not allowed ({{path}})".into()),
+                level: None,
+                code: None,
+            },
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let paths: Vec<_> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, vec!["$.nmae", "$.config.prot", "$.config[\"./p\"]"]);
+        assert_eq!(issues[0].message, "Unknown property 'nmae' at $.nmae");
+        assert_eq!(issues[1].message, "Key prot not allowed ($.config.prot)");
+        assert_eq!(get_json_path(&json, &issues[2].path), Some(&json!(3)));
+    }
+
+    #[test]
+    fn test_required_message_interpolation_path() {
+        let json = json!({"a":1});
+        let path = PathBuf::from("file.json");
+        let checks = vec![Check::Required { fields: vec!["a".into(), "b".into()], message: Some("Field '{{field}}' missing at {{path}}".into()), messages: None, level: None, code: None }];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "$.b");
+        assert_eq!(issues[0].message, "Field 'b' missing at $.b");
+    }
+
+    #[test]
+    fn test_type_sign_constrained_numeric_kinds() {
+        let json = json!({
+            "workers": 4, "retries": 0, "ratio": 0.5,
+            "zero": 0, "neg": -1, "frac": 1.5, "text": "3"
+        });
+        let path = PathBuf::from("file.json");
+        let fields = [
+            ("workers", "positive-integer"),
+            ("retries", "nonnegative-integer"),
+            ("ratio", "positive-number"),
+            ("zero", "nonnegative-number"),
+            ("neg", "nonnegative-integer"),
+            ("frac", "positive-integer"),
+            ("text", "positive-number"),
+            ("zero", "positive-integer"),
+        ];
+        let checks: Vec<Check> = fields
+            .iter()
+            .map(|(p, k)| Check::Type {
+                fields: vec![(p.to_string(), k.to_string())].into_iter().collect(),
+                message: None,
+                level: None,
+                code: None,
+            })
+            .collect();
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let msgs: Vec<_> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            msgs,
+            vec![
+                "Expected nonnegative-integer at $.neg, got -1",
+                "Expected positive-integer at $.frac, got 1.5",
+                "Expected positive-number at $.text, got \"3\"",
+                "Expected positive-integer at $.zero, got 0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_regex_files_loads_caches_and_rejects_invalid() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(tmp.path().join("ok.txt"), "^a+$\n").unwrap();
+        std::fs::write(tmp.path().join("bad.txt"), "(unclosed").unwrap();
+        let pattern = |from: &str| Check::Pattern {
+            field: "name".into(),
+            regex: String::new(),
+            regex_from: Some(from.into()),
+            flags: None,
+            message: None,
+            level: None,
+            code: None,
+        };
+        let mut files = HashMap::new();
+        let mut checks = vec![pattern("ok.txt"), pattern("ok.txt")];
+        resolve_regex_files(&mut checks, tmp.path(), &mut files).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(matches!(&checks[1], Check::Pattern { regex, .. } if regex == "^a+$"));
+
+        let mut checks = vec![pattern("bad.txt")];
+        let err = resolve_regex_files(&mut checks, tmp.path(), &mut files).unwrap_err();
+        assert!(err.starts_with("Invalid regex in") && err.contains("bad.txt"));
+    }
+
+    #[test]
+    fn test_validate_pattern_sources_requires_exactly_one() {
+        let pattern = |regex: &str, from: Option<&str>| Check::Pattern {
+            field: "name".into(),
+            regex: regex.into(),
+            regex_from: from.map(Into::into),
+            flags: None,
+            message: None,
+            level: None,
+            code: None,
+        };
+        assert!(validate_pattern_sources(&[pattern("^a$", None)]).is_ok());
+        assert!(validate_pattern_sources(&[pattern("", Some("a.txt"))]).is_ok());
+        let err = validate_pattern_sources(&[pattern("^a$", Some("a.txt"))]).unwrap_err();
+        assert!(err.contains("sets both regex and regex_from"));
+        // Nested checks are validated too
+        let nested = Check::Not {
+            check: Box::new(pattern("", None)),
+            message: None,
+            level: None,
+            code: None,
+        };
+        let err = validate_pattern_sources(&[nested]).unwrap_err();
+        assert_eq!(
+            err,
+            "Pattern check for 'name' sets neither regex nor regex_from"
+        );
+    }
+
+    #[test]
+    fn test_same_as_deep_equality_reports_both_paths() {
+        let json = json!({
+            "a": {"x": [1, {"y": 2}]},
+            "b": {"x": [1, {"y": 2}]},
+            "c": {"x": [1, {"y": 3}]},
+            "node": "20"
+        });
+        let path = PathBuf::from("file.json");
+        let same = |field: &str, reference: &str| Check::SameAs {
+            field: field.into(),
+            reference: reference.into(),
+            message: None,
+            level: None,
+            code: None,
+        };
+        let checks = vec![
+            same("a", "b"),
+            same("$.a", "$.c"),
+            same("missing", "also.missing"),
+            same("engine", "node"),
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let msgs: Vec<_> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            msgs,
+            vec![
+                "Value at $.a ({\"x\":[1,{\"y\":2}]}) must match $.c ({\"x\":[1,{\"y\":3}]})",
+                "Value at $.engine (missing) must match $.node (\"20\")",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_path_exists_resolves_from_file_dir_or_base() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join("pkg/dist")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("pkg/bin")).unwrap();
+        std::fs::write(tmp.path().join("pkg/dist/index.js"), "").unwrap();
+        std::fs::write(tmp.path().join("pkg/bin/cli.js"), "").unwrap();
+        let json = json!({
+            "main": "dist/index.js",
+            "types": "dist/index.d.ts",
+            "files": ["dist", "docs"],
+            "bin": {"cli": "./bin/cli.js", "old": "bin/old.js", "my.cli": "bin/my.js"},
+            "scripts": ["cli.js", 3]
+        });
+        let path = tmp.path().join("pkg/package.json");
+        let exists = |field: &str, base: Option<&str>| Check::PathExists {
+            field: field.into(),
+            base: base.map(String::from),
+            message: None,
+            level: None,
+            code: None,
+        };
+        let checks = vec![
+            exists("main", None),
+            exists("$.types", None),
+            exists("files", None),
+            exists("bin", None),
+            exists("scripts", Some("bin")),
+            exists("missing", None),
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let got: Vec<_> = issues
+            .iter()
+            .map(|i| (i.path.as_str(), i.message.as_str()))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                (
+                    "$.types",
+                    "Path 'dist/index.d.ts' at $.types does not exist"
+                ),
+                ("$.files[1]", "Path 'docs' at $.files[1] does not exist"),
+                ("$.bin.old", "Path 'bin/old.js' at $.bin.old does not exist"),
+                (
+                    "$.bin[\"my.cli\"]",
+                    "Path 'bin/my.js' at $.bin[\"my.cli\"] does not exist"
+                ),
+                (
+                    "$.scripts[1]",
+                    "Expected a path string at $.scripts[1], got 3"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_length_checks_count_array_items_and_object_keys() {
+        let json = json!({
+            "files": ["a"],
+            "keywords": ["a", "b", "c"],
+            "scripts": {"build": "x", "test": "y"},
+            "name": "ok",
+            "count": 10
+        });
+        let path = PathBuf::from("file.json");
+        let min = |field: &str, min: usize| Check::MinLength {
+            field: field.into(),
+            min,
+            message: None,
+            level: None,
+            code: None,
+        };
+        let max = |field: &str, max: usize| Check::MaxLength {
+            field: field.into(),
+            max,
+            message: None,
+            level: None,
+            code: None,
+        };
+        let checks = vec![
+            min("files", 2),
+            max("keywords", 2),
+            min("scripts", 3),
+            max("scripts", 2),
+            min("name", 2),
+            min("count", 100),
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let got: Vec<_> = issues
+            .iter()
+            .map(|i| (i.path.as_str(), i.message.as_str()))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                ("$.files", "Array shorter than minimum"),
+                ("$.keywords", "Array longer than maximum"),
+                ("$.scripts", "Object shorter than minimum"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_checks_address_array_elements() {
+        let json = json!({
+            "workspaces": ["packages/*"],
+            "contributors": [{"email": "a@x.dev"}, {"name": "b"}]
+        });
+        let path = PathBuf::from("package.json");
+        let checks = vec![
+            Check::Required {
+                fields: vec!["$.contributors[1].email".into(), "$.workspaces[0]".into()],
+                message: None,
+                messages: None,
+                level: None,
+                code: None,
+            },
+            Check::Const {
+                field: "$.workspaces[0]".into(),
+                value: json!("apps/*"),
+                ignore_case: None,
+                message: None,
+                level: None,
+                code: None,
+            },
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let paths: Vec<_> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, vec!["$.contributors[1].email", "$.workspaces[0]"]);
+    }
+
+    #[test]
+    fn test_wildcard_fields_report_each_concrete_path() {
+        let json = json!({
+            "scripts": {"build": "tsc", "lint": 1, "test": "vitest"},
+            "dependencies": {"a": "^1.0.0", "b": "latest"},
+            "contributors": [{"email": "a@x.dev"}, {"name": "b"}]
+        });
+        let path = PathBuf::from("package.json");
+        let mut types = HashMap::new();
+        types.insert("$.scripts.*".to_string(), "string".to_string());
+        let checks = vec![
+            Check::Type {
+                fields: types,
+                message: None,
+                level: None,
+                code: None,
+            },
+            Check::Pattern {
+                field: "$.dependencies.*".into(),
+                regex: "^\\^?\\d".into(),
+                regex_from: None,
+                flags: None,
+                message: Some("Bad range at {{path}}".into()),
+                level: None,
+                code: None,
+            },
+            Check::Required {
+                fields: vec!["$.contributors[*].email".into()],
+                message: None,
+                messages: None,
+                level: None,
+                code: None,
+            },
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let paths: Vec<_> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "$.scripts.lint",
+                "$.dependencies.b",
+                "$.contributors[1].email"
+            ]
+        );
+        assert_eq!(issues[1].message, "Bad range at $.dependencies.b");
+    }
+
+    #[test]
+    fn test_wildcard_fields_quote_keys_with_dots() {
+        let json = json!({
+            "exports": {
+                "./utils": {"types": "./utils.d.ts"},
+                "./cli": {"import": "./cli.js"}
+            }
+        });
+        let checks = vec![Check::Required {
+            fields: vec!["$.exports.*.types".into()],
+            message: None,
+            messages: None,
+            level: None,
+            code: None,
+        }];
+        let issues = run_checks(&checks, &json, &PathBuf::from("package.json"), "rule");
+        let paths: Vec<_> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, vec!["$.exports[\"./cli\"].types"]);
+    }
+
+    #[test]
+    fn test_minimum_maximum_with_exclusive_bounds() {
+        let json = json!({"timeout": 30, "retries": 0, "ratio": 1.5, "name": "x"});
+        let path = PathBuf::from("file.json");
+        let min = |field: &str, min: f64, exclusive: Option<bool>| Check::Minimum {
+            field: field.into(),
+            min,
+            exclusive,
+            message: None,
+            level: None,
+            code: None,
+        };
+        let max = |field: &str, max: f64, exclusive: Option<bool>| Check::Maximum {
+            field: field.into(),
+            max,
+            exclusive,
+            message: None,
+            level: None,
+            code: None,
+        };
+        let checks = vec![
+            min("timeout", 30.0, None),
+            min("retries", 0.0, Some(true)),
+            max("ratio", 1.0, None),
+            max("timeout", 30.0, Some(true)),
+            max("timeout", 60.0, None),
+            min("name", 1.0, None),
+            min("missing", 1.0, None),
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let msgs: Vec<_> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            msgs,
+            vec![
+                "Value at $.retries must be > 0, got 0",
+                "Value at $.ratio must be <= 1, got 1.5",
+                "Value at $.timeout must be < 30, got 30",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multiple_of_integer_fractional_and_zero_divisor() {
+        let json = json!({"memory": 768, "odd": 700, "price": 0.3, "step": 0.35, "name": "x"});
+        let path = PathBuf::from("file.json");
+        let multiple = |field: &str, divisor: f64| Check::MultipleOf {
+            field: field.into(),
+            divisor,
+            message: None,
+            level: None,
+            code: None,
+        };
+        let checks = vec![
+            multiple("memory", 256.0),
+            multiple("odd", 256.0),
+            multiple("price", 0.1),
+            multiple("step", 0.1),
+            multiple("memory", 0.0),
+            multiple("name", 2.0),
+            multiple("missing", 2.0),
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let msgs: Vec<_> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            msgs,
+            vec![
+                "Value at $.odd must be a multiple of 256, got 700",
+                "Value at $.step must be a multiple of 0.1, got 0.35",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_url_scheme_and_host_allowlists() {
+        let json = json!({
+            "homepage": "https://example.dev/docs",
+            "plain": "http://example.dev",
+            "repo": "https://GitHub.com/nazahex/riglet",
+            "mirror": "https://gitlab.com/nazahex/riglet",
+            "git": "git+https://github.com/nazahex/riglet.git",
+            "bad": "not a url"
+        });
+        let path = PathBuf::from("package.json");
+        let url = |field: &str, schemes: &[&str], hosts: &[&str]| Check::Url {
+            field: field.into(),
+            schemes: schemes.iter().map(|s| s.to_string()).collect(),
+            hosts: hosts.iter().map(|s| s.to_string()).collect(),
+            message: None,
+            level: None,
+            code: None,
+        };
+        let checks = vec![
+            url("homepage", &[], &[]),
+            url("plain", &[], &[]),
+            url("repo", &[], &["github.com"]),
+            url("mirror", &[], &["github.com"]),
+            url("git", &["git+https"], &["github.com"]),
+            url("bad", &[], &[]),
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let msgs: Vec<_> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            msgs,
+            vec![
+                "URL at $.plain must use scheme https, got http://example.dev",
+                "URL at $.mirror must be on host github.com, got https://gitlab.com/nazahex/riglet",
+                "Invalid URL at $.bad: relative URL without a base",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unique_items_reports_first_duplicate() {
+        let json = json!({
+            "workspaces": ["apps/*", "packages/*", "apps/*"],
+            "files": ["dist", "README.md"],
+            "exports": [{"a": 1}, {"b": 2}, {"a": 1}],
+            "name": "not-an-array"
+        });
+        let path = PathBuf::from("package.json");
+        let unique = |field: &str| Check::UniqueItems {
+            field: field.into(),
+            message: None,
+            level: None,
+            code: None,
+        };
+        let checks = vec![
+            unique("workspaces"),
+            unique("files"),
+            unique("exports"),
+            unique("name"),
+            unique("missing"),
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let msgs: Vec<_> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            msgs,
+            vec![
+                "Duplicate item at $.workspaces: apps/*",
+                "Duplicate item at $.exports: {\"a\":1}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_validators() {
+        let cases = [
+            ("email", "dev@example.com", true),
+            ("email", "dev@localhost", false),
+            ("email", "not an email", false),
+            ("uri", "https://example.com/a?b=c", true),
+            ("uri", "example.com", false),
+            ("date", "2024-02-29", true),
+            ("date", "2023-02-29", false),
+            ("date", "2024-13-01", false),
+            ("date", "2024-01-0é", false),
+            ("date-time", "2024-05-01T12:30:00Z", true),
+            ("date-time", "2024-05-01T12:30:00.123+02:00", true),
+            ("date-time", "2024-05-01 12:30:00Z", false),
+            ("date-time", "2024-05-01T24:00:00Z", false),
+            ("uuid", "123e4567-e89b-12d3-a456-426614174000", true),
+            ("uuid", "123e4567e89b12d3a456426614174000", false),
+            ("semver", "1.2.3-beta.1+build", true),
+            ("semver", "v1.2", false),
+        ];
+        for (format, value, ok) in cases {
+            assert_eq!(
+                matches_format(value, format),
+                Some(ok),
+                "{} {}",
+                format,
+                value
+            );
+        }
+        assert_eq!(matches_format("x", "hostname"), None);
+    }
+
+    #[test]
+    fn test_format_check_reports_value_and_skips_unknown() {
+        let json = json!({"author": {"email": "nope"}, "created": "2024-05-01", "id": "x"});
+        let path = PathBuf::from("package.json");
+        let checks = vec![
+            Check::Format {
+                field: "author.email".into(),
+                format: "email".into(),
+                message: None,
+                level: None,
+                code: None,
+            },
+            Check::Format {
+                field: "created".into(),
+                format: "date".into(),
+                message: None,
+                level: None,
+                code: None,
+            },
+            Check::Format {
+                field: "id".into(),
+                format: "ulid".into(),
+                message: None,
+                level: None,
+                code: None,
+            },
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "$.author.email");
+        assert_eq!(
+            issues[0].message,
+            "Value at $.author.email is not a valid email: nope"
+        );
+    }
+
+    #[test]
+    fn test_enum_deprecated_values_warn_with_preference() {
+        let json = json!({"entry": "main", "kind": "legacy", "mode": "strict", "bad": "nope"});
+        let path = PathBuf::from("package.json");
+        let enum_check = |field: &str| Check::Enum {
+            field: field.into(),
+            values: vec![json!("exports"), json!("strict")],
+            deprecated: vec![json!("main"), json!("legacy")],
+            prefer: HashMap::from([("main".to_string(), json!("exports"))]),
+            ignore_case: None,
+            message: None,
+            level: None,
+            code: None,
+        };
+        let checks = vec![
+            enum_check("entry"),
+            enum_check("kind"),
+            enum_check("mode"),
+            enum_check("bad"),
+        ];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let got: Vec<_> = issues
+            .iter()
+            .map(|i| (i.path.as_str(), i.severity.as_str(), i.message.as_str()))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                (
+                    "$.entry",
+                    "warn",
+                    "Value \"main\" at $.entry is deprecated; prefer \"exports\""
+                ),
+                ("$.kind", "warn", "Value \"legacy\" at $.kind is deprecated"),
+                ("$.bad", "error", "Value not in allowed set"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dependency_presence_trigger() {
+        let path = PathBuf::from("package.json");
+        let checks = vec![Check::Dependency {
+            if_field: "bin".into(),
+            if_equals: None,
+            require: vec!["files".into(), "engines.node".into()],
+            message: None,
+            level: None,
+            code: None,
+        }];
+        let with_bin = json!({"bin": "cli.js", "files": ["cli.js"]});
+        let issues = run_checks(&checks, &with_bin, &path, "rule");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "$.engines.node");
+        assert_eq!(
+            issues[0].message,
+            "Field 'engines.node' is required at $.engines.node when $.bin is set"
+        );
+        let without_bin = json!({"name": "lib"});
+        assert!(run_checks(&checks, &without_bin, &path, "rule").is_empty());
+    }
+
+    #[test]
+    fn test_dependency_value_match_trigger() {
+        let path = PathBuf::from("package.json");
+        let checks = vec![Check::Dependency {
+            if_field: "type".into(),
+            if_equals: Some(json!("module")),
+            require: vec!["exports".into()],
+            message: Some("ESM packages need {{path}}".into()),
+            level: Some("warn".into()),
+            code: None,
+        }];
+        let esm = json!({"type": "module"});
+        let issues = run_checks(&checks, &esm, &path, "rule");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].message, "ESM packages need $.exports");
+        assert_eq!(issues[0].severity, "warn");
+        let cjs = json!({"type": "commonjs"});
+        assert!(run_checks(&checks, &cjs, &path, "rule").is_empty());
+        let esm_ok = json!({"type": "module", "exports": "./index.js"});
+        assert!(run_checks(&checks, &esm_ok, &path, "rule").is_empty());
+    }
+
+    #[test]
+    fn test_forbidden_flags_present_paths_including_nested() {
+        let json = json!({
+            "bundledDependencies": ["x"],
+            "build": {"legacy": true, "target": "es2020"},
+            "contributors": [{"name": "a", "password": "p"}, {"name": "b"}]
+        });
+        let path = PathBuf::from("package.json");
+        let checks = vec![Check::Forbidden {
+            fields: vec![
+                "bundledDependencies".into(),
+                "$.build.legacy".into(),
+                "_secret".into(),
+                "contributors.*.password".into(),
+            ],
+            message: None,
             level: None,
+            code: None,
         }];
         let issues = run_checks(&checks, &json, &path, "rule");
-        // Expect 7 issues, one per path
-        assert_eq!(issues.len(), 7);
-        let paths: std::collections::HashSet<_> = issues.iter().map(|i| i.path.clone()).collect();
-        for p in ["$.s", "$.n", "$.i", "$.b", "$.a", "$.o", "$.z"].iter() {
-            assert!(paths.contains(&p.to_string()));
-        }
-        // spot-check a couple of messages include actual kind names
-        let msg_s = issues
-            .iter()
-            .find(|i| i.path == "$.s")
-            .unwrap()
-            .message
-            .clone();
-        assert!(msg_s.contains("got integer"));
-        let msg_a = issues
+        let got: Vec<_> = issues
             .iter()
-            .find(|i| i.path == "$.a")
-            .unwrap()
-            .message
-            .clone();
-        assert!(msg_a.contains("got object"));
+            .map(|i| (i.path.as_str(), i.message.as_str()))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                (
+                    "$.bundledDependencies",
+                    "Field 'bundledDependencies' is not allowed at $.bundledDependencies"
+                ),
+                (
+                    "$.build.legacy",
+                    "Field 'build.legacy' is not allowed at $.build.legacy"
+                ),
+                (
+                    "$.contributors[0].password",
+                    "Field 'contributors[0].password' is not allowed at $.contributors[0].password"
+                ),
+            ]
+        );
     }
 
     #[test]
-    fn test_required_only_missing_reported() {
-        let json = json!({"a":1, "b":2});
-        let path = PathBuf::from("file.json");
-        let checks = vec![Check::Required {
-            fields: vec!["a".into(), "c".into()],
+    fn test_enum_and_const_ignore_case() {
+        let json = json!({"license": "mit", "count": 1});
+        let path = PathBuf::from("package.json");
+        let checks = |fold: Option<bool>| {
+            vec![
+                Check::Enum {
+                    field: "license".into(),
+                    values: vec![json!("MIT"), json!("ISC")],
+                    deprecated: vec![],
+                    prefer: HashMap::new(),
+                    ignore_case: fold,
+                    message: None,
+                    level: None,
+                    code: None,
+                },
+                Check::Const {
+                    field: "license".into(),
+                    value: json!("MIT"),
+                    ignore_case: fold,
+                    message: None,
+                    level: None,
+                    code: None,
+                },
+                // Non-strings still compare exactly
+                Check::Const {
+                    field: "count".into(),
+                    value: json!(1.0),
+                    ignore_case: fold,
+                    message: None,
+                    level: None,
+                    code: None,
+                },
+            ]
+        };
+        let strict = run_checks(&checks(None), &json, &path, "rule");
+        assert_eq!(strict.len(), 3);
+        let folded = run_checks(&checks(Some(true)), &json, &path, "rule");
+        assert_eq!(folded.len(), 1);
+        assert_eq!(folded[0].path, "$.count");
+    }
+
+    #[test]
+    fn test_consistent_field_flags_outliers_against_majority() {
+        let docs = vec![
+            (PathBuf::from("a/package.json"), json!({"license": "MIT"})),
+            (PathBuf::from("b/package.json"), json!({"license": "ISC"})),
+            (PathBuf::from("c/package.json"), json!({"license": "MIT"})),
+            (PathBuf::from("d/package.json"), json!({"name": "d"})),
+        ];
+        let checks = vec![Check::ConsistentField {
+            field: "$.license".into(),
+            message: None,
+            level: None,
+            code: None,
+        }];
+        assert!(has_aggregate_checks(&checks));
+        // Per-file evaluation ignores cross-file kinds
+        assert!(run_checks(&checks, &docs[1].1, &docs[1].0, "rule").is_empty());
+        let issues = run_aggregate_checks(&checks, &docs, "rule");
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].file.ends_with("b/package.json"));
+        assert_eq!(
+            issues[0].message,
+            "Value at $.license is inconsistent across files: expected \"MIT\", got \"ISC\""
+        );
+    }
+
+    #[test]
+    fn test_string_length_counts_chars_not_bytes() {
+        // "né" is 3 bytes, "🎉🎉" is 8 bytes, "café" is 5 bytes
+        let json = json!({"a": "né", "b": "🎉🎉", "c": "café"});
+        let path = PathBuf::from("package.json");
+        let min = |field: &str, min: usize| Check::MinLength {
+            field: field.into(),
+            min,
+            message: None,
+            level: None,
+            code: None,
+        };
+        let max = |field: &str, max: usize| Check::MaxLength {
+            field: field.into(),
+            max,
             message: None,
             level: None,
+            code: None,
+        };
+        let passing = vec![max("a", 2), max("b", 2), max("c", 4), min("c", 4)];
+        assert!(run_checks(&passing, &json, &path, "rule").is_empty());
+        let failing = vec![min("a", 3), min("b", 3), max("c", 3)];
+        assert_eq!(run_checks(&failing, &json, &path, "rule").len(), 3);
+    }
+
+    #[test]
+    fn test_type_union_accepts_any_listed_kind() {
+        let json = json!({"bin": {"cli": "./cli.js"}, "version": 1, "main": true});
+        let path = PathBuf::from("package.json");
+        let checks = vec![Check::Type {
+            fields: HashMap::from([
+                ("bin".to_string(), "string|object".to_string()),
+                ("version".to_string(), "number | string".to_string()),
+                ("main".to_string(), "string|array".to_string()),
+            ]),
+            message: Some("Expected {{kind}} at {{path}}, got {{actual}}".into()),
+            level: None,
+            code: None,
         }];
         let issues = run_checks(&checks, &json, &path, "rule");
         assert_eq!(issues.len(), 1);
-        assert_eq!(issues[0].path, "$.c");
+        assert_eq!(
+            issues[0].message,
+            "Expected string|array at $.main, got boolean"
+        );
     }
 
     #[test]
-    fn test_const_match_and_mismatch() {
-        let json = json!({"x":"y", "n": 3});
-        let path = PathBuf::from("file.json");
+    fn test_pattern_flags_i_and_m() {
+        let json = json!({"name": "Rigra", "description": "Summary line\nSee: docs"});
+        let path = PathBuf::from("package.json");
+        let pattern = |field: &str, regex: &str, flags: Option<&str>| Check::Pattern {
+            field: field.into(),
+            regex: regex.into(),
+            regex_from: None,
+            flags: flags.map(String::from),
+            message: None,
+            level: None,
+            code: None,
+        };
+        let count = |chk: Check| run_checks(&[chk], &json, &path, "rule").len();
+        assert_eq!(count(pattern("name", "^rigra$", None)), 1);
+        assert_eq!(count(pattern("name", "^rigra$", Some("i"))), 0);
+        assert_eq!(count(pattern("description", "^See:", None)), 1);
+        assert_eq!(count(pattern("description", "^See:", Some("m"))), 0);
+        // Unknown flag characters fall back to no flags instead of failing
+        assert_eq!(count(pattern("name", "^rigra$", Some("iq"))), 1);
+        assert_eq!(count(pattern("name", "^Rigra$", Some("iq"))), 0);
+    }
+
+    #[test]
+    fn test_allowed_properties_alias_on_nested_object() {
+        #[derive(serde::Deserialize)]
+        struct Doc {
+            checks: Vec<Check>,
+        }
+        let doc: Doc = toml::from_str(
+            r#"
+[[checks]]
+kind = "allowedProperties"
+field = "publishConfig"
+allowed = ["access", "registry"]
+"#,
+        )
+        .unwrap();
+        let json = json!({"publishConfig": {"access": "public", "tag": "next"}, "extra": 1});
+        let issues = run_checks(&doc.checks, &json, &PathBuf::from("package.json"), "rule");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "$.publishConfig.tag");
+        // Non-object targets are skipped
+        let json = json!({"publishConfig": "public"});
+        assert!(run_checks(&doc.checks, &json, &PathBuf::from("package.json"), "rule").is_empty());
+    }
+
+    #[test]
+    fn test_non_empty_flags_blank_strings_only() {
+        let json = json!({
+            "name": "  ",
+            "description": "",
+            "version": "1.0.0",
+            "keywords": [],
+            "author": {"name": "\t"}
+        });
+        let path = PathBuf::from("package.json");
+        let checks = vec![Check::NonEmpty {
+            fields: vec![
+                "name".into(),
+                "description".into(),
+                "version".into(),
+                "keywords".into(),
+                "license".into(),
+                "author.name".into(),
+            ],
+            message: None,
+            level: None,
+            code: None,
+        }];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let paths: Vec<_> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, vec!["$.name", "$.description", "$.author.name"]);
+        assert_eq!(
+            issues[0].message,
+            "Field 'name' must not be empty at $.name"
+        );
+    }
+
+    #[test]
+    fn test_property_names_flags_keys_not_matching_regex() {
+        let json = json!({
+            "scripts": {
+                "build": "tsc",
+                "test-unit": "vitest",
+                "lintFix": "biome",
+                "pre_publish": "x",
+                "check:types": "tsc --noEmit",
+                "build.esm": "tsc -m esm"
+            }
+        });
+        let path = PathBuf::from("package.json");
+        let checks = vec![Check::PropertyNames {
+            field: "scripts".into(),
+            regex: "^[a-z][a-z0-9]*(-[a-z0-9]+)*(:[a-z0-9-]+)?$".into(),
+            message: None,
+            level: None,
+            code: None,
+        }];
+        let issues = run_checks(&checks, &json, &path, "rule");
+        let mut paths: Vec<_> = issues.iter().map(|i| i.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                "$.scripts.lintFix",
+                "$.scripts.pre_publish",
+                "$.scripts[\"build.esm\"]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_off_level_required_reports_nothing() {
         let checks = vec![
-            Check::Const {
-                field: "x".into(),
-                value: json!("y"),
-                message: Some("Field at {{path}} must equal {{expected}}, got {{actual}}".into()),
-                level: None,
+            Check::Required {
+                fields: vec!["name".into()],
+                message: None,
+                messages: None,
+                level: Some("off".into()),
+                code: None,
             },
-            Check::Const {
-                field: "n".into(),
-                value: json!(4),
-                message: Some("Field at {{path}} must equal {{expected}}, got {{actual}}".into()),
-                level: None,
+            Check::Required {
+                fields: vec!["version".into()],
+                message: None,
+                messages: None,
+                level: Some("ignore".into()),
+                code: None,
             },
         ];
-        let issues = run_checks(&checks, &json, &path, "rule");
-        assert_eq!(issues.len(), 1);
-        assert_eq!(issues[0].path, "$.n");
-        // Message interpolation includes expected, actual, and path
-        assert!(issues[0].message.contains("must equal 4"));
-        assert!(issues[0].message.contains("got 3") || issues[0].message.contains("3"));
-        assert!(issues[0].message.contains("$.n"));
+        let issues = run_checks(&checks, &json!({}), &PathBuf::from("x.json"), "r");
+        assert!(issues.is_empty());
     }
 
     #[test]
-    fn test_pattern_match_and_mismatch() {
-        let json = json!({"v":"1.2.3", "w":"nope"});
-        let path = PathBuf::from("file.json");
+    fn test_issue_code_defaults_to_kind_and_honors_override() {
         let checks = vec![
-            Check::Pattern {
-                field: "v".into(),
-                regex: "^\\d+\\.\\d+\\.\\d+$".into(),
-                message: Some("Value '{{actual}}' at {{path}} must match {{pattern}}".into()),
+            Check::Required {
+                fields: vec!["name".into()],
+                message: None,
+                messages: None,
                 level: None,
+                code: None,
             },
-            Check::Pattern {
-                field: "w".into(),
-                regex: "^\\d+$".into(),
-                message: Some("Value '{{actual}}' at {{path}} must match {{pattern}}".into()),
+            Check::Type {
+                fields: HashMap::from([("version".to_string(), "string".to_string())]),
+                message: None,
                 level: None,
+                code: Some("PKG001".into()),
             },
         ];
-        let issues = run_checks(&checks, &json, &path, "rule");
-        assert_eq!(issues.len(), 1);
-        assert_eq!(issues[0].path, "$.w");
-        assert_eq!(issues[0].message, "Value 'nope' at $.w must match ^\\d+$");
+        let issues = run_checks(
+            &checks,
+            &json!({"version": 1}),
+            &PathBuf::from("x.json"),
+            "r",
+        );
+        let codes: Vec<_> = issues.iter().map(|i| i.code.as_deref()).collect();
+        assert_eq!(codes, vec![Some("required"), Some("PKG001")]);
     }
 
     #[test]
-    fn test_enum_match_and_mismatch() {
-        let json = json!({"k":"b", "n": 2});
-        let path = PathBuf::from("file.json");
-        let checks = vec![
-            Check::Enum {
-                field: "k".into(),
-                values: vec![json!("a"), json!("b")],
-                message: Some("Value at {{path}} must be one of {{expected}}, got {{actual}}".into()),
+    fn test_each_reports_per_index_paths() {
+        let checks = vec![Check::Each {
+            field: "$.contributors".into(),
+            checks: vec![Check::Required {
+                fields: vec!["name".into(), "email".into()],
+                message: None,
+                messages: None,
                 level: None,
-            },
-            Check::Enum {
-                field: "n".into(),
-                values: vec![json!(1), json!(3)],
-                message: Some("Value at {{path}} must be one of {{expected}}, got {{actual}}".into()),
+                code: None,
+            }],
+            message: None,
+            level: None,
+            code: None,
+        }];
+        let doc = json!({"contributors": [
+            {"name": "a", "email": "a@x.io"},
+            {"name": "b"},
+            {"email": "c@x.io"}
+        ]});
+        let issues = run_checks(&checks, &doc, &PathBuf::from("x.json"), "r");
+        let paths: Vec<_> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["$.contributors[1].email", "$.contributors[2].name"]
+        );
+        assert_eq!(
+            issues[0].message,
+            "Field 'email' is required at $.contributors[1].email"
+        );
+
+        let not_array = run_checks(
+            &checks,
+            &json!({"contributors": {}}),
+            &PathBuf::from("x.json"),
+            "r",
+        );
+        assert_eq!(not_array[0].path, "$.contributors");
+        assert_eq!(not_array[0].code.as_deref(), Some("each"));
+
+        // Nested const issues cannot be fixed by `--fix`
+        let consts = vec![Check::Each {
+            field: "$.people".into(),
+            checks: vec![Check::Const {
+                field: "role".into(),
+                value: json!("dev"),
+                ignore_case: None,
+                message: None,
                 level: None,
-            },
-        ];
-        let issues = run_checks(&checks, &json, &path, "rule");
+                code: None,
+            }],
+            message: None,
+            level: None,
+            code: None,
+        }];
+        let doc = json!({"people": [{"role": "ops"}]});
+        let issues = run_checks(&consts, &doc, &PathBuf::from("x.json"), "r");
+        assert_eq!(issues[0].path, "$.people[0].role");
+        assert!(!issues[0].fixable);
+    }
+
+    #[test]
+    fn test_reroot_message_replaces_whole_paths_only() {
+        assert_eq!(
+            reroot_message("Expected object at $, got $.a", "$", "$.xs[0]"),
+            "Expected object at $.xs[0], got $.a"
+        );
+        assert_eq!(
+            reroot_message("at $.a; not $.ab", "$.a", "$.xs[1].a"),
+            "at $.xs[1].a; not $.ab"
+        );
+    }
+
+    fn required(field: &str) -> Check {
+        Check::Required {
+            fields: vec![field.into()],
+            message: None,
+            messages: None,
+            level: None,
+            code: None,
+        }
+    }
+
+    #[test]
+    fn test_any_of_passes_when_one_alternative_holds() {
+        let checks = vec![Check::AnyOf {
+            checks: vec![required("main"), required("exports")],
+            message: None,
+            level: None,
+            code: None,
+        }];
+        let p = PathBuf::from("x.json");
+        assert!(run_checks(&checks, &json!({"exports": "."}), &p, "r").is_empty());
+        let issues = run_checks(&checks, &json!({}), &p, "r");
         assert_eq!(issues.len(), 1);
-        assert_eq!(issues[0].path, "$.n");
-        // Message interpolation includes expected set, actual value, and path
-        assert!(issues[0].message.contains("one of"));
-        assert!(issues[0].message.contains("2"));
-        assert!(issues[0].message.contains("$.n"));
+        assert_eq!(issues[0].code.as_deref(), Some("anyOf"));
+        assert!(issues[0]
+            .message
+            .starts_with("None of 2 alternatives matched: "));
+        assert!(issues[0].message.contains("$.exports"));
     }
 
     #[test]
-    fn test_min_max_length_boundaries() {
-        let json = json!({"s1":"ab", "s2":"a", "s3":"abc", "s4":"abcdef"});
-        let path = PathBuf::from("file.json");
-        let checks = vec![
-            Check::MinLength {
-                field: "s1".into(),
-                min: 2,
-                message: Some("String at {{path}} length must be >= {{expected}}, got {{actual}}".into()),
-                level: None,
-            }, // ok
-            Check::MinLength {
-                field: "s2".into(),
-                min: 2,
-                message: Some("String at {{path}} length must be >= {{expected}}, got {{actual}}".into()),
+    fn test_all_of_reports_each_failure_or_one_message() {
+        let mut checks = vec![Check::AllOf {
+            checks: vec![required("a"), required("b")],
+            message: None,
+            level: None,
+            code: None,
+        }];
+        let p = PathBuf::from("x.json");
+        assert_eq!(run_checks(&checks, &json!({}), &p, "r").len(), 2);
+        assert!(run_checks(&checks, &json!({"a": 1, "b": 2}), &p, "r").is_empty());
+        if let Check::AllOf { message, .. } = &mut checks[0] {
+            *message = Some("a and b are required".into());
+        }
+        let issues = run_checks(&checks, &json!({"a": 1}), &p, "r");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].message, "a and b are required");
+
+        let consts = vec![Check::AllOf {
+            checks: vec![Check::Const {
+                field: "private".into(),
+                value: json!(true),
+                ignore_case: None,
+                message: None,
                 level: None,
-            }, // fail
-            Check::MaxLength {
-                field: "s3".into(),
-                max: 3,
-                message: Some("String at {{path}} length must be <= {{expected}}, got {{actual}}".into()),
+                code: None,
+            }],
+            message: None,
+            level: None,
+            code: None,
+        }];
+        let issues = run_checks(&consts, &json!({"private": false}), &p, "r");
+        assert_eq!(issues.len(), 1);
+        assert!(!issues[0].fixable);
+    }
+
+    #[test]
+    fn test_not_reports_only_when_inner_check_passes() {
+        let checks = vec![Check::Not {
+            check: Box::new(Check::Const {
+                field: "private".into(),
+                value: json!(true),
+                ignore_case: None,
+                message: None,
                 level: None,
-            }, // ok
-            Check::MaxLength {
-                field: "s4".into(),
-                max: 5,
-                message: Some("String at {{path}} length must be <= {{expected}}, got {{actual}}".into()),
+                code: None,
+            }),
+            message: None,
+            level: None,
+            code: None,
+        }];
+        let p = PathBuf::from("x.json");
+        let issues = run_checks(&checks, &json!({"private": true}), &p, "r");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "$.private");
+        assert_eq!(
+            issues[0].message,
+            "Value at $.private must not satisfy 'const'"
+        );
+        assert!(run_checks(&checks, &json!({"private": false}), &p, "r").is_empty());
+    }
+
+    #[test]
+    fn test_not_and_any_of_skip_absent_or_disabled_inner_checks() {
+        let pattern = |level: Option<&str>| Check::Pattern {
+            field: "name".into(),
+            regex: "^tmp-".into(),
+            regex_from: None,
+            flags: None,
+            message: None,
+            level: level.map(Into::into),
+            code: None,
+        };
+        let not = |inner| {
+            vec![Check::Not {
+                check: Box::new(inner),
+                message: None,
                 level: None,
-            }, // fail
-        ];
-        let issues = run_checks(&checks, &json, &path, "rule");
-        let paths: std::collections::HashSet<_> = issues.iter().map(|i| i.path.clone()).collect();
-        assert_eq!(issues.len(), 2);
-        assert!(paths.contains("$.s2"));
-        assert!(paths.contains("$.s4"));
-        // Message interpolation includes expected, actual, and path in both issues
-        let m2 = issues.iter().find(|i| i.path == "$.s2").unwrap().message.clone();
-        assert!(m2.contains("$.s2"));
-        assert!(m2.contains(">= 2"));
-        let m4 = issues.iter().find(|i| i.path == "$.s4").unwrap().message.clone();
-        assert!(m4.contains("$.s4"));
-        assert!(m4.contains("<= 5"));
+                code: None,
+            }]
+        };
+        let p = PathBuf::from("x.json");
+        let named = json!({"name": "tmp-x"});
+        // Absent field: the inner pattern trivially passes but is not applicable
+        assert!(run_checks(&not(pattern(None)), &json!({}), &p, "r").is_empty());
+        assert_eq!(run_checks(&not(pattern(None)), &named, &p, "r").len(), 1);
+        assert!(run_checks(&not(pattern(Some("off"))), &named, &p, "r").is_empty());
+
+        let mut off = required("main");
+        if let Check::Required { level, .. } = &mut off {
+            *level = Some("off".into());
+        }
+        let any_of = vec![Check::AnyOf {
+            checks: vec![off, required("exports")],
+            message: None,
+            level: None,
+            code: None,
+        }];
+        let issues = run_checks(&any_of, &json!({}), &p, "r");
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0]
+            .message
+            .starts_with("None of 1 alternatives matched: "));
     }
 
     #[test]
-    fn test_required_message_interpolation_path() {
-        let json = json!({"a":1});
-        let path = PathBuf::from("file.json");
-        let checks = vec![Check::Required { fields: vec!["a".into(), "b".into()], message: Some("Field '{{field}}' missing at {{path}}".into()), level: None }];
-        let issues = run_checks(&checks, &json, &path, "rule");
+    fn test_object_values_reports_each_mistyped_value() {
+        let checks = vec![Check::ObjectValues {
+            field: "$.dependencies".into(),
+            value_kind: "string".into(),
+            message: None,
+            level: None,
+            code: None,
+        }];
+        let doc = json!({"dependencies": {"a": "^1.0.0", "b": 2, "c": {"v": "1"}, "./d": 3}});
+        let issues = run_checks(&checks, &doc, &PathBuf::from("x.json"), "r");
+        let got: Vec<_> = issues
+            .iter()
+            .map(|i| (i.path.as_str(), i.message.as_str()))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                (
+                    "$.dependencies.b",
+                    "Expected string at $.dependencies.b, got integer"
+                ),
+                (
+                    "$.dependencies.c",
+                    "Expected string at $.dependencies.c, got object"
+                ),
+                (
+                    "$.dependencies[\"./d\"]",
+                    "Expected string at $.dependencies[\"./d\"], got integer"
+                ),
+            ]
+        );
+        assert_eq!(get_json_path(&doc, &issues[2].path), Some(&json!(3)));
+        // Absent or non-object fields are left to other checks
+        assert!(run_checks(
+            &checks,
+            &json!({"dependencies": []}),
+            &PathBuf::from("x.json"),
+            "r"
+        )
+        .is_empty());
+        // The document root itself yields `$.key` paths
+        let root = vec![Check::ObjectValues {
+            field: "$".into(),
+            value_kind: "string".into(),
+            message: None,
+            level: None,
+            code: None,
+        }];
+        let doc = json!({"a": "x", "b": 1});
+        let issues = run_checks(&root, &doc, &PathBuf::from("x.json"), "r");
         assert_eq!(issues.len(), 1);
         assert_eq!(issues[0].path, "$.b");
-        assert_eq!(issues[0].message, "Field 'b' missing at $.b");
+    }
+
+    #[test]
+    fn test_discriminated_selects_variant_checks() {
+        let toml_src = r#"
+kind = "discriminated"
+discriminator = "source.type"
+[variants]
+npm = [{ kind = "required", fields = ["source.registry"] }]
+git = [{ kind = "required", fields = ["source.url"] }, { kind = "const", field = "source.ref", value = "main" }]
+"#;
+        let checks = vec![toml::from_str::<Check>(toml_src).unwrap()];
+        let p = PathBuf::from("x.json");
+        let run = |doc: Json| run_checks(&checks, &doc, &p, "r");
+
+        assert!(run(json!({"source": {"type": "git", "url": "u", "ref": "main"}})).is_empty());
+        let git = run(json!({"source": {"type": "git", "url": "u", "ref": "dev"}}));
+        assert_eq!(git[0].path, "$.source.ref");
+        assert!(!git[0].fixable);
+        let npm = run(json!({"source": {"type": "npm", "url": "u"}}));
+        assert_eq!(npm.len(), 1);
+        assert_eq!(npm[0].path, "$.source.registry");
+        let unknown = run(json!({"source": {"type": "svn"}}));
+        assert_eq!(
+            unknown[0].message,
+            "Unknown variant at $.source.type: svn (expected one of git, npm)"
+        );
+        assert_eq!(unknown[0].code.as_deref(), Some("discriminated"));
+        let missing = run(json!({"source": {}}));
+        assert!(missing[0].message.contains(": (missing) "));
     }
 }
@@ -15,6 +15,42 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub cmd: Commands,
+    #[arg(
+        long,
+        global = true,
+        action = clap::ArgAction::SetTrue,
+        help = "Never hit the network; only use cached conventions and file: sources (env: RIGRA_OFFLINE)"
+    )]
+    pub offline: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        help = "Also write info/note/error diagnostics to this file (stdout keeps results only)"
+    )]
+    pub log_file: Option<String>,
+    #[arg(
+        long,
+        global = true,
+        action = clap::ArgAction::SetTrue,
+        help = "Print nothing to stdout or stderr (including JSON); report only via exit code"
+    )]
+    pub silent: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        help = "Write a Chrome trace (JSON) of walk/parse/check timings to this file"
+    )]
+    pub profile_output: Option<String>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "WHEN",
+        value_parser = ["auto", "always", "never"],
+        help = "Color output: auto|always|never (default: auto; CLICOLOR_FORCE, NO_COLOR, CLICOLOR also honored)"
+    )]
+    pub color: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -23,9 +59,13 @@ pub enum Commands {
     /// Show version
     #[command(
         about = "Show version",
-        long_about = "Print the current rigra version."
+        long_about = "Print the current rigra version. With --verbose, also print the git commit, rustc version, target triple, and enabled cargo features (useful in bug reports).",
+        after_help = "Examples:\n  rigra version\n  rigra version --verbose"
     )]
-    Version,
+    Version {
+        #[arg(long, alias = "build-info", action = clap::ArgAction::SetTrue, help = "Include git commit, rustc version, target, and features")]
+        verbose: bool,
+    },
     /// Lint configs using TOML policies
     #[command(
         about = "Run lint checks",
@@ -39,14 +79,31 @@ pub enum Commands {
         scope: Option<String>,
         #[arg(long, help = "Output mode: human|json (default: human)")]
         output: Option<String>,
-        #[arg(long, help = "Path to index.toml (required)")]
+        #[arg(
+            long,
+            help = "Path to index.toml (required), or '-' to read an index bundle with inline [policies.<name>] from stdin"
+        )]
         index: Option<String>,
+        #[arg(long, action = clap::ArgAction::SetTrue, help = "Apply fixable edits before linting (deterministic; conflicts are reported)")]
+        fix: bool,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Only match files up to N levels below the repo root (1 = root files only)"
+        )]
+        max_depth: Option<usize>,
+        #[arg(long, action = clap::ArgAction::SetTrue, help = "Reuse results from .rigra/cache when targets, policies, index, and config are unchanged")]
+        cache: bool,
+        #[arg(long, action = clap::ArgAction::SetTrue, help = "Stop at the first file with an error-severity issue and report only that file")]
+        fail_fast: bool,
+        #[arg(long, action = clap::ArgAction::SetTrue, help = "Show the offending source line with a caret under each issue (human output)")]
+        show_source: bool,
     },
     /// Format files deterministically
     #[command(
         about = "Apply deterministic formatting",
         long_about = "Reorder keys and adjust line breaks per policy. When --diff or --check is set, write is disabled.",
-        after_help = "Examples:\n  rigra format --index conv/index.toml --diff\n  rigra format --index conv/index.toml --write"
+        after_help = "Examples:\n  rigra format --index conv/index.toml --diff\n  rigra format --index conv/index.toml --write\n  rigra format --index conv/index.toml --path package.json --stdout"
     )]
     Format {
         #[arg(long, help = "Repository root (default: current dir)")]
@@ -61,6 +118,10 @@ pub enum Commands {
         output: Option<String>,
         #[arg(long, help = "Path to index.toml (required)")]
         index: Option<String>,
+        #[arg(long, help = "Only format this file (relative to the repo root)")]
+        path: Option<String>,
+        #[arg(long, action = clap::ArgAction::SetTrue, requires = "path", conflicts_with_all = ["write", "diff", "check"], help = "Print the formatted content of --path to stdout; the file is not modified")]
+        stdout: bool,
     },
     /// Sync templates/configs
     #[command(
@@ -83,8 +144,28 @@ pub enum Commands {
         dry_run: bool,
         #[arg(long, action = clap::ArgAction::SetTrue, help = "Exit non-zero if changes would occur")]
         check: bool,
+        #[arg(long, action = clap::ArgAction::SetTrue, help = "Only validate rule `when` tokens against the policy's declared scopes")]
+        only_scope_check: bool,
+        #[arg(long, action = clap::ArgAction::SetTrue, help = "Overwrite merged targets (or prune files) that were edited outside sync since the last run")]
+        force: bool,
+        #[arg(long, action = clap::ArgAction::SetTrue, help = "Delete files synced by earlier runs that the policy no longer produces")]
+        prune: bool,
     },
-    /// Convention management (install/list/prune/path)
+    /// Run a policy against inline JSON
+    #[command(
+        about = "Test a policy against sample JSON",
+        long_about = "Run a policy's checks and order lint against JSON given inline, as a file path, or '-' for stdin. No index or fixture files needed.",
+        after_help = "Examples:\n  rigra test-policy --policy conv/policy.toml --input '{\"name\":\"x\"}'\n  echo '{}' | rigra test-policy --policy conv/policy.toml --input -"
+    )]
+    TestPolicy {
+        #[arg(long, help = "Path to policy TOML")]
+        policy: String,
+        #[arg(long, help = "JSON literal, path to a JSON file, or '-' for stdin")]
+        input: String,
+        #[arg(long, help = "Output mode: human|json (default: human)")]
+        output: Option<String>,
+    },
+    /// Convention management (install/list/prune/path/pack)
     Conv {
         #[command(subcommand)]
         cmd: ConvCmd,
@@ -97,16 +178,33 @@ pub enum ConvCmd {
     /// Install a convention into cache
     #[command(
         about = "Install convention",
-        long_about = "Install a convention archive into repo cache under .rigra/conv."
+        long_about = "Install a convention archive into repo cache under .rigra/conv.\n\nEach fresh install is pinned in conv.lock at the repo root. Without arguments, installs exactly the entries pinned in conv.lock and fails if an archive checksum changed."
     )]
     Install {
         #[arg(long, help = "Repository root (default: current dir)")]
         repo_root: Option<String>,
-        /// Optional source override: gh:owner/repo@tag or file:/abs/path
+        /// Optional source override: gh:owner/repo@tag, git:<url>#<ref>, https://…/conv.tar.gz, or file:/abs/path
         source: Option<String>,
         /// Optional name@version override for cache key
         #[arg(long, help = "Override name@version used as cache folder key")]
         name: Option<String>,
+        /// Expected sha256 of the archive (hex); verified before extraction
+        #[arg(long, help = "Expected sha256 (hex) of the downloaded archive")]
+        sha256: Option<String>,
+    },
+    /// Re-fetch a cached convention
+    #[command(
+        about = "Update convention",
+        long_about = "Remove the cached name@version entry and install it again, e.g. after a tag moved. Installing a new version leaves older versions in place. Without a source, the one pinned in conv.lock is used. The refreshed entry is re-pinned in conv.lock.",
+        after_help = "Examples:\n  rigra conv update myconv@v0.1.0\n  rigra conv update myconv@v0.2.0 gh:owner/myconv@v0.2.0"
+    )]
+    Update {
+        #[arg(long, help = "Repository root (default: current dir)")]
+        repo_root: Option<String>,
+        #[arg(help = "Convention name@version to refresh")]
+        name: String,
+        /// Source to fetch from (default: the source pinned in conv.lock)
+        source: Option<String>,
     },
     /// List installed conventions
     #[command(
@@ -116,6 +214,10 @@ pub enum ConvCmd {
     Ls {
         #[arg(long, help = "Repository root (default: current dir)")]
         repo_root: Option<String>,
+        #[arg(long, action = clap::ArgAction::SetTrue, help = "Show each convention's files as a tree, marking the index and referenced policies")]
+        tree: bool,
+        #[arg(long, default_value_t = 3, help = "Maximum tree depth for --tree")]
+        depth: usize,
     },
     /// Prune all convention cache
     #[command(
@@ -126,6 +228,32 @@ pub enum ConvCmd {
         #[arg(long, help = "Repository root (default: current dir)")]
         repo_root: Option<String>,
     },
+    /// Pack a convention directory into a tarball
+    #[command(
+        about = "Pack convention",
+        long_about = "Validate a convention directory (index, referenced policies, sync policy) and pack it into a gzip tarball installable via file: sources. Prints the archive's sha256.",
+        after_help = "Examples:\n  rigra conv pack conventions/ts-base --out dist/ts-base-v0.1.0.tar.gz"
+    )]
+    Pack {
+        #[arg(help = "Convention directory containing index.toml")]
+        dir: String,
+        #[arg(long, value_name = "FILE", help = "Output tarball path (.tar.gz)")]
+        out: String,
+    },
+    /// Show metadata about a cached convention
+    #[command(
+        about = "Show convention info",
+        long_about = "Show a cached convention's version, source and digest (from conv.lock), install date, directory size, and the rule ids and patterns declared in its index.",
+        after_help = "Examples:\n  rigra conv info conv:myconv@v0.1.0\n  rigra conv info conv:myconv@v0.1.0 --output json"
+    )]
+    Info {
+        #[arg(long, help = "Repository root (default: current dir)")]
+        repo_root: Option<String>,
+        #[arg(long, help = "Output mode: human|json (default: human)")]
+        output: Option<String>,
+        #[arg(help = "Convention ref: conv:name@ver[:subpath]")]
+        conv: String,
+    },
     /// Resolve a conv path (conv:name@ver[:subpath])
     #[command(
         about = "Resolve path",
@@ -8,6 +8,8 @@
 //! - `output`: `human`
 //! - `format.write|diff|check`: false
 //! - `format.strictLineBreak`: true
+//! - `format.maxWidth`: unset (arrays always expanded one item per line)
+//! - `format.normalizeStrings`: true (canonical string escapes)
 //! - `format.linebreak.{between_groups,before_fields,in_fields}`: optional
 //!
 //! Overrides precedence: CLI > config file > defaults.
@@ -24,6 +26,13 @@ pub struct FormatCfg {
     pub check: Option<bool>,
     #[serde(rename = "strictLineBreak")]
     pub strict_linebreak: Option<bool>,
+    /// Collapse arrays of scalars onto one line when they fit within this width
+    #[serde(rename = "maxWidth")]
+    pub max_width: Option<usize>,
+    /// Canonicalize string escapes (default true); false keeps each string's
+    /// original spelling, e.g. `\u00e9` or `\/`
+    #[serde(rename = "normalizeStrings")]
+    pub normalize_strings: Option<bool>,
     pub linebreak: Option<LineBreakCfg>,
 }
 
@@ -62,6 +71,8 @@ pub struct Effective {
     pub diff: bool,
     pub check: bool,
     pub strict_linebreak: bool,
+    pub max_width: Option<usize>,
+    pub normalize_strings: bool,
     pub lb_between_groups: Option<bool>,
     pub lb_before_fields: std::collections::HashMap<String, String>,
     pub lb_in_fields: std::collections::HashMap<String, String>,
@@ -90,9 +101,11 @@ pub struct SyncCfg {
     #[serde(default)]
     pub config: Option<std::collections::HashMap<String, SyncClientCfg>>, // [sync.config.<id>]
     #[serde(default)]
-    pub hooks: Option<SyncHooks>, // [sync.hooks.post]
+    pub hooks: Option<SyncHooks>, // [sync.hooks.pre] / [sync.hooks.post]
     /// Default write behavior for `rigra sync` when CLI flags are absent
     pub write: Option<bool>,
+    /// Default for `rigra sync --prune`: delete stale previously-synced files
+    pub prune: Option<bool>,
     /// Ignore specific sync IDs entirely
     #[serde(default)]
     pub ignore: Option<Vec<String>>, // [sync].ignore = ["id1","id2"]
@@ -100,6 +113,9 @@ pub struct SyncCfg {
 
 #[derive(Debug, Default, Deserialize, Clone)]
 pub struct SyncHooks {
+    /// Commands run before a rule syncs (with `--write`); a failure skips the rule
+    #[serde(default)]
+    pub pre: Option<std::collections::HashMap<String, Vec<String>>>,
     #[serde(default)]
     pub post: Option<std::collections::HashMap<String, Vec<String>>>,
 }
@@ -108,6 +124,9 @@ pub struct SyncHooks {
 pub struct SyncClientCfg {
     pub target: Option<String>,
     pub merge: Option<SyncClientMergeCfg>,
+    /// Copy a target to `<target>.rigra.bak` before sync overwrites it
+    #[serde(default)]
+    pub backup: Option<bool>,
 }
 
 #[derive(Debug, Default, Deserialize, Clone)]
@@ -119,7 +138,28 @@ pub struct SyncClientMergeCfg {
     #[serde(default, rename = "noSync")]
     pub nosync_paths: Vec<String>,
     #[serde(default)]
-    pub array: Option<std::collections::HashMap<String, String>>, // path -> union|replace
+    pub array: Option<std::collections::HashMap<String, ArrayStrategy>>, // path -> strategy
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+/// Array merge strategy for one path: `"union"`, `"replace"`, or
+/// `{ strategy = "by-key", key = "name" }` (shorthand `"by:name"`) to
+/// deep-merge elements sharing a key.
+pub enum ArrayStrategy {
+    Named(String),
+    Keyed { strategy: String, key: String },
+}
+
+impl ArrayStrategy {
+    /// Key field for by-key merging, from either the table or `by:<key>` form.
+    pub fn merge_key(&self) -> Option<&str> {
+        match self {
+            ArrayStrategy::Keyed { strategy, key } if strategy == "by-key" => Some(key),
+            ArrayStrategy::Named(s) => s.strip_prefix("by:").filter(|k| !k.is_empty()),
+            ArrayStrategy::Keyed { .. } => None,
+        }
+    }
 }
 
 /// Walk upward from `start` to detect the repository root.
@@ -165,7 +205,23 @@ pub fn resolve_effective(
 ) -> Effective {
     let start = PathBuf::from(cli_repo_root.unwrap_or("."));
     let repo_root = detect_repo_root(&start);
-    let cfg = load_config(&repo_root).unwrap_or_default();
+    crate::diag::debug(|| {
+        format!(
+            "repo root: {} (searched from {})",
+            repo_root.display(),
+            start.display()
+        )
+    });
+    let cfg = match load_config(&repo_root) {
+        Some(cfg) => {
+            crate::diag::debug(|| format!("config: {}", repo_root.join("rigra.toml").display()));
+            cfg
+        }
+        None => {
+            crate::diag::debug(|| "config: none found, using defaults");
+            RigletConfig::default()
+        }
+    };
 
     let index_src = cli_index.map(|s| s.to_string()).or(cfg.index);
     let (mut index, mut index_configured) = match index_src.clone() {
@@ -197,6 +253,12 @@ pub fn resolve_effective(
         .as_ref()
         .and_then(|f| f.strict_linebreak)
         .unwrap_or(true);
+    let max_width = cfg.format.as_ref().and_then(|f| f.max_width);
+    let normalize_strings = cfg
+        .format
+        .as_ref()
+        .and_then(|f| f.normalize_strings)
+        .unwrap_or(true);
     let lb_between_groups = cfg
         .format
         .as_ref()
@@ -231,14 +293,29 @@ pub fn resolve_effective(
     // Resolve conv index if specified using Option A: conv:name@ver[:subpath]
     if let Some(ref idx) = index_src {
         if let Some(cr) = crate::conv::parse_conv_ref(idx) {
+            // Ranges (`conv:acme@^1.2`) pin to the highest cached match
+            let cr = match crate::conv::resolve_version(&repo_root, &cr) {
+                Ok(pinned) => pinned,
+                Err(e) => {
+                    crate::diag::error(e);
+                    cr
+                }
+            };
             let resolved = crate::conv::resolve_path(&repo_root, &cr);
-            // If not present, optionally auto-install from sources map
-            if !resolved.exists() && conv_auto_install {
+            // If not present, optionally auto-install from sources map; a
+            // range that matched nothing has no concrete version to install
+            if !resolved.exists()
+                && conv_auto_install
+                && crate::conv::version_req(&cr.ver).is_none()
+            {
                 if let Some(src) = conv_source.as_ref() {
                     let name_ver = format!("{}@{}", cr.name, cr.ver);
-                    let _ = crate::conv::install(&repo_root, &name_ver, src);
+                    let _ = crate::conv::install(&repo_root, &name_ver, src, None);
                 }
             }
+            if !resolved.exists() && crate::conv::is_offline() {
+                report_offline_missing(&cr.name, &cr.ver);
+            }
             index = resolved
                 .strip_prefix(&repo_root)
                 .unwrap_or(resolved.as_path())
@@ -271,9 +348,12 @@ pub fn resolve_effective(
                                     src_str = format!("gh:{}/{}@{}", owner, repo, ver);
                                 }
                             }
-                            let _ = crate::conv::install(&repo_root, pkg, &src_str);
+                            let _ = crate::conv::install(&repo_root, pkg, &src_str, None);
                         }
                     }
+                    if !resolved.exists() && crate::conv::is_offline() {
+                        report_offline_missing(name, ver);
+                    }
                     index = resolved
                         .strip_prefix(&repo_root)
                         .unwrap_or(resolved.as_path())
@@ -285,6 +365,12 @@ pub fn resolve_effective(
         }
     }
 
+    crate::diag::debug(|| match (&index_src, index_configured) {
+        (_, false) => "index: not configured".to_string(),
+        (Some(src), true) if *src != index => format!("index: {} (from {})", index, src),
+        _ => format!("index: {}", index),
+    });
+
     Effective {
         repo_root,
         index,
@@ -295,6 +381,8 @@ pub fn resolve_effective(
         diff,
         check,
         strict_linebreak,
+        max_width,
+        normalize_strings,
         lb_between_groups,
         lb_before_fields,
         lb_in_fields,
@@ -302,6 +390,14 @@ pub fn resolve_effective(
     }
 }
 
+/// Explain why a `conv:` index cannot be resolved while offline.
+fn report_offline_missing(name: &str, ver: &str) {
+    crate::diag::error(format!(
+        "Offline mode: convention '{}@{}' is not cached under .rigra/conv. Install it while online or from a file: source.",
+        name, ver
+    ));
+}
+
 pub fn rsplit_once_at(s: &str, ch: char) -> Option<(&str, &str)> {
     let mut iter = s.rsplitn(2, ch);
     let b = iter.next()?;
@@ -370,6 +466,8 @@ write = true
 diff = false
 check = false
 strictLineBreak = true
+maxWidth = 100
+normalizeStrings = false
 [format.linebreak]
 between_groups = false
 [format.linebreak.before_fields]
@@ -383,6 +481,8 @@ scripts = "keep"
         // CLI overrides write=false should take precedence over config write=true
         let eff = resolve_effective(root.to_str(), None, None, None, Some(false), None, None);
         assert!(!eff.write);
+        assert_eq!(eff.max_width, Some(100));
+        assert!(!eff.normalize_strings);
         // Linebreak overrides should be loaded from config
         assert_eq!(eff.lb_between_groups, Some(false));
         assert_eq!(
@@ -487,4 +587,29 @@ source = "github"
         assert_eq!(root.join(&eff.index).to_string_lossy(), expected);
         // No installation attempted since autoInstall=false; file won't exist.
     }
+
+    #[test]
+    fn test_sync_array_strategy_accepts_name_or_keyed_table() {
+        let cfg: SyncClientMergeCfg = toml::from_str(
+            r#"
+[array]
+"$.files" = "union"
+"$.jobs" = { strategy = "by-key", key = "name" }
+"$.plugins" = "by:id"
+"#,
+        )
+        .unwrap();
+        let arr = cfg.array.unwrap();
+        assert_eq!(arr["$.files"], ArrayStrategy::Named("union".into()));
+        assert_eq!(
+            arr["$.jobs"],
+            ArrayStrategy::Keyed {
+                strategy: "by-key".into(),
+                key: "name".into()
+            }
+        );
+        assert_eq!(arr["$.files"].merge_key(), None);
+        assert_eq!(arr["$.jobs"].merge_key(), Some("name"));
+        assert_eq!(arr["$.plugins"].merge_key(), Some("id"));
+    }
 }
@@ -2,12 +2,60 @@
 //!
 //! Implements minimal functions to:
 //! - Parse `conv:` index strings (`conv:name@ver[:subpath]`)
-//! - Resolve cache path under `.rigra/conv/name@ver/subpath`
-//! - Install conventions from sources: `gh:owner/repo@tag` or `file:/abs/path`
-//! - List and prune cache
+//! - Resolve cache path under `.rigra/conv/name@ver/subpath`, picking the
+//!   highest cached version when `ver` is a semver range with an explicit
+//!   operator (`^1.2`, `~1.2.3`, `>=1.3`)
+//! - Install conventions from sources: `gh:owner/repo@tag`, `git:<url>#<ref>`,
+//!   `https://host/path/conv.tar.gz`, or `file:/abs/path`
+//! - Update (re-fetch) a single cached convention, list and prune cache
+//! - Describe a cached convention (source, install date, size, rules)
+//! - Record installs in `conv.lock` and reinstall exactly those pins
+//! - Pack a convention directory into a `.tar.gz` for `file:` sources
+//!
+//! Archive layout: archive sources (`gh:`, `https:`/`http:`, `file:`) are
+//! `.tar.gz` or `.zip` files holding a single top-level directory with
+//! `index.toml` at its root (the shape GitHub release archives and
+//! `rigra conv pack` produce). That directory is stripped on extraction.
+//! Zips are recognized by extension or magic bytes and always extracted
+//! in-process.
+//!
+//! Fetching shells out to `curl` and `tar` by default. Builds with the
+//! `native-fetch` feature download and extract in-process instead; set
+//! `RIGRA_FETCH=system` to fall back to the external binaries.
+//!
+//! Offline contract: when offline mode is active (`--offline` or
+//! `RIGRA_OFFLINE=1`), rigra never touches the network. `install` only
+//! accepts `file:` sources and `conv:` references must already be cached.
 
+use crate::models::index::Index;
+use crate::models::policy::Policy;
+use crate::models::sync_policy::SyncPolicy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Force offline mode for this process (set from the `--offline` flag).
+pub fn set_offline(on: bool) {
+    OFFLINE.store(on, Ordering::Relaxed);
+}
+
+/// Whether offline mode is active via flag or `RIGRA_OFFLINE` env var.
+pub fn is_offline() -> bool {
+    if OFFLINE.load(Ordering::Relaxed) {
+        return true;
+    }
+    match std::env::var("RIGRA_OFFLINE") {
+        Ok(v) => {
+            let v = v.trim();
+            !(v.is_empty() || v == "0" || v.eq_ignore_ascii_case("false"))
+        }
+        Err(_) => false,
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct ConvRef {
@@ -45,6 +93,60 @@ pub fn resolve_path(repo_root: &Path, cr: &ConvRef) -> PathBuf {
         .join(&cr.subpath)
 }
 
+/// The semver range in `ver`, or `None` when it is an exact version
+/// (`v1.2.3`) or not semver at all (e.g. a branch name). Only versions
+/// starting with an operator (`^ ~ > < = *`) are ranges, so partial tags
+/// such as `1.2` or `2024` stay literal.
+pub fn version_req(ver: &str) -> Option<semver::VersionReq> {
+    if !ver.trim_start().starts_with(['^', '~', '>', '<', '=', '*']) {
+        return None;
+    }
+    semver::VersionReq::parse(ver).ok()
+}
+
+fn parse_version(ver: &str) -> Option<semver::Version> {
+    semver::Version::parse(ver.trim_start_matches('v')).ok()
+}
+
+/// Pin a range version in `cr` to the highest cached version satisfying it.
+/// Exact and non-semver versions are returned unchanged.
+pub fn resolve_version(repo_root: &Path, cr: &ConvRef) -> Result<ConvRef, String> {
+    let Some(req) = version_req(&cr.ver) else {
+        return Ok(cr.clone());
+    };
+    let prefix = cache_key(&cr.name, "");
+    let cached: Vec<(semver::Version, String)> = list(repo_root)
+        .into_iter()
+        .filter_map(|key| {
+            let ver = key.strip_prefix(&prefix)?.to_string();
+            parse_version(&ver).map(|v| (v, ver))
+        })
+        .collect();
+    let best = cached
+        .iter()
+        .filter(|(v, _)| req.matches(v))
+        .max_by(|a, b| a.0.cmp(&b.0));
+    match best {
+        Some((_, ver)) => Ok(ConvRef {
+            ver: ver.clone(),
+            ..cr.clone()
+        }),
+        None => {
+            let have: Vec<&str> = cached.iter().map(|(_, v)| v.as_str()).collect();
+            Err(format!(
+                "no cached version of '{}' satisfies '{}' (cached: {})",
+                cr.name,
+                cr.ver,
+                if have.is_empty() {
+                    "none".to_string()
+                } else {
+                    have.join(", ")
+                }
+            ))
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Source {
     Gh {
@@ -55,6 +157,15 @@ pub enum Source {
     File {
         path: String,
     },
+    /// Any git remote cloned at a branch or tag (`git:<url>#<ref>`)
+    Git {
+        url: String,
+        reference: String,
+    },
+    /// A `.tar.gz` served over plain HTTP(S), e.g. an internal artifact server
+    Http {
+        url: String,
+    },
 }
 
 pub fn parse_source(s: &str) -> Option<Source> {
@@ -73,74 +184,553 @@ pub fn parse_source(s: &str) -> Option<Source> {
             path: rest.to_string(),
         });
     }
+    if s.starts_with("https:") || s.starts_with("http:") {
+        return Some(Source::Http { url: s.to_string() });
+    }
+    if let Some(rest) = s.strip_prefix("git:") {
+        // git:<url>#<ref>; split at the last '#' so the url stays intact
+        let (url, reference) = rest.rsplit_once('#')?;
+        // A leading '-' would reach git as an option rather than a url
+        if url.is_empty() || reference.is_empty() || url.starts_with('-') {
+            return None;
+        }
+        return Some(Source::Git {
+            url: url.to_string(),
+            reference: reference.to_string(),
+        });
+    }
     None
 }
 
 /// Install a convention into repo cache.
-/// Uses system `curl` and `tar` to keep binary small.
-pub fn install(repo_root: &Path, name_ver: &str, source_str: &str) -> Result<PathBuf, String> {
+/// Uses system `curl`, `tar`, and `git` to keep binary small.
+///
+/// When `sha256` is given, the archive is verified against it before
+/// extraction; a mismatch removes the download and the cache entry.
+pub fn install(
+    repo_root: &Path,
+    name_ver: &str,
+    source_str: &str,
+    sha256: Option<&str>,
+) -> Result<PathBuf, String> {
+    install_entry(repo_root, name_ver, source_str, sha256).map(|(path, _)| path)
+}
+
+/// Like [`install`], but also returns the lock entry for a fresh install
+/// (`None` when the convention was already cached).
+pub fn install_entry(
+    repo_root: &Path,
+    name_ver: &str,
+    source_str: &str,
+    sha256: Option<&str>,
+) -> Result<(PathBuf, Option<LockEntry>), String> {
+    install_entry_with(repo_root, name_ver, source_str, sha256, is_offline())
+}
+
+/// [`install_entry`] with offline mode given explicitly rather than read
+/// from the process-wide flag.
+fn install_entry_with(
+    repo_root: &Path,
+    name_ver: &str,
+    source_str: &str,
+    sha256: Option<&str>,
+    offline: bool,
+) -> Result<(PathBuf, Option<LockEntry>), String> {
     let src = parse_source(source_str).ok_or_else(|| "invalid source".to_string())?;
+    if sha256.is_some() && matches!(src, Source::Git { .. }) {
+        return Err("--sha256 is only supported for archive sources".to_string());
+    }
     let (name, ver) = name_ver
         .rsplit_once('@')
         .ok_or_else(|| "name must be in form name@version".to_string())?;
     let dest_root = cache_root(repo_root).join(cache_key(name, ver));
     if dest_root.exists() {
-        return Ok(dest_root);
+        return Ok((dest_root, None));
+    }
+    if offline && !matches!(src, Source::File { .. }) {
+        return Err(format!(
+            "offline mode: '{}' is not cached and only file: sources are allowed",
+            name_ver
+        ));
     }
     fs::create_dir_all(&dest_root).map_err(|e| format!("create cache dir: {}", e))?;
-    match src {
+    let res = match src {
         Source::Gh { owner, repo, tag } => {
             let url = format!(
                 "https://github.com/{}/{}/archive/refs/tags/{}.tar.gz",
                 owner, repo, tag
             );
-            let tmp = repo_root
-                .join(".rigra")
-                .join("tmp")
-                .join(format!("{}-{}-{}.tar.gz", owner, repo, tag));
-            let tmp_parent = tmp.parent().unwrap_or(Path::new("."));
-            fs::create_dir_all(tmp_parent).map_err(|e| format!("prepare tmp: {}", e))?;
-            let mut cmd = std::process::Command::new("curl");
-            let st = cmd
-                .args(["-fsSL", &url, "-o"])
-                .arg(&tmp)
-                .status()
-                .map_err(|e| format!("curl exec failed: {}", e))?;
-            if !st.success() {
-                return Err(format!("curl download failed: exit {}", st));
+            let tmp = tmp_archive(repo_root, &format!("{}-{}-{}", owner, repo, tag));
+            download_and_extract(&url, &tmp, &dest_root, sha256).map(|d| (url, Some(d)))
+        }
+        Source::Http { url } => {
+            let tmp = tmp_archive(repo_root, &cache_key(name, ver));
+            download_and_extract(&url, &tmp, &dest_root, sha256).map(|d| (url, Some(d)))
+        }
+        Source::Git { url, reference } => clone_into(&url, &reference, &dest_root)
+            .map(|_| (format!("git:{}#{}", url, reference), None)),
+        Source::File { path } => {
+            let archive = Path::new(&path);
+            check_sha256(archive, sha256).and_then(|d| {
+                extract_archive(archive, &dest_root)?;
+                Ok((format!("file:{}", path), Some(d)))
+            })
+        }
+    };
+    match res {
+        Ok((source, sha256)) => {
+            let entry = LockEntry {
+                name: name.to_string(),
+                version: ver.to_string(),
+                source,
+                sha256,
+            };
+            Ok((dest_root, Some(entry)))
+        }
+        Err(e) => {
+            // Never leave a half-installed entry that would look cached
+            let _ = fs::remove_dir_all(&dest_root);
+            Err(e)
+        }
+    }
+}
+
+/// One pinned convention in `conv.lock`. `source` is the resolved source
+/// (`gh:` refs are stored as their archive URL) and `sha256` the archive
+/// digest; git sources have no archive and carry no digest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockEntry {
+    pub name: String,
+    pub version: String,
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+/// Contents of `conv.lock`, kept sorted by name then version.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(default, rename = "conv")]
+    pub entries: Vec<LockEntry>,
+}
+
+impl Lockfile {
+    /// Insert or replace the entry for the same `name@version`.
+    pub fn upsert(&mut self, entry: LockEntry) {
+        self.entries
+            .retain(|e| !(e.name == entry.name && e.version == entry.version));
+        self.entries.push(entry);
+        self.entries
+            .sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+    }
+}
+
+/// Path of the lockfile at the repo root.
+pub fn lock_path(repo_root: &Path) -> PathBuf {
+    repo_root.join("conv.lock")
+}
+
+/// Parse lockfile text.
+pub fn parse_lock(s: &str) -> Result<Lockfile, String> {
+    toml::from_str(s).map_err(|e| format!("invalid conv.lock: {}", e))
+}
+
+/// Render a lockfile as TOML with a generated-file header.
+pub fn render_lock(lock: &Lockfile) -> Result<String, String> {
+    let body = toml::to_string(lock).map_err(|e| format!("serialize conv.lock: {}", e))?;
+    Ok(format!(
+        "# Generated by `rigra conv install`. Do not edit by hand.\n\n{}",
+        body
+    ))
+}
+
+/// Read `conv.lock` from the repo root; `None` when it does not exist.
+pub fn read_lock(repo_root: &Path) -> Result<Option<Lockfile>, String> {
+    let path = lock_path(repo_root);
+    match fs::read_to_string(&path) {
+        Ok(s) => parse_lock(&s).map(Some),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("read {}: {}", path.to_string_lossy(), e)),
+    }
+}
+
+/// Write `conv.lock` to the repo root.
+pub fn write_lock(repo_root: &Path, lock: &Lockfile) -> Result<(), String> {
+    let path = lock_path(repo_root);
+    fs::write(&path, render_lock(lock)?)
+        .map_err(|e| format!("write {}: {}", path.to_string_lossy(), e))
+}
+
+/// Pin `entry` in the repo's `conv.lock`, creating the file if needed.
+pub fn record_lock(repo_root: &Path, entry: LockEntry) -> Result<(), String> {
+    let mut lock = read_lock(repo_root)?.unwrap_or_default();
+    lock.upsert(entry);
+    write_lock(repo_root, &lock)
+}
+
+/// Re-fetch `name@ver` from `source_str`, replacing only that cache entry;
+/// other cached versions of the same convention are left in place. The
+/// previous copy is restored if the fresh install fails.
+pub fn update(
+    repo_root: &Path,
+    name_ver: &str,
+    source_str: &str,
+) -> Result<(PathBuf, Option<LockEntry>), String> {
+    let (name, ver) = name_ver
+        .rsplit_once('@')
+        .ok_or_else(|| "name must be in form name@version".to_string())?;
+    let key = cache_key(name, ver);
+    let dest_root = cache_root(repo_root).join(&key);
+    if !dest_root.exists() {
+        return install_entry(repo_root, name_ver, source_str, None);
+    }
+    let backup = repo_root
+        .join(".rigra")
+        .join("tmp")
+        .join(format!("update-{}", key));
+    let _ = fs::remove_dir_all(&backup);
+    fs::create_dir_all(backup.parent().unwrap_or(Path::new(".")))
+        .map_err(|e| format!("prepare tmp: {}", e))?;
+    fs::rename(&dest_root, &backup).map_err(|e| format!("move old cache entry: {}", e))?;
+    match install_entry(repo_root, name_ver, source_str, None) {
+        Ok(res) => {
+            let _ = fs::remove_dir_all(&backup);
+            Ok(res)
+        }
+        Err(e) => {
+            let _ = fs::rename(&backup, &dest_root);
+            Err(e)
+        }
+    }
+}
+
+/// Install every entry pinned in `lock`, verifying archive digests.
+pub fn install_locked(repo_root: &Path, lock: &Lockfile) -> Result<Vec<PathBuf>, String> {
+    lock.entries
+        .iter()
+        .map(|e| {
+            let name_ver = format!("{}@{}", e.name, e.version);
+            install(repo_root, &name_ver, &e.source, e.sha256.as_deref())
+                .map_err(|err| format!("{}: {}", name_ver, err))
+        })
+        .collect()
+}
+
+/// Download location for a tarball source under `.rigra/tmp`.
+fn tmp_archive(repo_root: &Path, stem: &str) -> PathBuf {
+    repo_root
+        .join(".rigra")
+        .join("tmp")
+        .join(format!("{}.tar.gz", stem))
+}
+
+/// Fetch `url` to `tmp`, verify it against `sha256` if given, then extract
+/// it into `dest`. Returns the archive digest.
+fn download_and_extract(
+    url: &str,
+    tmp: &Path,
+    dest: &Path,
+    sha256: Option<&str>,
+) -> Result<String, String> {
+    let tmp_parent = tmp.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(tmp_parent).map_err(|e| format!("prepare tmp: {}", e))?;
+    download(url, tmp)?;
+    let digest = match check_sha256(tmp, sha256) {
+        Ok(d) => d,
+        Err(e) => {
+            let _ = fs::remove_file(tmp);
+            return Err(e);
+        }
+    };
+    extract_archive(tmp, dest)?;
+    Ok(digest)
+}
+
+/// Download `url` to `out` with `curl` (or in-process with `native-fetch`).
+fn download(url: &str, out: &Path) -> Result<(), String> {
+    #[cfg(feature = "native-fetch")]
+    if native_fetch() {
+        return native::download(url, out);
+    }
+    let mut cmd = std::process::Command::new("curl");
+    let st = cmd
+        .args(["-fsSL", url, "-o"])
+        .arg(out)
+        .status()
+        .map_err(|e| format!("curl exec failed: {}", e))?;
+    if !st.success() {
+        return Err(format!("curl download failed: exit {}", st));
+    }
+    Ok(())
+}
+
+/// Whether `archive` is a zip, by `.zip` extension or the `PK\x03\x04` magic.
+fn is_zip(archive: &Path) -> bool {
+    use std::io::Read;
+    if archive
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("zip"))
+    {
+        return true;
+    }
+    let mut magic = [0u8; 4];
+    fs::File::open(archive)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .is_ok()
+        && magic == *b"PK\x03\x04"
+}
+
+/// Extract a zip into `dest`, dropping its single top-level directory like
+/// `--strip-components 1` does for tarballs. Entries whose names would
+/// escape `dest` are rejected.
+fn extract_zip(archive: &Path, dest: &Path) -> Result<(), String> {
+    let file = fs::File::open(archive).map_err(|e| format!("open archive: {}", e))?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("zip extract failed: {}", e))?;
+    for i in 0..zip.len() {
+        let mut entry = zip
+            .by_index(i)
+            .map_err(|e| format!("zip extract failed: {}", e))?;
+        let name = entry
+            .enclosed_name()
+            .ok_or_else(|| format!("unsafe archive entry: {}", entry.name()))?;
+        let rel: PathBuf = name.components().skip(1).collect();
+        if rel.as_os_str().is_empty() {
+            continue;
+        }
+        let out = dest.join(&rel);
+        if entry.is_dir() {
+            fs::create_dir_all(&out).map_err(|e| format!("create dir: {}", e))?;
+            continue;
+        }
+        if let Some(parent) = out.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("create dir: {}", e))?;
+        }
+        let mut f =
+            fs::File::create(&out).map_err(|e| format!("write {}: {}", rel.display(), e))?;
+        std::io::copy(&mut entry, &mut f).map_err(|e| format!("zip extract failed: {}", e))?;
+        #[cfg(unix)]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(&out, fs::Permissions::from_mode(mode & 0o7777));
+        }
+    }
+    Ok(())
+}
+
+/// Digest of `archive`, failing if it differs from the expected hex `want`.
+fn check_sha256(archive: &Path, want: Option<&str>) -> Result<String, String> {
+    let got = sha256_file(archive)?;
+    if let Some(want) = want {
+        if !got.eq_ignore_ascii_case(want.trim()) {
+            return Err(format!(
+                "sha256 mismatch for {}: expected {}, got {}",
+                archive.to_string_lossy(),
+                want.trim(),
+                got
+            ));
+        }
+    }
+    Ok(got)
+}
+
+/// Extract a `.tar.gz` into `dest`, dropping its single top-level directory.
+fn extract_archive(archive: &Path, dest: &Path) -> Result<(), String> {
+    if is_zip(archive) {
+        return extract_zip(archive, dest);
+    }
+    #[cfg(feature = "native-fetch")]
+    if native_fetch() {
+        return native::extract(archive, dest);
+    }
+    let mut tar = std::process::Command::new("tar");
+    let st = tar
+        .arg("-xzf")
+        .arg(archive)
+        .arg("-C")
+        .arg(dest)
+        .arg("--strip-components")
+        .arg("1")
+        .status()
+        .map_err(|e| format!("tar exec failed: {}", e))?;
+    if !st.success() {
+        return Err(format!("tar extract failed: exit {}", st));
+    }
+    Ok(())
+}
+
+/// Whether the in-process fetch path is used (unless `RIGRA_FETCH=system`).
+#[cfg(feature = "native-fetch")]
+fn native_fetch() -> bool {
+    !matches!(std::env::var("RIGRA_FETCH"), Ok(v) if v.trim().eq_ignore_ascii_case("system"))
+}
+
+#[cfg(feature = "native-fetch")]
+mod native {
+    use std::fs;
+    use std::path::{Component, Path, PathBuf};
+
+    /// Download `url` to `out` with a blocking HTTP client.
+    pub fn download(url: &str, out: &Path) -> Result<(), String> {
+        let mut resp = reqwest::blocking::get(url)
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("download failed: {}", e))?;
+        let mut file = fs::File::create(out).map_err(|e| format!("write tmp: {}", e))?;
+        resp.copy_to(&mut file)
+            .map_err(|e| format!("download failed: {}", e))?;
+        Ok(())
+    }
+
+    /// Same as `tar -xzf archive -C dest --strip-components 1`, refusing
+    /// entries that would land outside `dest`.
+    pub fn extract(archive: &Path, dest: &Path) -> Result<(), String> {
+        let file = fs::File::open(archive).map_err(|e| format!("open archive: {}", e))?;
+        let mut ar = tar::Archive::new(flate2::read::GzDecoder::new(file));
+        let entries = ar
+            .entries()
+            .map_err(|e| format!("tar extract failed: {}", e))?;
+        for entry in entries {
+            let mut entry = entry.map_err(|e| format!("tar extract failed: {}", e))?;
+            let path = entry
+                .path()
+                .map_err(|e| format!("tar extract failed: {}", e))?
+                .into_owned();
+            let mut rel = PathBuf::new();
+            for comp in path.components().skip(1) {
+                match comp {
+                    Component::Normal(c) => rel.push(c),
+                    Component::CurDir => {}
+                    _ => return Err(format!("unsafe archive entry: {}", path.display())),
+                }
+            }
+            if rel.as_os_str().is_empty() {
+                continue;
             }
-            let mut tar = std::process::Command::new("tar");
-            let st = tar
-                .arg("-xzf")
-                .arg(&tmp)
-                .arg("-C")
-                .arg(&dest_root)
-                .arg("--strip-components")
-                .arg("1")
-                .status()
-                .map_err(|e| format!("tar exec failed: {}", e))?;
-            if !st.success() {
-                return Err(format!("tar extract failed: exit {}", st));
+            // Links could point (or let later entries write) outside `dest`
+            let kind = entry.header().entry_type();
+            if kind.is_symlink() || kind.is_hard_link() {
+                return Err(format!("unsafe archive entry (link): {}", path.display()));
             }
-            Ok(dest_root)
+            let out = dest.join(&rel);
+            if let Some(parent) = out.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("create dir: {}", e))?;
+            }
+            entry
+                .unpack(&out)
+                .map_err(|e| format!("tar extract failed: {}", e))?;
         }
-        Source::File { path } => {
-            let mut tar = std::process::Command::new("tar");
-            let st = tar
-                .arg("-xzf")
-                .arg(&path)
-                .arg("-C")
-                .arg(&dest_root)
-                .arg("--strip-components")
-                .arg("1")
-                .status()
-                .map_err(|e| format!("tar exec failed: {}", e))?;
-            if !st.success() {
-                return Err(format!("tar extract failed: exit {}", st));
+        Ok(())
+    }
+}
+
+/// Problems that would make a convention directory unusable once installed:
+/// a missing/invalid `index.toml`, or referenced policy and sync files that
+/// are missing or fail to parse.
+pub fn validate_dir(dir: &Path) -> Vec<String> {
+    let idx_path = dir.join("index.toml");
+    let ix: Index = match fs::read_to_string(&idx_path) {
+        Ok(s) => match toml::from_str(&s) {
+            Ok(ix) => ix,
+            Err(e) => return vec![format!("index.toml is not a valid index: {}", e)],
+        },
+        Err(_) => return vec![format!("missing {}", idx_path.to_string_lossy())],
+    };
+    let mut problems = Vec::new();
+    for r in ix.rules.iter() {
+        if ix.policies.contains_key(&r.policy) {
+            continue;
+        }
+        match fs::read_to_string(dir.join(&r.policy)) {
+            Ok(s) => {
+                if let Err(e) = toml::from_str::<Policy>(&s) {
+                    problems.push(format!("policy '{}' (rule '{}'): {}", r.policy, r.id, e));
+                }
             }
-            Ok(dest_root)
+            Err(_) => problems.push(format!("policy '{}' (rule '{}') not found", r.policy, r.id)),
         }
     }
+    if let Some(sr) = ix.sync_ref.as_ref() {
+        match fs::read_to_string(dir.join(sr)) {
+            Ok(s) => {
+                if let Err(e) = toml::from_str::<SyncPolicy>(&s) {
+                    problems.push(format!("sync policy '{}': {}", sr, e));
+                }
+            }
+            Err(_) => problems.push(format!("sync policy '{}' not found", sr)),
+        }
+    }
+    problems
+}
+
+/// Pack a convention directory into a gzip tarball at `out` and return its
+/// sha256 (hex). The directory is validated first, and entries sit under a
+/// single top-level folder so `install` (`--strip-components 1`) restores
+/// the layout. Uses system `tar` like `install`.
+pub fn pack(dir: &Path, out: &Path) -> Result<String, String> {
+    if !dir.is_dir() {
+        return Err(format!("not a directory: {}", dir.to_string_lossy()));
+    }
+    let problems = validate_dir(dir);
+    if !problems.is_empty() {
+        return Err(format!("invalid convention: {}", problems.join("; ")));
+    }
+    let dir = fs::canonicalize(dir).map_err(|e| format!("resolve dir: {}", e))?;
+    let (parent, name) = match (dir.parent(), dir.file_name()) {
+        (Some(p), Some(n)) => (p, n),
+        _ => return Err("cannot pack the filesystem root".to_string()),
+    };
+    if let Some(p) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(p).map_err(|e| format!("create output dir: {}", e))?;
+    }
+    let st = std::process::Command::new("tar")
+        .arg("-czf")
+        .arg(out)
+        .arg("--exclude=.rigra")
+        .arg("-C")
+        .arg(parent)
+        .arg(name)
+        .status()
+        .map_err(|e| format!("tar exec failed: {}", e))?;
+    if !st.success() {
+        return Err(format!("tar create failed: exit {}", st));
+    }
+    sha256_file(out)
+}
+
+/// Lowercase hex sha256 of a file.
+fn sha256_file(path: &Path) -> Result<String, String> {
+    use sha2::{Digest, Sha256};
+    let mut file =
+        fs::File::open(path).map_err(|e| format!("read {}: {}", path.to_string_lossy(), e))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| format!("sha256 failed: {}", e))?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Shallow-clone `url` at `reference` into the (empty) `dest`, drop the
+/// `.git` directory, and check that the checkout holds an `index.toml`.
+fn clone_into(url: &str, reference: &str, dest: &Path) -> Result<(), String> {
+    let st = std::process::Command::new("git")
+        .args([
+            "clone", "--quiet", "--depth", "1", "--branch", reference, "--", url,
+        ])
+        .arg(dest)
+        .status()
+        .map_err(|e| format!("git exec failed: {}", e))?;
+    if !st.success() {
+        return Err(format!("git clone failed: exit {}", st));
+    }
+    fs::remove_dir_all(dest.join(".git")).map_err(|e| format!("remove .git: {}", e))?;
+    if !dest.join("index.toml").is_file() {
+        return Err(format!(
+            "{}#{} has no index.toml at its root",
+            url, reference
+        ));
+    }
+    Ok(())
 }
 
 pub fn list(repo_root: &Path) -> Vec<String> {
@@ -161,6 +751,155 @@ pub fn list(repo_root: &Path) -> Vec<String> {
     out
 }
 
+/// What `rigra conv info` reports about one cached convention.
+#[derive(Debug)]
+pub struct ConvInfo {
+    pub name: String,
+    pub version: String,
+    pub path: PathBuf,
+    /// Resolved source and archive digest, when pinned in `conv.lock`
+    pub source: Option<String>,
+    pub sha256: Option<String>,
+    /// Install date (`YYYY-MM-DD`, UTC) taken from the cache entry's mtime
+    pub installed: Option<String>,
+    /// Total size of the files under the cache entry, in bytes
+    pub size: u64,
+    /// `(id, patterns)` for each rule declared by the index
+    pub rules: Vec<(String, Vec<String>)>,
+}
+
+/// Gather [`ConvInfo`] for `cr`, reading its index (`cr.subpath`) from cache.
+pub fn info(repo_root: &Path, cr: &ConvRef) -> Result<ConvInfo, String> {
+    let dir = cache_root(repo_root).join(cache_key(&cr.name, &cr.ver));
+    let md = fs::metadata(&dir).map_err(|_| format!("{}@{} is not installed", cr.name, cr.ver))?;
+    let idx_path = resolve_path(repo_root, cr);
+    let idx_str = fs::read_to_string(&idx_path)
+        .map_err(|e| format!("read {}: {}", idx_path.to_string_lossy(), e))?;
+    let index: Index = toml::from_str(&idx_str)
+        .map_err(|e| format!("invalid {}: {}", idx_path.to_string_lossy(), e))?;
+    let pinned = read_lock(repo_root).ok().flatten().and_then(|l| {
+        l.entries
+            .into_iter()
+            .find(|e| e.name == cr.name && e.version == cr.ver)
+    });
+    Ok(ConvInfo {
+        name: cr.name.clone(),
+        version: cr.ver.clone(),
+        source: pinned.as_ref().map(|e| e.source.clone()),
+        sha256: pinned.and_then(|e| e.sha256),
+        installed: md.modified().ok().map(crate::utils::date_utc),
+        size: dir_size(&dir),
+        rules: index
+            .rules
+            .into_iter()
+            .map(|r| (r.id, r.patterns))
+            .collect(),
+        path: dir,
+    })
+}
+
+/// Sum of file sizes under `dir` (symlinks are not followed).
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(rd) = fs::read_dir(dir) else {
+        return 0;
+    };
+    rd.flatten()
+        .filter_map(|e| {
+            let ft = e.file_type().ok()?;
+            if ft.is_dir() {
+                Some(dir_size(&e.path()))
+            } else {
+                e.metadata().ok().map(|m| m.len())
+            }
+        })
+        .sum()
+}
+
+/// One rendered line of a convention file tree.
+pub struct TreeLine {
+    /// Tree-drawing prefix plus the entry name (directories end with `/`)
+    pub text: String,
+    /// `index`, `policy`, or `sync` when the file is the convention index or
+    /// referenced by it
+    pub role: Option<&'static str>,
+}
+
+/// Render the cache directory of one convention as a tree, descending at
+/// most `max_depth` levels. Entries are sorted, directories first.
+pub fn tree(conv_dir: &Path, max_depth: usize) -> Vec<TreeLine> {
+    let mut roles: HashMap<PathBuf, &'static str> = HashMap::new();
+    let idx_path = conv_dir.join("index.toml");
+    roles.insert(idx_path.clone(), "index");
+    if let Some(ix) = fs::read_to_string(&idx_path)
+        .ok()
+        .and_then(|s| toml::from_str::<Index>(&s).ok())
+    {
+        for r in ix.rules.iter() {
+            roles.insert(normalize_path(&conv_dir.join(&r.policy)), "policy");
+        }
+        if let Some(sr) = ix.sync_ref.as_ref() {
+            roles.insert(normalize_path(&conv_dir.join(sr)), "sync");
+        }
+    }
+    let mut out = Vec::new();
+    walk_tree(conv_dir, "", 1, max_depth, &roles, &mut out);
+    out
+}
+
+fn walk_tree(
+    dir: &Path,
+    prefix: &str,
+    depth: usize,
+    max_depth: usize,
+    roles: &HashMap<PathBuf, &'static str>,
+    out: &mut Vec<TreeLine>,
+) {
+    if depth > max_depth {
+        return;
+    }
+    let mut entries: Vec<(bool, String, PathBuf)> = match fs::read_dir(dir) {
+        Ok(rd) => rd
+            .flatten()
+            .map(|e| {
+                let p = e.path();
+                (!p.is_dir(), e.file_name().to_string_lossy().to_string(), p)
+            })
+            .collect(),
+        Err(_) => return,
+    };
+    entries.sort();
+    let n = entries.len();
+    for (i, (is_file, name, p)) in entries.into_iter().enumerate() {
+        let last = i + 1 == n;
+        let branch = if last { "└── " } else { "├── " };
+        let label = if is_file { name } else { format!("{}/", name) };
+        out.push(TreeLine {
+            text: format!("{}{}{}", prefix, branch, label),
+            role: roles.get(&normalize_path(&p)).copied(),
+        });
+        if !is_file {
+            let child = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            walk_tree(&p, &child, depth + 1, max_depth, roles, out);
+        }
+    }
+}
+
+/// Lexically resolve `.`/`..` components so index references compare equal
+/// to walked paths.
+fn normalize_path(p: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for c in p.components() {
+        match c {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
 pub fn prune(repo_root: &Path) -> Result<(), String> {
     let root = cache_root(repo_root);
     if root.exists() {
@@ -208,6 +947,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_source_http() {
+        let url = "https://artifacts.example.com/conv/ts-base-1.4.0.tar.gz";
+        match parse_source(url).unwrap() {
+            Source::Http { url: u } => assert_eq!(u, url),
+            _ => panic!("expected http source"),
+        }
+        match parse_source("http://10.0.0.5:8080/conv.tar.gz").unwrap() {
+            Source::Http { url } => assert_eq!(url, "http://10.0.0.5:8080/conv.tar.gz"),
+            _ => panic!("expected http source"),
+        }
+    }
+
+    #[test]
+    fn test_parse_source_git() {
+        match parse_source("git:https://gitlab.example.com/team/conv.git#v1.2.0").unwrap() {
+            Source::Git { url, reference } => {
+                assert_eq!(url, "https://gitlab.example.com/team/conv.git");
+                assert_eq!(reference, "v1.2.0");
+            }
+            _ => panic!("expected git source"),
+        }
+        match parse_source("git:git@gitlab.example.com:team/conv.git#main").unwrap() {
+            Source::Git { url, reference } => {
+                assert_eq!(url, "git@gitlab.example.com:team/conv.git");
+                assert_eq!(reference, "main");
+            }
+            _ => panic!("expected git source"),
+        }
+        // The ref is required
+        assert!(parse_source("git:https://gitlab.example.com/team/conv.git").is_none());
+        assert!(parse_source("git:https://gitlab.example.com/team/conv.git#").is_none());
+        assert!(parse_source("git:--upload-pack=touch /tmp/x#main").is_none());
+    }
+
     #[test]
     fn test_resolve_path_list_and_prune() {
         let dir = tempdir().unwrap();
@@ -254,12 +1028,275 @@ mod tests {
             root,
             "myconv@v0.1.0",
             &format!("file:{}", tgz.to_string_lossy()),
+            None,
         )
         .unwrap();
         assert!(dest.join("index.toml").exists());
         assert!(dest.join("nested/file.txt").exists());
     }
 
+    #[test]
+    fn test_install_verifies_sha256() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let staged = root.join("build/conv");
+        fs::create_dir_all(&staged).unwrap();
+        fs::write(staged.join("index.toml"), "# idx").unwrap();
+        let tgz = root.join("conv.tar.gz");
+        let digest = pack(&staged, &tgz).unwrap();
+        let source = format!("file:{}", tgz.to_string_lossy());
+
+        let bad = "0".repeat(64);
+        let err = install(root, "conv@v1.0.0", &source, Some(&bad)).unwrap_err();
+        assert!(err.contains("sha256 mismatch"), "{}", err);
+        // No partial cache entry is left behind
+        assert!(list(root).is_empty());
+
+        let dest = install(root, "conv@v1.0.0", &source, Some(&digest.to_uppercase())).unwrap();
+        assert!(dest.join("index.toml").is_file());
+    }
+
+    #[test]
+    fn test_lockfile_round_trip() {
+        let mut lock = Lockfile::default();
+        lock.upsert(LockEntry {
+            name: "zeta".into(),
+            version: "v2.0.0".into(),
+            source: "git:https://git.example.com/zeta.git#v2.0.0".into(),
+            sha256: None,
+        });
+        lock.upsert(LockEntry {
+            name: "@acme/conv".into(),
+            version: "v1.0.0".into(),
+            source: "https://github.com/acme/conv/archive/refs/tags/v1.0.0.tar.gz".into(),
+            sha256: Some("ab".repeat(32)),
+        });
+        // Re-pinning the same name@version replaces the entry
+        lock.upsert(LockEntry {
+            name: "@acme/conv".into(),
+            version: "v1.0.0".into(),
+            source: "file:/tmp/conv.tar.gz".into(),
+            sha256: Some("cd".repeat(32)),
+        });
+        assert_eq!(lock.entries.len(), 2);
+        assert_eq!(lock.entries[0].name, "@acme/conv");
+
+        let text = render_lock(&lock).unwrap();
+        assert!(text.starts_with("# Generated by"));
+        assert!(text.contains("[[conv]]"));
+        assert_eq!(parse_lock(&text).unwrap(), lock);
+
+        let dir = tempdir().unwrap();
+        assert!(read_lock(dir.path()).unwrap().is_none());
+        write_lock(dir.path(), &lock).unwrap();
+        assert_eq!(read_lock(dir.path()).unwrap(), Some(lock));
+    }
+
+    #[test]
+    fn test_install_locked_rejects_changed_archive() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let staged = root.join("build/conv");
+        fs::create_dir_all(&staged).unwrap();
+        fs::write(staged.join("index.toml"), "# idx").unwrap();
+        let tgz = root.join("conv.tar.gz");
+        pack(&staged, &tgz).unwrap();
+        let source = format!("file:{}", tgz.to_string_lossy());
+
+        let (_, entry) = install_entry(root, "conv@v1.0.0", &source, None).unwrap();
+        let entry = entry.expect("fresh install yields a lock entry");
+        assert_eq!(entry.source, source);
+        let mut lock = Lockfile::default();
+        lock.upsert(entry);
+        prune(root).unwrap();
+
+        // Same archive reinstalls from the lock
+        assert_eq!(install_locked(root, &lock).unwrap().len(), 1);
+        prune(root).unwrap();
+
+        // A republished archive no longer matches the pinned digest
+        fs::write(staged.join("index.toml"), "# changed").unwrap();
+        pack(&staged, &tgz).unwrap();
+        let err = install_locked(root, &lock).unwrap_err();
+        assert!(err.contains("sha256 mismatch"), "{}", err);
+    }
+
+    #[test]
+    fn test_update_refetches_only_the_given_version() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let staged = root.join("build/conv");
+        fs::create_dir_all(&staged).unwrap();
+        fs::write(staged.join("index.toml"), "# v1").unwrap();
+        let tgz = root.join("conv.tar.gz");
+        pack(&staged, &tgz).unwrap();
+        let source = format!("file:{}", tgz.to_string_lossy());
+        let old = install(root, "conv@v1.0.0", &source, None).unwrap();
+        let cur = install(root, "conv@v1.1.0", &source, None).unwrap();
+
+        // Republish under the same tag; a plain install keeps the cached copy
+        fs::write(staged.join("index.toml"), "# v1.1 fixed").unwrap();
+        pack(&staged, &tgz).unwrap();
+        install(root, "conv@v1.1.0", &source, None).unwrap();
+        assert_eq!(fs::read_to_string(cur.join("index.toml")).unwrap(), "# v1");
+
+        let (path, entry) = update(root, "conv@v1.1.0", &source).unwrap();
+        assert_eq!(path, cur);
+        assert_eq!(
+            fs::read_to_string(cur.join("index.toml")).unwrap(),
+            "# v1.1 fixed"
+        );
+        assert_eq!(entry.unwrap().version, "v1.1.0");
+        // Older versions stay installed
+        assert_eq!(fs::read_to_string(old.join("index.toml")).unwrap(), "# v1");
+
+        // A failed refresh keeps the previous copy
+        let err = update(root, "conv@v1.1.0", "file:/nonexistent/conv.tar.gz");
+        assert!(err.is_err());
+        assert_eq!(
+            fs::read_to_string(cur.join("index.toml")).unwrap(),
+            "# v1.1 fixed"
+        );
+    }
+
+    #[test]
+    fn test_info_reports_rules_source_and_size() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let staged = root.join("build/conv");
+        fs::create_dir_all(staged.join("policies")).unwrap();
+        fs::write(
+            staged.join("index.toml"),
+            "[[rules]]\nid = \"pkg\"\npatterns = [\"package.json\", \"*/package.json\"]\npolicy = \"policies/pkg.toml\"\n\n[[rules]]\nid = \"ts\"\npatterns = [\"tsconfig.json\"]\npolicy = \"policies/pkg.toml\"\n",
+        )
+        .unwrap();
+        fs::write(staged.join("policies/pkg.toml"), "[order]\n").unwrap();
+        let tgz = root.join("conv.tar.gz");
+        let digest = pack(&staged, &tgz).unwrap();
+        let source = format!("file:{}", tgz.to_string_lossy());
+        let (_, entry) = install_entry(root, "conv@v1.0.0", &source, None).unwrap();
+        record_lock(root, entry.unwrap()).unwrap();
+
+        let cr = parse_conv_ref("conv:conv@v1.0.0").unwrap();
+        let info = info(root, &cr).unwrap();
+        let ids: Vec<&str> = info.rules.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["pkg", "ts"]);
+        assert_eq!(info.rules[0].1, ["package.json", "*/package.json"]);
+        assert_eq!(info.source.as_deref(), Some(source.as_str()));
+        assert_eq!(info.sha256.as_deref(), Some(digest.as_str()));
+        assert_eq!(
+            info.installed.as_deref(),
+            Some(crate::utils::today_utc().as_str())
+        );
+        let expected = fs::metadata(staged.join("index.toml")).unwrap().len()
+            + fs::metadata(staged.join("policies/pkg.toml"))
+                .unwrap()
+                .len();
+        assert_eq!(info.size, expected);
+
+        let missing = parse_conv_ref("conv:conv@v9.9.9").unwrap();
+        let err = super::info(root, &missing).unwrap_err();
+        assert!(err.contains("not installed"), "{}", err);
+    }
+
+    #[test]
+    fn test_resolve_version_ranges_and_exact_pins() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        for key in [
+            "@acme__conv@v1.2.0",
+            "@acme__conv@1.2.9",
+            "@acme__conv@v1.3.5",
+            "@acme__conv@v1.10.0",
+            "@acme__conv@v2.0.0-beta.1",
+            "@acme__conv@main",
+            "@acme__other@v1.99.0",
+        ] {
+            fs::create_dir_all(cache_root(root).join(key)).unwrap();
+        }
+        let pick = |r: &str| {
+            let cr = parse_conv_ref(&format!("conv:@acme/conv@{}:sub/index.toml", r)).unwrap();
+            resolve_version(root, &cr)
+        };
+
+        // Caret: highest 1.x, ignoring other names and non-semver dirs
+        let cr = pick("^1.2").unwrap();
+        assert_eq!(cr.ver, "v1.10.0");
+        assert_eq!(cr.subpath, "sub/index.toml");
+        assert!(resolve_path(root, &cr).ends_with("@acme__conv@v1.10.0/sub/index.toml"));
+        // Tilde: patch updates only; the v-less directory still counts
+        assert_eq!(pick("~1.2").unwrap().ver, "1.2.9");
+        assert_eq!(pick("~1.3.0").unwrap().ver, "v1.3.5");
+        // Exact pins and non-semver refs are left as written
+        assert_eq!(pick("v1.3.5").unwrap().ver, "v1.3.5");
+        assert_eq!(pick("1.4.0").unwrap().ver, "1.4.0");
+        assert_eq!(pick("main").unwrap().ver, "main");
+        assert_eq!(pick("=1.3.5").unwrap().ver, "v1.3.5");
+        // Without an operator a partial version is a literal tag
+        assert_eq!(pick("1.2").unwrap().ver, "1.2");
+        assert_eq!(pick("2024").unwrap().ver, "2024");
+        assert_eq!(pick(">=1.3, <1.10").unwrap().ver, "v1.3.5");
+
+        let err = pick("^3").unwrap_err();
+        assert!(err.contains("satisfies '^3'"), "{}", err);
+        assert!(err.contains("v1.10.0"), "{}", err);
+    }
+
+    #[test]
+    fn test_install_from_local_zip() {
+        use zip::write::SimpleFileOptions;
+
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let archive = root.join("conv-v1.0.0.zip");
+        let mut zw = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+        let opts = SimpleFileOptions::default();
+        zw.add_directory("conv-v1.0.0/", opts).unwrap();
+        zw.start_file("conv-v1.0.0/index.toml", opts).unwrap();
+        zw.write_all(b"# zipped index").unwrap();
+        zw.start_file("conv-v1.0.0/policies/pkg.toml", opts)
+            .unwrap();
+        zw.write_all(b"[order]\n").unwrap();
+        zw.finish().unwrap();
+
+        let source = format!("file:{}", archive.to_string_lossy());
+        let dest = install(root, "conv@v1.0.0", &source, None).unwrap();
+        assert_eq!(
+            fs::read_to_string(dest.join("index.toml")).unwrap(),
+            "# zipped index"
+        );
+        assert!(dest.join("policies/pkg.toml").is_file());
+        assert!(!dest.join("conv-v1.0.0").exists());
+
+        // Detected by magic bytes too, e.g. a download saved without extension
+        let renamed = root.join("download");
+        fs::copy(&archive, &renamed).unwrap();
+        let source = format!("file:{}", renamed.to_string_lossy());
+        let dest = install(root, "conv@v1.0.1", &source, None).unwrap();
+        assert!(dest.join("index.toml").is_file());
+    }
+
+    #[test]
+    fn test_install_offline_refuses_network_source() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let res: Vec<_> = [
+            "gh:owner/repo@v0.1.0",
+            "https://artifacts.example.com/myconv-v0.1.0.tar.gz",
+            "git:https://git.example.com/myconv.git#v0.1.0",
+        ]
+        .iter()
+        .map(|src| install_entry_with(root, "myconv@v0.1.0", src, None, true))
+        .collect();
+        for r in res {
+            let err = r.unwrap_err();
+            assert!(err.contains("offline mode"), "{}", err);
+            assert!(err.contains("not cached"), "{}", err);
+        }
+        // Nothing should have been created in the cache
+        assert!(list(root).is_empty());
+    }
+
     #[test]
     fn test_parse_conv_ref_scoped_name_and_cache_key() {
         let cr = parse_conv_ref("conv:@nazahex/conv-lib-ts-mono@v0.1.0").unwrap();
@@ -269,4 +1306,154 @@ mod tests {
         let s = p.to_string_lossy();
         assert!(s.contains("@nazahex__conv-lib-ts-mono@v0.1.0"));
     }
+
+    #[test]
+    fn test_tree_marks_index_and_referenced_files() {
+        let dir = tempdir().unwrap();
+        let conv = dir.path();
+        fs::create_dir_all(conv.join("policies/deep/deeper")).unwrap();
+        fs::create_dir_all(conv.join("templates")).unwrap();
+        fs::write(
+            conv.join("index.toml"),
+            "sync = \"sync.toml\"\n[[rules]]\nid = \"a\"\npatterns = [\"a.json\"]\npolicy = \"./policies/a.toml\"\n",
+        )
+        .unwrap();
+        fs::write(conv.join("sync.toml"), "").unwrap();
+        fs::write(conv.join("policies/a.toml"), "").unwrap();
+        fs::write(conv.join("policies/deep/deeper/x.toml"), "").unwrap();
+        fs::write(conv.join("templates/t.txt"), "").unwrap();
+
+        let lines = tree(conv, 2);
+        let rendered: Vec<_> = lines
+            .iter()
+            .map(|l| match l.role {
+                Some(r) => format!("{} [{}]", l.text, r),
+                None => l.text.clone(),
+            })
+            .collect();
+        assert_eq!(
+            rendered,
+            vec![
+                "├── policies/",
+                "│   ├── deep/",
+                "│   └── a.toml [policy]",
+                "├── templates/",
+                "│   └── t.txt",
+                "├── index.toml [index]",
+                "└── sync.toml [sync]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pack_validates_and_round_trips_through_install() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let conv = root.join("myconv");
+        fs::create_dir_all(conv.join("policies")).unwrap();
+        fs::write(
+            conv.join("index.toml"),
+            "[[rules]]\nid = \"pkg\"\npatterns = [\"package.json\"]\npolicy = \"policies/pkg.toml\"\n",
+        )
+        .unwrap();
+
+        // Referenced policy missing: refuse to pack
+        let err = pack(&conv, &root.join("out.tar.gz")).unwrap_err();
+        assert!(err.contains("policies/pkg.toml"));
+
+        fs::write(conv.join("policies/pkg.toml"), "checks = []\n").unwrap();
+        let tgz = root.join("dist/myconv.tar.gz");
+        let sha = pack(&conv, &tgz).unwrap();
+        assert_eq!(sha.len(), 64);
+        assert!(sha.chars().all(|c| c.is_ascii_hexdigit()));
+
+        let dest = install(
+            root,
+            "myconv@v1.0.0",
+            &format!("file:{}", tgz.to_string_lossy()),
+            None,
+        )
+        .unwrap();
+        assert!(dest.join("index.toml").exists());
+        assert!(dest.join("policies/pkg.toml").exists());
+    }
+
+    #[cfg(feature = "native-fetch")]
+    #[test]
+    fn test_native_fetch_installs_served_tarball() {
+        use std::io::Read;
+        use std::net::TcpListener;
+
+        // Build conv-1.0.0/{index.toml,policies/a.toml} as a .tar.gz in memory
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        {
+            let mut b = tar::Builder::new(&mut gz);
+            for (name, body) in [
+                ("conv-1.0.0/index.toml", "[[rules]]\n"),
+                ("conv-1.0.0/policies/a.toml", "[order]\n"),
+            ] {
+                let mut h = tar::Header::new_gnu();
+                h.set_size(body.len() as u64);
+                h.set_mode(0o644);
+                h.set_cksum();
+                b.append_data(&mut h, name, body.as_bytes()).unwrap();
+            }
+            b.finish().unwrap();
+        }
+        let body = gz.finish().unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut sock, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let mut req = Vec::new();
+            while !req.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = sock.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                req.extend_from_slice(&buf[..n]);
+            }
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            sock.write_all(head.as_bytes()).unwrap();
+            sock.write_all(&body).unwrap();
+        });
+
+        let td = tempdir().unwrap();
+        let url = format!("http://{}/conv-1.0.0.tar.gz", addr);
+        let dest = install(td.path(), "conv@1.0.0", &url, None).unwrap();
+        server.join().unwrap();
+        assert!(dest.join("index.toml").is_file());
+        assert_eq!(
+            fs::read_to_string(dest.join("policies/a.toml")).unwrap(),
+            "[order]\n"
+        );
+    }
+
+    #[cfg(feature = "native-fetch")]
+    #[test]
+    fn test_native_extract_rejects_link_entries() {
+        let td = tempdir().unwrap();
+        let archive = td.path().join("conv.tar.gz");
+        let gz = flate2::write::GzEncoder::new(
+            fs::File::create(&archive).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut b = tar::Builder::new(gz);
+        let mut h = tar::Header::new_gnu();
+        h.set_entry_type(tar::EntryType::Symlink);
+        h.set_size(0);
+        h.set_cksum();
+        b.append_link(&mut h, "conv-1.0.0/escape", "/etc").unwrap();
+        b.into_inner().unwrap().finish().unwrap();
+
+        let dest = td.path().join("out");
+        let err = native::extract(&archive, &dest).unwrap_err();
+        assert!(err.contains("unsafe archive entry"), "{}", err);
+        assert!(!dest.join("escape").exists());
+    }
 }
@@ -0,0 +1,151 @@
+//! Diagnostic stream for human-facing banners, notes, and errors.
+//!
+//! All `info`/`note`/`warn`/`error` prefixed messages are written to stderr
+//! through this module so they can be tee'd to a `--log-file` while stdout
+//! stays reserved for results. The log file receives plain text (ANSI color
+//! codes stripped). With `--silent`, nothing reaches the terminal (stdout or
+//! stderr) but the log file is still written.
+//!
+//! `debug` lines trace config discovery, index resolution, and rule matching;
+//! they are off unless `RIGRA_LOG=debug` (or `trace`) is set.
+
+use std::fmt::Display;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+static SILENT: AtomicBool = AtomicBool::new(false);
+
+/// Suppress all terminal output for the run (`--silent`); exit codes remain.
+pub fn set_silent(on: bool) {
+    SILENT.store(on, Ordering::Relaxed);
+}
+
+pub fn is_silent() -> bool {
+    SILENT.load(Ordering::Relaxed)
+}
+
+/// Tee subsequent diagnostics to `path` (created or truncated).
+pub fn set_log_file(path: &Path) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    let file = File::create(path)?;
+    if let Ok(mut guard) = LOG_FILE.lock() {
+        *guard = Some(file);
+    }
+    Ok(())
+}
+
+/// Write a raw diagnostic line to stderr and the log file, if any.
+pub fn emit(line: &str) {
+    if !is_silent() {
+        eprintln!("{}", line);
+    }
+    if let Ok(mut guard) = LOG_FILE.lock() {
+        if let Some(f) = guard.as_mut() {
+            let _ = writeln!(f, "{}", strip_ansi(line));
+        }
+    }
+}
+
+pub fn error(msg: impl Display) {
+    emit(&format!("{} {}", crate::utils::error_prefix(), msg));
+}
+
+pub fn warn(msg: impl Display) {
+    emit(&format!("{} {}", crate::utils::warn_prefix(), msg));
+}
+
+pub fn info(msg: impl Display) {
+    emit(&format!("{} {}", crate::utils::info_prefix(), msg));
+}
+
+pub fn note(msg: impl Display) {
+    emit(&format!("{} {}", crate::utils::note_prefix(), msg));
+}
+
+/// Whether `RIGRA_LOG` asks for debug tracing (read once per run).
+pub fn debug_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var("RIGRA_LOG")
+            .map(|v| log_level_enables_debug(&v))
+            .unwrap_or(false)
+    })
+}
+
+fn log_level_enables_debug(level: &str) -> bool {
+    matches!(
+        level.trim().to_ascii_lowercase().as_str(),
+        "debug" | "trace"
+    )
+}
+
+/// Trace line for `RIGRA_LOG=debug`; the message is only built when enabled.
+pub fn debug<D: Display>(msg: impl FnOnce() -> D) {
+    if debug_enabled() {
+        emit(&format!("{} {}", crate::utils::debug_prefix(), msg()));
+    }
+}
+
+/// Remove ANSI CSI escape sequences (e.g. colors) from `s`.
+fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' && chars.peek() == Some(&'[') {
+            chars.next();
+            for n in chars.by_ref() {
+                if n.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_strip_ansi_removes_color_codes() {
+        assert_eq!(
+            strip_ansi("\u{1b}[1;31m✖ ⟦error⟧\u{1b}[0m boom"),
+            "✖ ⟦error⟧ boom"
+        );
+        assert_eq!(strip_ansi("plain"), "plain");
+    }
+
+    #[test]
+    fn test_log_file_receives_diagnostics() {
+        let tmp = tempdir().unwrap();
+        let path = tmp.path().join("logs/rigra.log");
+        set_log_file(&path).unwrap();
+        error("something failed");
+        note("just a note");
+        *LOG_FILE.lock().unwrap() = None;
+        let logged = std::fs::read_to_string(&path).unwrap();
+        assert!(logged.contains("⟦error⟧ something failed"));
+        assert!(logged.contains("⟦note⟧ just a note"));
+        assert!(!logged.contains('\u{1b}'));
+    }
+
+    #[test]
+    fn test_rigra_log_levels() {
+        assert!(log_level_enables_debug("debug"));
+        assert!(log_level_enables_debug(" TRACE "));
+        assert!(!log_level_enables_debug("info"));
+        assert!(!log_level_enables_debug(""));
+    }
+}
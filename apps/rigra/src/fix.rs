@@ -0,0 +1,399 @@
+//! Autofix engine for `rigra lint --fix`.
+//!
+//! Fixable checks propose `Edit`s (set a JSON path to a value). Edits for a
+//! file are applied deterministically: sorted by path depth (deepest first)
+//! and then by path, so discovery or `HashMap` order never changes the
+//! result. Edits that disagree on the same path, or that overlap (one path is
+//! an ancestor of another), are reported as conflicts and not applied.
+//! Fixed files are rendered through the formatter's pipeline, and files lint
+//! would not check (multi-doc rules, beyond `max_depth`, mid-merge, or with
+//! duplicate keys) are left alone.
+
+use crate::checks::{expand_wildcards, json_eq};
+use crate::format::{is_jsonc_target, render, validate_preserve_matching, Layout};
+use crate::models::index::Index;
+use crate::models::policy::{Check, Policy};
+use crate::models::RunError;
+use crate::utils::{
+    find_duplicate_key, get_json_path, has_conflict_markers, path_segments, set_json_path,
+};
+use serde_json::Value as Json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A single proposed change: set `path` to `value`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edit {
+    pub path: String,
+    pub value: Json,
+}
+
+/// Two or more edits that cannot be applied together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub paths: Vec<String>,
+}
+
+/// Per-file fix outcome.
+pub struct FixResult {
+    pub file: String,
+    pub applied: usize,
+    pub wrote: bool,
+}
+
+/// Propose edits for fixable checks that currently fail on `json`.
+///
+/// Only `const` checks are fixable today: the expected value is written.
+/// Checks with `level = "off"` never produce edits.
+pub fn plan_edits(checks: &[Check], json: &Json) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    // Wildcard fields fix each matched path, never a literal `*` key
+    for chk in &expand_wildcards(checks, json) {
+        if chk.is_off() {
+            continue;
+        }
+        if let Check::Const {
+            field,
+            value,
+            ignore_case,
+            ..
+        } = chk
+        {
+            let fold = ignore_case.unwrap_or(false);
+            if !get_json_path(json, field).is_some_and(|g| json_eq(g, value, fold)) {
+                edits.push(Edit {
+                    path: normalize(field),
+                    value: value.clone(),
+                });
+            }
+        }
+    }
+    edits
+}
+
+/// Apply edits deterministically, returning the number applied and any
+/// conflicts detected. Conflicting edits are skipped entirely.
+pub fn apply_edits(json: &mut Json, edits: Vec<Edit>) -> (usize, Vec<Conflict>) {
+    let mut edits = edits;
+    edits.sort_by(|a, b| {
+        depth(&b.path)
+            .cmp(&depth(&a.path))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    // Identical edits proposed by several checks collapse into one
+    edits.dedup();
+
+    let mut conflicts: Vec<Conflict> = Vec::new();
+    let mut blocked: Vec<bool> = vec![false; edits.len()];
+    for i in 0..edits.len() {
+        for j in (i + 1)..edits.len() {
+            let (a, b) = (&edits[i].path, &edits[j].path);
+            if a == b || is_ancestor(a, b) || is_ancestor(b, a) {
+                blocked[i] = true;
+                blocked[j] = true;
+                conflicts.push(Conflict {
+                    paths: vec![display(a), display(b)],
+                });
+            }
+        }
+    }
+
+    let mut applied = 0usize;
+    for (edit, skip) in edits.into_iter().zip(blocked) {
+        if !skip && set_json_path(json, &edit.path, edit.value) {
+            applied += 1;
+        }
+    }
+    (applied, conflicts)
+}
+
+/// Apply fixes to files matched by the index, writing changed files when
+/// `write` is true. Written files follow `layout` and keep their trailing
+/// newline.
+pub fn run_fix(
+    repo_root: &str,
+    index_path: &str,
+    write: bool,
+    max_depth: Option<usize>,
+    layout: &Layout,
+    patterns_override: &HashMap<String, Vec<String>>,
+) -> (Vec<FixResult>, Vec<RunError>) {
+    let root = PathBuf::from(repo_root);
+    let idx_path = root.join(index_path);
+    let mut errors: Vec<RunError> = Vec::new();
+    let index: Index = match fs::read_to_string(&idx_path)
+        .ok()
+        .and_then(|s| toml::from_str(&s).ok())
+    {
+        Some(ix) => ix,
+        None => {
+            errors.push(RunError {
+                message: format!("Failed to load index: {}", idx_path.to_string_lossy()),
+            });
+            return (Vec::new(), errors);
+        }
+    };
+
+    let mut results = Vec::new();
+    for ri in index.rules {
+        // Multi-doc targets are not single JSON documents to rewrite
+        if ri.multi_doc {
+            continue;
+        }
+        let pol_path = idx_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(&ri.policy);
+        // Inline policies (index bundles) take precedence over files
+        let loaded: Policy;
+        let policy: &Policy = match index.policies.get(&ri.policy) {
+            Some(p) => p,
+            None => match fs::read_to_string(&pol_path)
+                .ok()
+                .and_then(|s| toml::from_str(&s).ok())
+            {
+                Some(p) => {
+                    loaded = p;
+                    &loaded
+                }
+                None => continue,
+            },
+        };
+        // Lint reports the invalid regex; rendering would reorder those keys
+        if validate_preserve_matching(policy).is_err() {
+            continue;
+        }
+        let use_patterns: Vec<String> = patterns_override
+            .get(&ri.id)
+            .cloned()
+            .unwrap_or_else(|| ri.patterns.clone());
+        let mut targets: Vec<PathBuf> = Vec::new();
+        for pat in use_patterns.iter() {
+            let pattern = root.join(pat).to_string_lossy().to_string();
+            if let Ok(itr) = glob::glob(&pattern) {
+                targets.extend(
+                    itr.flatten()
+                        .filter(|p| crate::utils::within_depth(&root, p, max_depth)),
+                );
+            }
+        }
+        targets.sort();
+        for path in targets {
+            let data = match fs::read_to_string(&path) {
+                Ok(s) => s,
+                Err(_) => continue,
+            };
+            // Lint reports conflicted files; rewriting would drop the other
+            // side, as it would all but the last of repeated keys
+            if has_conflict_markers(&data) || find_duplicate_key(&data).is_some() {
+                continue;
+            }
+            let mut json: Json = match serde_json::from_str(&data) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            let edits = plan_edits(&policy.checks, &json);
+            if edits.is_empty() {
+                continue;
+            }
+            let (applied, conflicts) = apply_edits(&mut json, edits);
+            for c in &conflicts {
+                errors.push(RunError {
+                    message: format!(
+                        "Conflicting fixes in '{}': {}",
+                        path.to_string_lossy(),
+                        c.paths.join(" vs ")
+                    ),
+                });
+            }
+            let mut wrote = false;
+            if write && applied > 0 {
+                let jsonc = is_jsonc_target(&path);
+                let rendered =
+                    render(&mut json, Some(policy), &data, jsonc, layout).map(|mut s| {
+                        if data.ends_with('\n') {
+                            s.push('\n');
+                        }
+                        s
+                    });
+                match rendered {
+                    Ok(s) => match fs::write(&path, s) {
+                        Ok(_) => wrote = true,
+                        Err(e) => errors.push(RunError {
+                            message: format!(
+                                "Failed to write fixed file '{}': {}",
+                                path.to_string_lossy(),
+                                e
+                            ),
+                        }),
+                    },
+                    Err(e) => errors.push(RunError {
+                        message: format!(
+                            "Failed to serialize fixed file '{}': {}",
+                            path.to_string_lossy(),
+                            e
+                        ),
+                    }),
+                }
+            }
+            results.push(FixResult {
+                file: path.to_string_lossy().to_string(),
+                applied,
+                wrote,
+            });
+        }
+    }
+    (results, errors)
+}
+
+fn normalize(path: &str) -> String {
+    path.trim()
+        .trim_start_matches('$')
+        .trim_start_matches('.')
+        .to_string()
+}
+
+fn depth(path: &str) -> usize {
+    path_segments(path).len()
+}
+
+fn is_ancestor(parent: &str, child: &str) -> bool {
+    let (p, c) = (path_segments(parent), path_segments(child));
+    p.len() < c.len() && c.starts_with(&p)
+}
+
+fn display(path: &str) -> String {
+    if path.is_empty() || path.starts_with('[') {
+        format!("${}", path)
+    } else {
+        format!("$.{}", path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn edit(path: &str, value: Json) -> Edit {
+        Edit {
+            path: path.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_apply_edits_deterministic_regardless_of_input_order() {
+        let base = json!({"a": {"b": 1}, "c": 2});
+        let edits = vec![
+            edit("c", json!(3)),
+            edit("a.b", json!(5)),
+            edit("d.e", json!("x")),
+        ];
+        let mut reversed = edits.clone();
+        reversed.reverse();
+
+        let mut j1 = base.clone();
+        let mut j2 = base.clone();
+        let (n1, c1) = apply_edits(&mut j1, edits);
+        let (n2, c2) = apply_edits(&mut j2, reversed);
+        assert_eq!(n1, 3);
+        assert_eq!(n2, 3);
+        assert!(c1.is_empty() && c2.is_empty());
+        assert_eq!(
+            serde_json::to_string(&j1).unwrap(),
+            serde_json::to_string(&j2).unwrap()
+        );
+        assert_eq!(j1, json!({"a": {"b": 5}, "c": 3, "d": {"e": "x"}}));
+    }
+
+    #[test]
+    fn test_apply_edits_reports_overlapping_and_same_path_conflicts() {
+        let mut j = json!({"a": {"b": 1}, "x": 1, "y": 1});
+        let edits = vec![
+            edit("a", json!({"z": 1})),
+            edit("a.b", json!(2)),
+            edit("x", json!(2)),
+            edit("x", json!(3)),
+            // identical duplicates are not conflicts
+            edit("y", json!(9)),
+            edit("y", json!(9)),
+        ];
+        let (applied, conflicts) = apply_edits(&mut j, edits);
+        assert_eq!(applied, 1);
+        assert_eq!(
+            conflicts,
+            vec![
+                Conflict {
+                    paths: vec!["$.a.b".into(), "$.a".into()]
+                },
+                Conflict {
+                    paths: vec!["$.x".into(), "$.x".into()]
+                },
+            ]
+        );
+        // Conflicting paths are left untouched
+        assert_eq!(j, json!({"a": {"b": 1}, "x": 1, "y": 9}));
+    }
+
+    #[test]
+    fn test_plan_edits_only_for_failing_const() {
+        let j = json!({"license": "ISC", "private": true});
+        let checks = vec![
+            Check::Const {
+                field: "$.license".into(),
+                value: json!("MIT"),
+                ignore_case: None,
+                message: None,
+                level: None,
+                code: None,
+            },
+            Check::Const {
+                field: "private".into(),
+                value: json!(true),
+                ignore_case: None,
+                message: None,
+                level: None,
+                code: None,
+            },
+        ];
+        assert_eq!(plan_edits(&checks, &j), vec![edit("license", json!("MIT"))]);
+    }
+
+    #[test]
+    fn test_plan_edits_expands_wildcard_const() {
+        let j = json!({"publishConfig": {"a": {"access": "public"}, "b": {"access": "x"}}});
+        let checks = vec![Check::Const {
+            field: "$.publishConfig.*.access".into(),
+            value: json!("public"),
+            ignore_case: None,
+            message: None,
+            level: None,
+            code: None,
+        }];
+        assert_eq!(
+            plan_edits(&checks, &j),
+            vec![edit("publishConfig.b.access", json!("public"))]
+        );
+    }
+
+    #[test]
+    fn test_quoted_keys_apply_and_conflict_by_segment() {
+        let mut j = json!({"exports": {"./utils": {"types": "x"}}});
+        let edits = vec![
+            edit("exports[\"./utils\"].types", json!("./utils.d.ts")),
+            edit("a", json!(1)),
+            edit("a[\"b.c\"]", json!(2)),
+        ];
+        let (applied, conflicts) = apply_edits(&mut j, edits);
+        assert_eq!(applied, 1);
+        assert_eq!(
+            conflicts,
+            vec![Conflict {
+                paths: vec!["$.a[\"b.c\"]".into(), "$.a".into()]
+            }]
+        );
+        assert_eq!(j["exports"]["./utils"]["types"], "./utils.d.ts");
+    }
+}
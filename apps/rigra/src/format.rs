@@ -1,14 +1,18 @@
 //! JSON formatter for policy-driven ordering and line breaks.
 //!
 //! This module applies two deterministic passes to JSON objects:
-//! - Key ordering based on the policy's `order.top`/`order.sub`.
+//! - Key ordering based on the policy's `order.top`/`order.sub`. Unlisted
+//!   keys matching `order.preserve_matching` stay next to the key that
+//!   preceded them in the source instead of being sorted. Keys in
+//!   `order.pin_first`/`order.pin_last` then move to the front/back.
 //! - Line-break adjustments governed by `linebreak` rules when
 //!   `strictLineBreak` is enabled (config default: true).
 //!
 //! Design notes:
 //! - Group line breaks are only inserted at object depth 1 (top-level),
 //!   and never before the first group. Rules in `before_fields` can
-//!   override insertion for the first key of each group.
+//!   override insertion for the first key of each group. A group whose
+//!   present keys all hold empty containers (`{}`/`[]`) gets no blank line.
 //! - In-field line breaks use the original source to faithfully preserve
 //!   existing blank lines for fields marked `keep`. We compute a map of
 //!   child entries that had a preceding blank line and mirror it after
@@ -16,16 +20,29 @@
 //! - `LineBreakRule::Keep` preserves exactly one blank line where it
 //!   originally existed (otherwise none). `LineBreakRule::None` forces
 //!   no blank line.
+//! - With `maxWidth` set, arrays of scalars are rendered on one line when
+//!   the whole line fits; otherwise one item per line as usual.
+//! - `.jsonc`/`.json5` targets (chosen by extension only, so e.g.
+//!   `tsconfig.json` is plain JSON) tolerate trailing commas on input and
+//!   follow the policy's `trailing_comma` (none|all|multiline) on output.
+//!   Comments are not supported: rewriting through `serde_json` would drop
+//!   them, so such files are reported and left untouched.
+//! - `// rigra-ignore` markers cannot be honoured for the same reason: the
+//!   formatter has no syntax tree to skip the next key/value in, so a marked
+//!   file is reported as such and left untouched. TOML is never formatted.
 
 use crate::models::index::Index;
-use crate::models::policy::{LineBreakRule, Policy};
+use crate::models::policy::{LineBreakRule, Policy, TrailingComma};
 use crate::models::RunError;
+use crate::utils::{conflict_markers_error, find_duplicate_key, has_conflict_markers};
 // colorization handled via utils::error_prefix for errors
 use rayon::prelude::*;
+use regex::Regex;
 use serde_json::{Map, Value as Json};
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 pub struct FormatResult {
     pub file: String,
@@ -45,6 +62,11 @@ pub struct FormatResult {
 ///   - `in_fields`: preserve/remove blank lines between entries inside specific
 ///     object fields using the original file as reference when `Keep`.
 ///
+/// - When `max_width` is set, arrays of scalars that fit are kept on one line.
+/// - String escapes are canonical (`é` rather than `\u00e9`, `/` rather than
+///   `\/`) unless `normalize_strings` is false, in which case each string keeps
+///   the spelling it had in the original file.
+///
 /// Returns one `FormatResult` per matched file. When `write` is false and
 /// `capture_old` is true, results include a pretty-printed preview and original.
 pub fn run_format(
@@ -53,6 +75,8 @@ pub fn run_format(
     write: bool,
     capture_old: bool,
     strict_linebreak: bool,
+    max_width: Option<usize>,
+    normalize_strings: bool,
     lb_between_groups_override: Option<bool>,
     lb_before_fields_override: &std::collections::HashMap<String, String>,
     lb_in_fields_override: &std::collections::HashMap<String, String>,
@@ -64,15 +88,11 @@ pub fn run_format(
     let idx_str = match fs::read_to_string(&idx_path) {
         Ok(s) => s,
         Err(e) => {
-            eprintln!(
-                "{} {}",
-                crate::utils::error_prefix(),
-                format!(
-                    "Failed to read index: {} — {}. Pass --index or configure rigra.toml.",
-                    idx_path.to_string_lossy(),
-                    e
-                )
-            );
+            crate::diag::error(format!(
+                "Failed to read index: {} — {}. Pass --index or configure rigra.toml.",
+                idx_path.to_string_lossy(),
+                e
+            ));
             errors.push(RunError {
                 message: format!(
                     "Failed to read index: {} — {}",
@@ -83,18 +103,14 @@ pub fn run_format(
             return (Vec::new(), errors);
         }
     };
-    let index: Index = match toml::from_str(&idx_str) {
+    let mut index: Index = match toml::from_str(&idx_str) {
         Ok(ix) => ix,
         Err(e) => {
-            eprintln!(
-                "{} {}",
-                crate::utils::error_prefix(),
-                format!(
-                    "Failed to parse index TOML: {} — {}",
-                    idx_path.to_string_lossy(),
-                    e
-                )
-            );
+            crate::diag::error(format!(
+                "Failed to parse index TOML: {} — {}",
+                idx_path.to_string_lossy(),
+                e
+            ));
             errors.push(RunError {
                 message: format!(
                     "Failed to parse index TOML: {} — {}",
@@ -106,9 +122,22 @@ pub fn run_format(
         }
     };
 
+    let layout = Layout {
+        strict_linebreak,
+        max_width,
+        normalize_strings,
+        between_groups: lb_between_groups_override,
+        before_fields: lb_before_fields_override,
+        in_fields: lb_in_fields_override,
+    };
     let mut results = Vec::new();
     // Cache policies across rules by path to avoid repeated I/O and parse when shared
     let mut policy_cache: HashMap<PathBuf, Policy> = HashMap::new();
+    // Inline policies stand in for files of the same name beside the index
+    let idx_dir = idx_path.parent().unwrap_or_else(|| Path::new("."));
+    for (name, p) in std::mem::take(&mut index.policies) {
+        policy_cache.insert(idx_dir.join(name), p);
+    }
     for ri in index.rules {
         // Load policy for this rule to discover per-target ordering rules
         let pol_path = idx_path
@@ -129,12 +158,21 @@ pub fn run_format(
                 None => None,
             }
         };
+        // Formatting anyway would reorder the keys the author meant to keep
+        if let Some(Err(msg)) = policy.map(validate_preserve_matching) {
+            crate::diag::error(format!("Rule '{}': {} — skipping formatting", ri.id, msg));
+            errors.push(RunError {
+                message: format!("Rule '{}': {} — skipping formatting", ri.id, msg),
+            });
+            continue;
+        }
 
         // Collect all target files for this rule (use overrides when present)
         let use_patterns: Vec<String> = patterns_override
             .get(&ri.id)
             .cloned()
             .unwrap_or_else(|| ri.patterns.clone());
+        let walk = crate::profile::span("walk", || format!("format {}", ri.id));
         let mut targets: Vec<PathBuf> = Vec::new();
         for pat in use_patterns.iter() {
             let abs_glob = root.join(pat);
@@ -142,14 +180,10 @@ pub fn run_format(
             let itr = match glob::glob(&pattern) {
                 Ok(it) => it,
                 Err(e) => {
-                    eprintln!(
-                        "{} {}",
-                        crate::utils::error_prefix(),
-                        format!(
-                            "Invalid glob pattern for rule '{}': {} — {}",
-                            ri.id, pattern, e
-                        )
-                    );
+                    crate::diag::error(format!(
+                        "Invalid glob pattern for rule '{}': {} — {}",
+                        ri.id, pattern, e
+                    ));
                     errors.push(RunError {
                         message: format!(
                             "Invalid glob pattern for rule '{}': {} — {}",
@@ -165,9 +199,20 @@ pub fn run_format(
                 }
             }
         }
+        drop(walk);
+
+        crate::diag::debug(|| {
+            format!(
+                "rule '{}': patterns [{}] matched {} file(s)",
+                ri.id,
+                use_patterns.join(", "),
+                targets.len()
+            )
+        });
 
         // Process targets in parallel for throughput; gather deterministic order by file path
-        let ord_opt = policy.and_then(|p| p.order.as_ref()).cloned();
+        let ord_opt = policy.and_then(|p| p.order.as_ref());
+        let skipped: Mutex<Vec<RunError>> = Mutex::new(Vec::new());
         let rule_results: Vec<FormatResult> = targets
             .par_iter()
             .map(|path| {
@@ -182,70 +227,99 @@ pub fn run_format(
                         }
                     }
                 };
-                let mut json: Json = match serde_json::from_str(&data) {
+                // Never rewrite a file mid-merge; report it instead of a parse failure
+                if has_conflict_markers(&data) {
+                    if let Ok(mut c) = skipped.lock() {
+                        c.push(conflict_markers_error(path));
+                    }
+                    return FormatResult {
+                        file: path.to_string_lossy().to_string(),
+                        changed: false,
+                        preview: None,
+                        original: None,
+                    };
+                }
+                let jsonc = is_jsonc_target(path);
+                let parse = crate::profile::span("parse", || crate::utils::rel_to_wd(path));
+                let parsed = if jsonc {
+                    serde_json::from_str(&strip_trailing_commas(&data))
+                } else {
+                    serde_json::from_str(&data)
+                };
+                drop(parse);
+                let mut json: Json = match parsed {
                     Ok(v) => v,
                     Err(_) => {
+                        if jsonc && has_comments(&data) {
+                            let message = if has_ignore_marker(&data) {
+                                format!(
+                                    "rigra-ignore markers are not supported in {}; file not formatted",
+                                    path.to_string_lossy()
+                                )
+                            } else {
+                                format!(
+                                    "Comments are not supported in {}; file not formatted",
+                                    path.to_string_lossy()
+                                )
+                            };
+                            if let Ok(mut c) = skipped.lock() {
+                                c.push(RunError { message });
+                            }
+                        }
                         return FormatResult {
                             file: path.to_string_lossy().to_string(),
                             changed: false,
                             preview: None,
                             original: None,
-                        }
+                        };
                     }
                 };
-                if let Some(ord) = ord_opt.as_ref() {
+                // Parsing keeps only the last of repeated keys; rewriting would
+                // silently drop the others, so leave the file for the user
+                let dup = if jsonc {
+                    find_duplicate_key(&strip_trailing_commas(&data))
+                } else {
+                    find_duplicate_key(&data)
+                };
+                if let Some(dup) = dup {
+                    if let Ok(mut c) = skipped.lock() {
+                        c.push(RunError {
+                            message: format!(
+                                "Duplicate key {} in {}; resolve it manually (file not formatted)",
+                                dup,
+                                path.to_string_lossy()
+                            ),
+                        });
+                    }
+                    return FormatResult {
+                        file: path.to_string_lossy().to_string(),
+                        changed: false,
+                        preview: None,
+                        original: None,
+                    };
+                }
+                if ord_opt.is_some() {
                     // Apply ordering (mutates json), then render and compare to original
-                    let _ = apply_order_from(&mut json, &ord.top, &ord.sub);
-                    let mut s = match serde_json::to_string_pretty(&json) {
+                    let s = match render(&mut json, policy, &data, jsonc, &layout) {
                         Ok(v) => v,
                         Err(e) => {
-                            eprintln!(
-                                "{} {}",
-                                crate::utils::error_prefix(),
-                                format!(
-                                    "Failed to serialize JSON for '{}': {} — skipping formatting",
-                                    path.to_string_lossy(),
-                                    e
-                                )
-                            );
+                            crate::diag::error(format!(
+                                "Failed to serialize JSON for '{}': {} — skipping formatting",
+                                path.to_string_lossy(),
+                                e
+                            ));
                             data.clone()
                         }
                     };
-                    if strict_linebreak {
-                        let between = lb_between_groups_override
-                            .or(policy
-                                .and_then(|p| p.linebreak.as_ref())
-                                .and_then(|lb| lb.between_groups))
-                            .unwrap_or(false);
-                        let fields = merge_linebreak_fields(
-                            policy
-                                .and_then(|p| p.linebreak.as_ref())
-                                .map(|lb| &lb.before_fields),
-                            lb_before_fields_override,
-                        );
-                        let in_fields = merge_linebreak_fields(
-                            policy
-                                .and_then(|p| p.linebreak.as_ref())
-                                .map(|lb| &lb.in_fields),
-                            lb_in_fields_override,
-                        );
-                        s = apply_linebreaks(s, &ord.top, between, &fields);
-                        let keep_map = compute_in_field_keep_map(&data, &in_fields);
-                        s = apply_in_field_linebreaks(s, &in_fields, &keep_map);
-                    }
-                    let changed = s.trim_end() != data.trim_end();
+                    let changed = differs_significantly(&s, &data, strict_linebreak);
                     if write {
                         if changed {
                             if let Err(e) = fs::write(path, s.clone()) {
-                                eprintln!(
-                                    "{} {}",
-                                    crate::utils::error_prefix(),
-                                    format!(
-                                        "Failed to write formatted file '{}': {}",
-                                        path.to_string_lossy(),
-                                        e
-                                    )
-                                );
+                                crate::diag::error(format!(
+                                    "Failed to write formatted file '{}': {}",
+                                    path.to_string_lossy(),
+                                    e
+                                ));
                             }
                         }
                         return FormatResult {
@@ -276,10 +350,263 @@ pub fn run_format(
         let mut rule_results = rule_results;
         rule_results.sort_by(|a, b| a.file.cmp(&b.file));
         results.extend(rule_results);
+        let mut skipped = skipped.into_inner().unwrap_or_default();
+        skipped.sort_by(|a, b| a.message.cmp(&b.message));
+        errors.extend(skipped);
     }
     (results, errors)
 }
 
+/// Layout settings from config/CLI that shape how a rewritten target is
+/// rendered.
+pub struct Layout<'a> {
+    pub strict_linebreak: bool,
+    pub max_width: Option<usize>,
+    pub normalize_strings: bool,
+    pub between_groups: Option<bool>,
+    pub before_fields: &'a HashMap<String, String>,
+    pub in_fields: &'a HashMap<String, String>,
+}
+
+/// Render `json` as `format` writes it: the policy's key order and line
+/// breaks, string spellings and blank lines relative to `original`, and
+/// trailing commas for `.jsonc`/`.json5`. Without an `order`, keys keep
+/// their current order and no line-break rules apply.
+pub fn render(
+    json: &mut Json,
+    policy: Option<&Policy>,
+    original: &str,
+    jsonc: bool,
+    layout: &Layout,
+) -> Result<String, serde_json::Error> {
+    let ord = policy.and_then(|p| p.order.as_ref());
+    if let Some(ord) = ord {
+        let _ = apply_order_from(json, &ord.top, &ord.sub, &ord.preserve_matching);
+        apply_pins(json, &ord.pin_first, &ord.pin_last);
+    }
+    let mut s = match layout.max_width {
+        Some(w) => to_pretty_with_width(json, w),
+        None => serde_json::to_string_pretty(json)?,
+    };
+    if let Some(ord) = ord.filter(|_| layout.strict_linebreak) {
+        let lb = policy.and_then(|p| p.linebreak.as_ref());
+        let between = layout
+            .between_groups
+            .or(lb.and_then(|lb| lb.between_groups))
+            .unwrap_or(false);
+        let fields = merge_linebreak_fields(lb.map(|lb| &lb.before_fields), layout.before_fields);
+        let in_fields = merge_linebreak_fields(lb.map(|lb| &lb.in_fields), layout.in_fields);
+        s = apply_linebreaks(s, &ord.top, between, &fields);
+        let keep_map = compute_in_field_keep_map(original, &in_fields);
+        s = apply_in_field_linebreaks(s, &in_fields, &keep_map);
+    }
+    if !layout.normalize_strings {
+        s = restore_string_spellings(&s, original);
+    }
+    if jsonc {
+        if let Some(mode) = policy.and_then(|p| p.trailing_comma) {
+            s = apply_trailing_commas(&s, mode);
+        }
+    }
+    Ok(s)
+}
+
+/// Pattern overrides that restrict a format run to the single file `rel`
+/// (relative to the repo root): rules whose patterns match it target only
+/// that file, all others target nothing. `None` when no rule matches.
+pub fn patterns_for_path(
+    index: &Index,
+    patterns_override: &HashMap<String, Vec<String>>,
+    rel: &str,
+) -> Option<HashMap<String, Vec<String>>> {
+    let opts = glob::MatchOptions {
+        require_literal_separator: true,
+        ..glob::MatchOptions::new()
+    };
+    let rel = rel.trim_start_matches("./");
+    let mut out = HashMap::new();
+    let mut any = false;
+    for ri in &index.rules {
+        let pats = patterns_override.get(&ri.id).unwrap_or(&ri.patterns);
+        let hit = pats.iter().any(|p| {
+            glob::Pattern::new(p.trim_start_matches("./")).is_ok_and(|g| g.matches_with(rel, opts))
+        });
+        any |= hit;
+        let only = if hit {
+            vec![rel.to_string()]
+        } else {
+            Vec::new()
+        };
+        out.insert(ri.id.clone(), only);
+    }
+    any.then_some(out)
+}
+
+/// Whether `path` is a `.jsonc`/`.json5` target governed by `trailing_comma`
+/// (by extension only; content is not sniffed).
+pub fn is_jsonc_target(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("jsonc") | Some("json5")
+    )
+}
+
+/// Byte ranges of the string literals (quotes included) in JSON text.
+fn string_literal_spans(s: &str) -> Vec<std::ops::Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match start {
+            Some(from) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == '"' {
+                    spans.push(from..i + 1);
+                    start = None;
+                }
+            }
+            None if c == '"' => start = Some(i),
+            None => {}
+        }
+    }
+    spans
+}
+
+/// Put back the original escaping of strings in `rendered`: any literal the
+/// serializer spelled differently (e.g. `"é"` for `"\u00e9"`) is replaced by
+/// its first spelling in `original`.
+fn restore_string_spellings(rendered: &str, original: &str) -> String {
+    let mut spelling: HashMap<String, &str> = HashMap::new();
+    for span in string_literal_spans(original) {
+        let raw = &original[span];
+        let Ok(value) = serde_json::from_str::<String>(raw) else {
+            continue;
+        };
+        if let Ok(canonical) = serde_json::to_string(&value) {
+            if canonical != raw {
+                spelling.entry(canonical).or_insert(raw);
+            }
+        }
+    }
+    if spelling.is_empty() {
+        return rendered.to_string();
+    }
+    let mut out = String::with_capacity(rendered.len());
+    let mut last = 0;
+    for span in string_literal_spans(rendered) {
+        if let Some(raw) = spelling.get(&rendered[span.clone()]) {
+            out.push_str(&rendered[last..span.start]);
+            out.push_str(raw);
+            last = span.end;
+        }
+    }
+    out.push_str(&rendered[last..]);
+    out
+}
+
+/// Whether `s` has a `//` or `/*` comment outside string literals.
+fn has_comments(s: &str) -> bool {
+    let spans = string_literal_spans(s);
+    s.match_indices("//")
+        .chain(s.match_indices("/*"))
+        .any(|(i, _)| !spans.iter().any(|r| r.contains(&i)))
+}
+
+/// Whether a line comment is a `// rigra-ignore` formatter marker.
+fn has_ignore_marker(s: &str) -> bool {
+    s.lines().any(|l| {
+        l.trim_start()
+            .strip_prefix("//")
+            .is_some_and(|c| c.trim() == "rigra-ignore")
+    })
+}
+
+/// Remove commas that directly precede `}`/`]` (outside strings).
+fn strip_trailing_commas(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = String::with_capacity(s.len());
+    let mut in_str = false;
+    let mut escaped = false;
+    for (i, &c) in chars.iter().enumerate() {
+        if in_str {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_str = false;
+            }
+        } else if c == '"' {
+            in_str = true;
+        } else if c == ',' {
+            let next = chars[i + 1..].iter().find(|n| !n.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Apply a trailing-comma style to rendered JSON text.
+fn apply_trailing_commas(s: &str, mode: TrailingComma) -> String {
+    let base = strip_trailing_commas(s);
+    if mode == TrailingComma::None {
+        return base;
+    }
+    let mut out = String::with_capacity(base.len() + 16);
+    let mut in_str = false;
+    let mut escaped = false;
+    for c in base.chars() {
+        if in_str {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_str = false;
+            }
+        } else if c == '"' {
+            in_str = true;
+        } else if c == '}' || c == ']' {
+            let body = out.trim_end();
+            let gap = &out[body.len()..];
+            let last = body.chars().last();
+            let non_empty = !matches!(last, Some('{') | Some('[') | None);
+            let wanted = match mode {
+                TrailingComma::All => true,
+                TrailingComma::Multiline => gap.contains('\n'),
+                TrailingComma::None => false,
+            };
+            if non_empty && wanted {
+                out.insert(body.len(), ',');
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Whether `formatted` differs from `original` in ways the active settings
+/// manage. Without `strict_linebreak`, blank lines are left to the author, so
+/// differences consisting only of blank lines do not count as changes.
+fn differs_significantly(formatted: &str, original: &str, strict_linebreak: bool) -> bool {
+    if strict_linebreak {
+        return formatted.trim_end() != original.trim_end();
+    }
+    let significant = |s: &'_ str| {
+        s.lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.trim_end().to_string())
+            .collect::<Vec<_>>()
+    };
+    significant(formatted) != significant(original)
+}
+
 /// Reorder an object according to top-level groups and sub-field orders.
 ///
 /// Returns true if the order changed. Remaining keys not listed in `top` or
@@ -288,9 +615,11 @@ fn apply_order_from(
     json: &mut Json,
     top: &Vec<Vec<String>>,
     sub: &std::collections::HashMap<String, Vec<String>>,
+    preserve_matching: &[String],
 ) -> bool {
     let mut changed = false;
     if let Json::Object(obj) = json {
+        let original: Vec<String> = obj.keys().cloned().collect();
         let mut new_obj = Map::new();
         for keys in top.iter() {
             for key in keys {
@@ -315,11 +644,183 @@ fn apply_order_from(
                 new_obj.insert(key.clone(), v);
             }
         }
+        if !preserve_matching.is_empty() {
+            let listed = |k: &str| top.iter().chain(sub.values()).flatten().any(|t| t == k);
+            let ordered: Vec<String> = new_obj.keys().cloned().collect();
+            let keys = keep_matching_in_place(&original, ordered, preserve_matching, &listed);
+            let mut reordered = Map::new();
+            for key in keys {
+                if let Some(v) = new_obj.remove(&key) {
+                    reordered.insert(key, v);
+                }
+            }
+            new_obj = reordered;
+        }
         *obj = new_obj;
     }
     changed
 }
 
+/// Move `pin_first` keys to the front and `pin_last` keys to the back of a
+/// top-level object, in the order listed; other keys keep their order.
+fn apply_pins(json: &mut Json, pin_first: &[String], pin_last: &[String]) {
+    if pin_first.is_empty() && pin_last.is_empty() {
+        return;
+    }
+    if let Json::Object(obj) = json {
+        let keys = pin_keys(obj.keys().cloned().collect(), pin_first, pin_last);
+        let mut pinned = Map::new();
+        for key in keys {
+            if let Some(v) = obj.remove(&key) {
+                pinned.insert(key, v);
+            }
+        }
+        *obj = pinned;
+    }
+}
+
+/// Reorder `ordered` so present `pin_first` keys lead and present `pin_last`
+/// keys trail, each in listed order. Shared with the lint order check.
+pub fn pin_keys(ordered: Vec<String>, pin_first: &[String], pin_last: &[String]) -> Vec<String> {
+    let present = |pins: &[String]| -> Vec<String> {
+        let mut out: Vec<String> = Vec::new();
+        for p in pins {
+            if ordered.contains(p) && !out.contains(p) {
+                out.push(p.clone());
+            }
+        }
+        out
+    };
+    let first = present(pin_first);
+    let last: Vec<String> = present(pin_last)
+        .into_iter()
+        .filter(|k| !first.contains(k))
+        .collect();
+    let middle = ordered
+        .iter()
+        .filter(|k| !first.contains(k) && !last.contains(k))
+        .cloned();
+    let mut out = first.clone();
+    out.extend(middle);
+    out.extend(last);
+    out
+}
+
+/// `order.preserve_matching` entries must compile; an invalid one would
+/// otherwise be dropped and its keys reordered after all.
+pub fn validate_preserve_matching(policy: &Policy) -> Result<(), String> {
+    let patterns = policy.order.iter().flat_map(|o| &o.preserve_matching);
+    for p in patterns {
+        if let Err(e) = Regex::new(p) {
+            return Err(format!(
+                "Invalid order.preserve_matching regex '{}': {}",
+                p, e
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Re-thread keys matching any of `patterns` (and not `listed`) so they keep
+/// their original relative positions: each follows the unmatched key that
+/// preceded it in `original`, or leads when nothing did. Shared with the
+/// lint order check so `format` output always passes lint.
+pub fn keep_matching_in_place(
+    original: &[String],
+    ordered: Vec<String>,
+    patterns: &[String],
+    listed: &dyn Fn(&str) -> bool,
+) -> Vec<String> {
+    let res: Vec<Regex> = patterns.iter().filter_map(|p| Regex::new(p).ok()).collect();
+    let pinned = |k: &str| !listed(k) && res.iter().any(|re| re.is_match(k));
+    // Pinned keys grouped by their original unpinned predecessor
+    let mut leading: Vec<String> = Vec::new();
+    let mut after: HashMap<&str, Vec<String>> = HashMap::new();
+    let mut anchor: Option<&str> = None;
+    for k in original {
+        if pinned(k) {
+            match anchor {
+                Some(a) => after.entry(a).or_default().push(k.clone()),
+                None => leading.push(k.clone()),
+            }
+        } else {
+            anchor = Some(k);
+        }
+    }
+    let mut out = leading;
+    for k in ordered.iter().filter(|k| !pinned(k)) {
+        out.push(k.clone());
+        if let Some(group) = after.remove(k.as_str()) {
+            out.extend(group);
+        }
+    }
+    out
+}
+
+/// Pretty-print JSON like `serde_json::to_string_pretty` (two-space indent),
+/// except that arrays of scalars are kept on a single line when the resulting
+/// line fits within `max_width` characters.
+fn to_pretty_with_width(json: &Json, max_width: usize) -> String {
+    let mut out = String::new();
+    write_pretty(&mut out, json, 0, 0, max_width);
+    out
+}
+
+/// Render `json` into `out`. `prefix_len` is the width already used on the
+/// current line (indent plus `"key": `) and `indent` the nesting level.
+fn write_pretty(out: &mut String, json: &Json, indent: usize, prefix_len: usize, max_width: usize) {
+    let scalar = |v: &Json| serde_json::to_string(v).unwrap_or_else(|_| "null".to_string());
+    match json {
+        Json::Object(map) if !map.is_empty() => {
+            out.push_str("{\n");
+            let pad = "  ".repeat(indent + 1);
+            for (i, (k, v)) in map.iter().enumerate() {
+                let key = scalar(&Json::String(k.clone()));
+                out.push_str(&pad);
+                out.push_str(&key);
+                out.push_str(": ");
+                let used = pad.len() + key.chars().count() + 2;
+                write_pretty(out, v, indent + 1, used, max_width);
+                if i + 1 < map.len() {
+                    out.push(',');
+                }
+                out.push('\n');
+            }
+            out.push_str(&"  ".repeat(indent));
+            out.push('}');
+        }
+        Json::Array(items) if !items.is_empty() => {
+            let all_scalar = items.iter().all(|v| !v.is_array() && !v.is_object());
+            if all_scalar {
+                let inline = format!(
+                    "[{}]",
+                    items.iter().map(scalar).collect::<Vec<_>>().join(", ")
+                );
+                // Reserve one column for a possible trailing comma
+                if prefix_len + inline.chars().count() < max_width {
+                    out.push_str(&inline);
+                    return;
+                }
+            }
+            out.push_str("[\n");
+            let pad = "  ".repeat(indent + 1);
+            for (i, v) in items.iter().enumerate() {
+                out.push_str(&pad);
+                write_pretty(out, v, indent + 1, pad.len(), max_width);
+                if i + 1 < items.len() {
+                    out.push(',');
+                }
+                out.push('\n');
+            }
+            out.push_str(&"  ".repeat(indent));
+            out.push(']');
+        }
+        Json::Object(_) => out.push_str("{}"),
+        Json::Array(_) => out.push_str("[]"),
+        other => out.push_str(&scalar(other)),
+    }
+}
+
 /// Merge policy-provided field rules with CLI/config overrides.
 ///
 /// Override values accept `"keep"` or anything else treated as `None`.
@@ -425,12 +926,13 @@ fn apply_linebreaks(
     if !between_groups || groups.is_empty() {
         return pretty;
     }
-    let mut group_first_keys: HashSet<&str> = HashSet::new();
+    let mut group_first_keys: HashMap<&str, &Vec<String>> = HashMap::new();
     for grp in groups.iter() {
         if let Some(first) = grp.first() {
-            group_first_keys.insert(first.as_str());
+            group_first_keys.insert(first.as_str(), grp);
         }
     }
+    let empty_map = top_level_empty_containers(&pretty);
     let mut out: Vec<String> = Vec::new();
     let mut seen_first = false;
     let mut depth: i32 = 0; // track object depth; top-level keys at depth==1
@@ -441,9 +943,19 @@ fn apply_linebreaks(
                 let rest = &trimmed[pos + 1..];
                 if let Some(end) = rest.find('"') {
                     let key = &rest[..end];
-                    if group_first_keys.contains(key) {
+                    if let Some(grp) = group_first_keys.get(key) {
+                        // A group made only of empty containers gets no separating blank line
+                        let only_empty = grp
+                            .iter()
+                            .filter_map(|k| empty_map.get(k.as_str()))
+                            .all(|empty| *empty);
+                        let rule = if only_empty {
+                            Some(LineBreakRule::None)
+                        } else {
+                            field_rules.get(key).copied()
+                        };
                         if seen_first {
-                            match field_rules.get(key).copied() {
+                            match rule {
                                 Some(LineBreakRule::None) => {
                                     if let Some(last) = out.last() {
                                         if last.is_empty() {
@@ -485,6 +997,34 @@ fn apply_linebreaks(
     out.join("\n")
 }
 
+/// Map each top-level key of a pretty-printed object to whether its value is
+/// an empty container rendered inline (`{}` or `[]`).
+fn top_level_empty_containers(pretty: &str) -> HashMap<&str, bool> {
+    let mut out: HashMap<&str, bool> = HashMap::new();
+    let mut depth: i32 = 0;
+    for line in pretty.lines() {
+        let trimmed = line.trim_start();
+        if depth == 1 && trimmed.starts_with('"') {
+            let rest = &trimmed[1..];
+            if let Some(end) = rest.find('"') {
+                let value = rest[end + 1..]
+                    .trim_start_matches(':')
+                    .trim()
+                    .trim_end_matches(',');
+                out.insert(&rest[..end], value == "{}" || value == "[]");
+            }
+        }
+        for ch in trimmed.chars() {
+            if ch == '{' {
+                depth += 1;
+            } else if ch == '}' {
+                depth -= 1;
+            }
+        }
+    }
+    out
+}
+
 /// Apply in-field line break rules for object fields listed in `in_field_rules`.
 ///
 /// When a field is `Keep`, we ensure one blank line before the child key if and
@@ -617,22 +1157,78 @@ mod tests {
         let order = OrderSpec {
             top: vec![vec!["name".into()]],
             sub,
+            preserve_matching: Vec::new(),
+            pin_first: Vec::new(),
+            pin_last: Vec::new(),
             message: None,
             level: None,
         };
-        let changed = apply_order_from(&mut json, &order.top, &order.sub);
+        let changed = apply_order_from(&mut json, &order.top, &order.sub, &order.preserve_matching);
         assert!(changed);
         let keys: Vec<_> = json.as_object().unwrap().keys().cloned().collect();
         assert_eq!(keys, vec!["name", "version", "a", "b", "z"]);
     }
 
+    #[test]
+    fn test_preserve_matching_keeps_extension_keys_in_place() {
+        let mut json = json!({
+            "x-top": 0,
+            "paths": {},
+            "x-internal": true,
+            "x-owner": "team",
+            "info": {},
+            "openapi": "3.1.0",
+            "x-first": 1
+        });
+        let top = vec![vec!["openapi".to_string(), "x-first".to_string()]];
+        apply_order_from(&mut json, &top, &HashMap::new(), &["^x-".to_string()]);
+        let keys: Vec<_> = json.as_object().unwrap().keys().cloned().collect();
+        // Listed `x-first` follows `top`; other x- keys ride with their
+        // original predecessor while the rest is sorted
+        assert_eq!(
+            keys,
+            vec![
+                "x-top",
+                "openapi",
+                "x-first",
+                "info",
+                "paths",
+                "x-internal",
+                "x-owner"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pins_compose_with_group_ordering() {
+        let mut json = json!({
+            "version": "1",
+            "z": 0,
+            "$schema": "s",
+            "name": "n",
+            "a": 1
+        });
+        let top = vec![vec!["name".to_string(), "version".to_string()]];
+        apply_order_from(&mut json, &top, &HashMap::new(), &[]);
+        apply_pins(&mut json, &["$schema".to_string()], &["name".to_string()]);
+        let keys: Vec<_> = json.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(keys, vec!["$schema", "version", "a", "z", "name"]);
+        // Absent pins are ignored; pin_first wins over pin_last
+        let keys = pin_keys(
+            vec!["a".into(), "b".into()],
+            &["b".into(), "missing".into()],
+            &["b".into()],
+        );
+        assert_eq!(keys, vec!["b", "a"]);
+    }
+
     #[test]
     fn test_apply_linebreaks_between_groups_inserts_blank_line() {
         // pretty JSON with two groups: first key is name, second group's first key is scripts
         let pretty = r#"{
   "name": "x",
   "version": "1.0.0",
-  "scripts": {},
+  "scripts": {"build": "b"},
   "dependencies": {}
 }"#
         .to_string();
@@ -652,7 +1248,7 @@ mod tests {
         let pretty = r#"{
   "name": "x",
   "license": "MIT",
-  "scripts": {}
+  "scripts": {"build": "b"}
 }"#
         .to_string();
         let groups = vec![
@@ -669,6 +1265,62 @@ mod tests {
         assert!(out_none.contains("\n\n  \"scripts\""));
     }
 
+    #[test]
+    fn test_apply_linebreaks_empty_containers_stay_inline() {
+        let mut json = json!({
+            "dependencies": {},
+            "name": "x",
+            "files": [],
+            "scripts": {"build": "b"}
+        });
+        let top = vec![
+            vec!["name".to_string()],
+            vec!["dependencies".to_string(), "files".to_string()],
+            vec!["scripts".to_string()],
+        ];
+        apply_order_from(&mut json, &top, &HashMap::new(), &[]);
+        let pretty = serde_json::to_string_pretty(&json).unwrap();
+        let mut in_rules: HashMap<String, LineBreakRule> = HashMap::new();
+        in_rules.insert("dependencies".to_string(), LineBreakRule::Keep);
+        let out = apply_linebreaks(pretty, &top, true, &HashMap::new());
+        let out = apply_in_field_linebreaks(out, &in_rules, &HashMap::new());
+        // Empty containers remain on one line
+        assert!(out.contains("\n  \"dependencies\": {},\n"));
+        assert!(out.contains("\n  \"files\": [],\n"));
+        // No blank line for a group consisting only of empty containers
+        assert!(!out.contains("\n\n  \"dependencies\""));
+        // Subsequent non-empty group still gets its blank line
+        assert!(out.contains("\n\n  \"scripts\""));
+        // No blank lines injected inside empty containers
+        assert!(!out.contains("{\n\n}"));
+        assert_eq!(out.matches("\n\n").count(), 1);
+    }
+
+    #[test]
+    fn test_to_pretty_with_width_collapses_short_scalar_arrays() {
+        let json = json!({
+            "os": ["linux", "darwin"],
+            "files": ["a-very-long-file-name.js", "another-long-file-name.js", "third.js"],
+            "nested": {"tags": [1, 2, 3]},
+            "objs": [{"a": 1}],
+            "empty": []
+        });
+        let out = to_pretty_with_width(&json, 60);
+        assert!(out.contains("\n  \"os\": [\"linux\", \"darwin\"],\n"));
+        assert!(out.contains("\n    \"tags\": [1, 2, 3]\n"));
+        // Too wide for one line: expanded one item per line
+        assert!(out.contains("\"files\": [\n    \"a-very-long-file-name.js\",\n"));
+        // Arrays containing containers keep the standard layout
+        assert!(out.contains("\"objs\": [\n    {\n      \"a\": 1\n    }\n  ]"));
+        assert!(out.contains("\"empty\": []"));
+        // Without collapsible arrays the output matches serde's pretty printer
+        let plain = json!({"a": {"b": [{"c": null}], "d": "e"}, "f": 1.5});
+        assert_eq!(
+            to_pretty_with_width(&plain, 80),
+            serde_json::to_string_pretty(&plain).unwrap()
+        );
+    }
+
     #[test]
     fn test_apply_in_field_linebreaks_keep_does_not_insert() {
         let pretty = r#"{
@@ -709,4 +1361,87 @@ mod tests {
         let out = apply_in_field_linebreaks(pretty, &rules, &keep_map);
         assert!(out.contains("\"build\": \"echo build\",\n\n    \"test\""));
     }
+
+    #[test]
+    fn test_differs_significantly_ignores_blank_lines_unless_strict() {
+        let formatted = "{\n  \"a\": 1,\n  \"b\": 2\n}\n";
+        let with_blanks = "{\n  \"a\": 1,\n\n  \"b\": 2\n}\n\n";
+        assert!(!differs_significantly(formatted, with_blanks, false));
+        assert!(differs_significantly(formatted, with_blanks, true));
+        // Reordering is always significant
+        let reordered = "{\n  \"b\": 2,\n  \"a\": 1\n}\n";
+        assert!(differs_significantly(formatted, reordered, false));
+    }
+
+    #[test]
+    fn test_trailing_comma_modes() {
+        let pretty = "{\n  \"a\": [1, 2],\n  \"b\": {},\n  \"c\": \"x,]\"\n}";
+        assert_eq!(
+            apply_trailing_commas(pretty, TrailingComma::Multiline),
+            "{\n  \"a\": [1, 2],\n  \"b\": {},\n  \"c\": \"x,]\",\n}"
+        );
+        assert_eq!(
+            apply_trailing_commas(pretty, TrailingComma::All),
+            "{\n  \"a\": [1, 2,],\n  \"b\": {},\n  \"c\": \"x,]\",\n}"
+        );
+        let with = apply_trailing_commas(pretty, TrailingComma::All);
+        assert_eq!(apply_trailing_commas(&with, TrailingComma::None), pretty);
+        // Input with trailing commas parses once stripped
+        assert!(serde_json::from_str::<Json>(&strip_trailing_commas(&with)).is_ok());
+    }
+
+    #[test]
+    fn test_has_comments_ignores_string_contents() {
+        assert!(has_comments("{\n  // note\n  \"a\": 1\n}"));
+        assert!(has_comments("{\"a\": 1 /* note */}"));
+        assert!(!has_comments("{\"url\": \"https://x.dev/*\"}"));
+        assert!(has_ignore_marker("{\n  // rigra-ignore\n  \"a\": 1\n}"));
+        assert!(!has_ignore_marker(
+            "{\n  // rigra-ignore-next\n  \"a\": 1\n}"
+        ));
+    }
+
+    #[test]
+    fn test_string_escapes_normalized_or_restored() {
+        let original = "{\n  \"name\": \"caf\\u00e9\",\n  \"url\": \"https:\\/\\/x.dev\",\n  \"q\": \"say \\\"hi\\\"\"\n}";
+        let json: Json = serde_json::from_str(original).unwrap();
+        let pretty = serde_json::to_string_pretty(&json).unwrap();
+        // Serializer output is canonical: literal non-ASCII, unescaped solidus
+        assert!(pretty.contains("\"café\""));
+        assert!(pretty.contains("\"https://x.dev\""));
+        // Opting out puts each original spelling back
+        let restored = restore_string_spellings(&pretty, original);
+        assert!(restored.contains("\"caf\\u00e9\""));
+        assert!(restored.contains("\"https:\\/\\/x.dev\""));
+        assert!(restored.contains("\"say \\\"hi\\\"\""));
+        assert_eq!(restore_string_spellings(&pretty, &pretty), pretty);
+    }
+
+    #[test]
+    fn test_patterns_for_path_narrows_matching_rules() {
+        let index: Index = toml::from_str(
+            r#"
+[[rules]]
+id = "pkg"
+patterns = ["packages/*/package.json"]
+policy = "p.toml"
+
+[[rules]]
+id = "ts"
+patterns = ["tsconfig.json"]
+policy = "t.toml"
+"#,
+        )
+        .unwrap();
+        let none = HashMap::new();
+        let o = patterns_for_path(&index, &none, "./packages/a/package.json").unwrap();
+        assert_eq!(o["pkg"], vec!["packages/a/package.json".to_string()]);
+        assert!(o["ts"].is_empty());
+        // `*` does not cross directories, and unmatched files yield None
+        assert!(patterns_for_path(&index, &none, "packages/a/b/package.json").is_none());
+        // Config overrides replace index patterns
+        let ov = HashMap::from([("ts".to_string(), vec!["cfg/*.json".to_string()])]);
+        assert!(patterns_for_path(&index, &ov, "tsconfig.json").is_none());
+        assert!(patterns_for_path(&index, &ov, "cfg/tsconfig.json").is_some());
+    }
 }
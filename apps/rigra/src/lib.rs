@@ -6,22 +6,30 @@
 //! High-level modules:
 //! - `cli`: CLI argument parsing (binary uses this).
 //! - `config`: Discovery and effective configuration resolution.
+//! - `diag`: Diagnostic (stderr) stream with optional `--log-file` tee.
 //! - `format`: Deterministic JSON formatting including ordering and line breaks.
+//! - `fix`: Deterministic autofix edits for fixable issues (`lint --fix`).
 //! - `lint`: Policy-driven validation, including order lint with message/level.
 //! - `sync`: Template synchronization with scope gating.
 //! - `models`: Data models for index, policy, and lint output structs.
 //! - `output`: Human/JSON printers for lint/format/sync.
+//! - `profile`: Opt-in Chrome trace of walk/parse/check timings (`--profile-output`).
 //! - `utils`: Supporting helpers.
 //! - `checks`: Implementation of policy checks.
+//! - `cache`: On-disk lint cache keyed on targets, policies, index, and config.
 //!
 //! Note: All documentation comments are written in English by convention.
+pub mod cache;
 pub mod checks;
 pub mod cli;
 pub mod config;
+pub mod diag;
+pub mod fix;
 pub mod format;
 pub mod lint;
 pub mod models;
 pub mod output;
+pub mod profile;
 pub mod sync;
 pub mod utils;
 pub mod conv;
@@ -1,14 +1,23 @@
 //! Lint runner for policy checks and order validation.
 //!
-//! Produces a `LintResult` with issues and a summary. Order lint uses
-//! `policy.order` with optional `message` and `level` per policy.
+//! Produces a `LintResult` with issues and a summary; problems loading the
+//! index or policies are reported as `diagnostics`, not issues. Order lint uses
+//! `policy.order` with optional `message` and `level` per policy. Rules with
+//! `multi_doc = true` lint each document of an NDJSON/multi-doc YAML file,
+//! prefixing issue paths with `[doc N]` (1-based).
 
-use crate::checks::run_checks;
+use crate::cache::{self, KeyHasher, LintCache};
+use crate::checks::{
+    has_aggregate_checks, is_date, resolve_regex_files, run_aggregate_checks, run_checks,
+    validate_pattern_sources,
+};
+use crate::format::{keep_matching_in_place, pin_keys, validate_preserve_matching};
 use crate::models::index::{Index, RuleIndex};
-use crate::models::policy::Policy;
+use crate::models::policy::{Check, Policy};
 use crate::models::sync_policy::SyncPolicy;
-use crate::models::{Issue, LintResult, RunError, Summary};
+use crate::models::{Diagnostic, Issue, LintResult, RunError, Summary};
 use crate::sync;
+use crate::utils::{conflict_markers_error, has_conflict_markers};
 use glob::glob;
 // owo_colors imported elsewhere for printing; not needed here after centralizing error prefix
 use rayon::prelude::*;
@@ -16,6 +25,8 @@ use serde_json::Value as Json;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 
 /// Run lint across files matched by the index.
 ///
@@ -24,10 +35,31 @@ use std::path::{Path, PathBuf};
 ///
 /// Severity accounting contributes to the final summary; `level = "error"`
 /// affects the error count and typical CI exit behavior upstream.
+///
+/// `max_depth` limits matched files to that many levels below the repo root
+/// (`1` = files directly in the root); `None` means unlimited.
+///
+/// With `fail_fast`, collection stops once a file has an error-severity
+/// issue and only that file's issues are reported (warnings/infos never
+/// trigger it). Files are linted in parallel, so when several fail at once
+/// the first by path is kept.
+///
+/// Rules with `max_issues` are budgets: at or under the limit their issues
+/// are reported as info; over it they keep their configured levels.
+///
+/// With `use_cache`, per-file results are reused from `.rigra/cache` when the
+/// target, its policy, the index, and the effective config are unchanged.
+///
+/// Ordering guarantee: returned issues are sorted by `(file, path, rule)`
+/// (then message), so repeated runs over the same inputs produce identical
+/// output regardless of `HashMap` iteration or parallel scheduling.
 pub fn run_lint(
     repo_root: &str,
     index_path: &str,
     scope: &str,
+    max_depth: Option<usize>,
+    use_cache: bool,
+    fail_fast: bool,
     patterns_override: &std::collections::HashMap<String, Vec<String>>,
 ) -> (LintResult, Vec<RunError>) {
     let root = PathBuf::from(repo_root);
@@ -39,75 +71,159 @@ pub fn run_lint(
             errors.push(RunError {
                 message: format!("Failed to read index: {}", idx_path.to_string_lossy()),
             });
+            let diag = Diagnostic::new(
+                "index-missing",
+                &idx_path,
+                "load-index",
+                format!(
+                    "Index file not found. Looked at '{}'. Pass --index or add rigra.toml.",
+                    idx_path.to_string_lossy()
+                ),
+            );
             return (
-                LintResult {
-                    issues: vec![Issue {
-                        file: idx_path.to_string_lossy().to_string(),
-                        rule: "load-index".into(),
-                        severity: "error".into(),
-                        path: "$".into(),
-                        message: format!(
-                            "Index file not found. Looked at '{}'. Pass --index or add rigra.toml.",
-                            idx_path.to_string_lossy()
-                        ),
-                    }],
-                    summary: Summary {
-                        errors: 1,
-                        warnings: 0,
-                        infos: 0,
-                        files: 0,
-                    },
-                },
+                LintResult::new(Vec::new(), summarize(&[], 0), Vec::new())
+                    .with_diagnostics(vec![diag]),
                 errors,
             );
         }
     };
-    let index: Index = match toml::from_str(&idx_str) {
+    lint_index(
+        &root,
+        &idx_path,
+        &idx_str,
+        errors,
+        scope,
+        max_depth,
+        use_cache,
+        fail_fast,
+        patterns_override,
+    )
+}
+
+/// Run lint from an index bundle already in memory (`rigra lint --index -`).
+///
+/// The bundle is an index that may carry its policies inline under
+/// `[policies.<name>]`; relative paths in it resolve from the repo root.
+pub fn run_lint_bundle(
+    repo_root: &str,
+    bundle: &str,
+    scope: &str,
+    max_depth: Option<usize>,
+    use_cache: bool,
+    fail_fast: bool,
+    patterns_override: &std::collections::HashMap<String, Vec<String>>,
+) -> (LintResult, Vec<RunError>) {
+    let root = PathBuf::from(repo_root);
+    let idx_path = root.join("<stdin>");
+    lint_index(
+        &root,
+        &idx_path,
+        bundle,
+        Vec::new(),
+        scope,
+        max_depth,
+        use_cache,
+        fail_fast,
+        patterns_override,
+    )
+}
+
+/// Lint against index text loaded from `idx_path` (or standing in for it).
+#[allow(clippy::too_many_arguments)]
+fn lint_index(
+    root: &PathBuf,
+    idx_path: &PathBuf,
+    idx_str: &str,
+    mut errors: Vec<RunError>,
+    scope: &str,
+    max_depth: Option<usize>,
+    use_cache: bool,
+    fail_fast: bool,
+    patterns_override: &std::collections::HashMap<String, Vec<String>>,
+) -> (LintResult, Vec<RunError>) {
+    let mut index: Index = match toml::from_str(idx_str) {
         Ok(ix) => ix,
         Err(_) => {
             errors.push(RunError {
                 message: format!("Failed to parse index TOML: {}", idx_path.to_string_lossy()),
             });
+            let diag = Diagnostic::new(
+                "index-invalid",
+                idx_path,
+                "parse-index",
+                "Index file is not valid TOML",
+            );
             return (
-                LintResult {
-                    issues: vec![Issue {
-                        file: idx_path.to_string_lossy().to_string(),
-                        rule: "parse-index".into(),
-                        severity: "error".into(),
-                        path: "$".into(),
-                        message: "Index file is not valid TOML".into(),
-                    }],
-                    summary: Summary {
-                        errors: 1,
-                        warnings: 0,
-                        infos: 0,
-                        files: 0,
-                    },
-                },
+                LintResult::new(Vec::new(), summarize(&[], 0), Vec::new())
+                    .with_diagnostics(vec![diag]),
                 errors,
             );
         }
     };
 
     let mut issues: Vec<Issue> = Vec::new();
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
     let mut files_count: usize = 0;
 
     // Cache policies across rules by path to avoid repeated I/O and parse when shared
     let mut policy_cache: HashMap<PathBuf, Policy> = HashMap::new();
+    // Files loaded via `regex_from`, shared across policies for the run
+    let mut regex_files: HashMap<PathBuf, String> = HashMap::new();
+    // Inline policies stand in for files of the same name beside the index
+    let idx_dir = idx_path.parent().unwrap_or_else(|| Path::new("."));
+    for (name, mut p) in std::mem::take(&mut index.policies) {
+        let valid =
+            validate_pattern_sources(&p.checks).and_then(|_| validate_preserve_matching(&p));
+        if let Err(msg) = valid {
+            diagnostics.push(Diagnostic::new("rule-config", idx_path, &name, msg));
+            continue;
+        }
+        if let Err(msg) = resolve_regex_files(&mut p.checks, idx_dir, &mut regex_files) {
+            diagnostics.push(Diagnostic::new("regex-file", idx_path, &name, msg));
+            continue;
+        }
+        policy_cache.insert(idx_dir.join(name), p);
+    }
+    let lint_cache = use_cache.then(|| LintCache::load(root));
+    let global = cache::global_key(root, idx_str, scope, max_depth, patterns_override);
+    let stop = AtomicBool::new(false);
+    // Rules that ran, reported as pass/fail in the result
+    let mut rule_ids: Vec<String> = Vec::new();
     for ri in index.rules {
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        rule_ids.push(ri.id.clone());
         lint_rule(
-            &root,
-            &idx_path,
+            root,
+            idx_path,
             ri,
             &mut issues,
+            &mut diagnostics,
+            &mut errors,
             &mut files_count,
             &mut policy_cache,
+            &mut regex_files,
+            max_depth,
             patterns_override,
+            lint_cache.as_ref().map(|c| (c, global)),
+            fail_fast.then_some(&stop),
         );
     }
+    if let Some(c) = lint_cache {
+        if let Err(e) = c.save() {
+            errors.push(RunError {
+                message: format!("Failed to write lint cache: {}", e),
+            });
+        }
+    }
 
-    // Evaluate sync status into lint using external policy
-    if let Some(sync_ref) = index.sync_ref.as_ref() {
+    // Evaluate sync status into lint using external policy (skipped once fail-fast tripped)
+    let sync_ref = index
+        .sync_ref
+        .as_ref()
+        .filter(|_| !stop.load(Ordering::Relaxed));
+    if let Some(sync_ref) = sync_ref {
         let pol_path = idx_path
             .parent()
             .unwrap_or_else(|| Path::new("."))
@@ -116,13 +232,12 @@ pub fn run_lint(
             if let Ok(policy) = toml::from_str::<SyncPolicy>(&pol_str) {
                 let defaults = policy.lint.unwrap_or_default();
                 for rule in policy.sync {
-                    if !is_rule_enabled(&rule.when, scope) {
+                    if !is_rule_enabled(&rule.when, scope) || !sync::conditions_met(root, &rule) {
                         continue;
                     }
-                    // src resolved relative to index
-                    let src = idx_path.parent().unwrap().join(&rule.source);
+                    rule_ids.push(format!("sync:{}", rule.id));
                     // apply client target override
-                    let client_cfg = crate::config::load_config(&root).unwrap_or_default();
+                    let client_cfg = crate::config::load_config(root).unwrap_or_default();
                     let dst_target = client_cfg
                         .sync
                         .as_ref()
@@ -131,68 +246,168 @@ pub fn run_lint(
                         .and_then(|c| c.target.clone())
                         .unwrap_or_else(|| rule.target.clone());
                     let dst = root.join(&dst_target);
-                    let (_w, would_write) = sync::apply_sync(
-                        &root,
-                        &rule,
-                        &src,
-                        &dst,
-                        client_cfg
-                            .sync
-                            .as_ref()
-                            .and_then(|s| s.config.as_ref())
-                            .and_then(|m| m.get(&rule.id)),
-                        false,
-                        Some(&mut errors),
-                    );
-                    if would_write {
-                        let sev = rule
-                            .level
-                            .clone()
-                            .or(defaults.level.clone())
-                            .unwrap_or_else(|| "info".to_string());
-                        let msg = rule
-                            .message
-                            .clone()
-                            .or(defaults.message.clone())
-                            .unwrap_or_else(|| {
-                                "Not synced yet. Please run rigra sync.".to_string()
+                    // Sources resolve relative to the index; globs expand per match
+                    let pairs =
+                        sync::expand_source(idx_dir, &rule.id, &rule.source, &dst, &mut errors);
+                    for (src, dst) in pairs {
+                        let would_write = sync::apply_sync(
+                            root,
+                            &rule,
+                            &src,
+                            &dst,
+                            client_cfg
+                                .sync
+                                .as_ref()
+                                .and_then(|s| s.config.as_ref())
+                                .and_then(|m| m.get(&rule.id)),
+                            false,
+                            false,
+                            false,
+                            Some(&mut errors),
+                        )
+                        .would_write;
+                        if would_write {
+                            let sev = rule
+                                .level
+                                .clone()
+                                .or(defaults.level.clone())
+                                .unwrap_or_else(|| "info".to_string());
+                            let msg = rule
+                                .message
+                                .clone()
+                                .or(defaults.message.clone())
+                                .unwrap_or_else(|| {
+                                    "Not synced yet. Please run rigra sync.".to_string()
+                                });
+                            issues.push(Issue {
+                                file: crate::utils::rel_to_wd(&dst),
+                                rule: format!("sync:{}", rule.id),
+                                severity: sev,
+                                fixable: true,
+                                path: "$".into(),
+                                message: msg,
+                                code: None,
                             });
-                        issues.push(Issue {
-                            file: dst.to_string_lossy().to_string(),
-                            rule: format!("sync:{}", rule.id),
-                            severity: sev,
-                            path: "$".into(),
-                            message: msg,
-                        });
+                        }
                     }
                 }
             }
         }
     }
 
+    if fail_fast {
+        // Report only the first failing file (by path) for a stable answer
+        let first = issues
+            .iter()
+            .filter(|i| i.severity == "error")
+            .map(|i| i.file.clone())
+            .min();
+        if let Some(first) = first {
+            issues.retain(|i| i.file == first);
+            files_count = 1;
+        }
+    }
+
+    issues.sort_by(|a, b| {
+        a.file
+            .cmp(&b.file)
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.rule.cmp(&b.rule))
+            .then_with(|| a.message.cmp(&b.message))
+    });
+
+    let summary = summarize(&issues, files_count);
+    (
+        LintResult::new(issues, summary, rule_ids).with_diagnostics(diagnostics),
+        errors,
+    )
+}
+
+/// Run a single policy against an inline JSON document (`rigra test-policy`).
+///
+/// `label` stands in for the file name on reported issues. Both checks and
+/// order lint are evaluated, exactly as `run_lint` would for one file.
+pub fn run_policy_test(
+    policy_path: &Path,
+    input: &str,
+    label: &str,
+) -> (LintResult, Vec<RunError>) {
+    let mut errors: Vec<RunError> = Vec::new();
+    let empty = |errors| {
+        (
+            LintResult::new(Vec::new(), summarize(&[], 0), Vec::new()),
+            errors,
+        )
+    };
+    let mut policy: Policy = match fs::read_to_string(policy_path) {
+        Ok(s) => match toml::from_str(&s) {
+            Ok(p) => p,
+            Err(e) => {
+                errors.push(RunError {
+                    message: format!(
+                        "Invalid policy TOML: {} — {}",
+                        policy_path.to_string_lossy(),
+                        e
+                    ),
+                });
+                return empty(errors);
+            }
+        },
+        Err(e) => {
+            errors.push(RunError {
+                message: format!(
+                    "Failed to read policy: {} — {}",
+                    policy_path.to_string_lossy(),
+                    e
+                ),
+            });
+            return empty(errors);
+        }
+    };
+    let pol_dir = policy_path.parent().unwrap_or_else(|| Path::new("."));
+    if let Err(msg) = validate_pattern_sources(&policy.checks)
+        .and_then(|_| validate_preserve_matching(&policy))
+        .and_then(|_| resolve_regex_files(&mut policy.checks, pol_dir, &mut HashMap::new()))
+    {
+        errors.push(RunError { message: msg });
+        return empty(errors);
+    }
+    let json: Json = match serde_json::from_str(input) {
+        Ok(v) => v,
+        Err(e) => {
+            errors.push(RunError {
+                message: format!("Input is not valid JSON: {}", e),
+            });
+            return empty(errors);
+        }
+    };
+    let issues = lint_document(&policy, &json, &PathBuf::from(label), "test-policy", false);
+    let summary = summarize(&issues, 1);
+    (
+        LintResult::new(issues, summary, ["test-policy".to_string()]),
+        errors,
+    )
+}
+
+fn summarize(issues: &[Issue], files: usize) -> Summary {
     let mut errs = 0usize;
     let mut warns = 0usize;
     let mut infos = 0usize;
-    for is in &issues {
+    for is in issues {
         match is.severity.as_str() {
             "error" => errs += 1,
-            "warning" => warns += 1,
+            "warning" | "warn" => warns += 1,
             _ => infos += 1,
         }
     }
-    (
-        LintResult {
-            issues,
-            summary: Summary {
-                errors: errs,
-                warnings: warns,
-                infos,
-                files: files_count,
-            },
-        },
-        errors,
-    )
+    Summary {
+        errors: errs,
+        warnings: warns,
+        infos,
+        files,
+    }
 }
+
 fn is_rule_enabled(when: &str, scope: &str) -> bool {
     let w = when.trim();
     if w.is_empty() || w == "*" || w.eq_ignore_ascii_case("any") || w.eq_ignore_ascii_case("all") {
@@ -204,15 +419,44 @@ fn is_rule_enabled(when: &str, scope: &str) -> bool {
 }
 
 /// Lint a single indexed rule against its targets, collecting issues.
+#[allow(clippy::too_many_arguments)]
 fn lint_rule(
     root: &PathBuf,
     idx_path: &PathBuf,
     ri: RuleIndex,
     issues: &mut Vec<Issue>,
+    diagnostics: &mut Vec<Diagnostic>,
+    errors: &mut Vec<RunError>,
     files_count: &mut usize,
     policy_cache: &mut HashMap<PathBuf, Policy>,
+    regex_files: &mut HashMap<PathBuf, String>,
+    max_depth: Option<usize>,
     patterns_override: &std::collections::HashMap<String, Vec<String>>,
+    cache: Option<(&LintCache, KeyHasher)>,
+    stop: Option<&AtomicBool>,
 ) {
+    // Staged rollout: checks without an explicit `level` report warnings
+    // until the date has passed, then errors
+    let escalated: Option<&str> = match ri.escalate_after.as_deref() {
+        None => None,
+        Some(d) if is_date(d) => Some(if crate::utils::today_utc().as_str() > d {
+            "error"
+        } else {
+            "warn"
+        }),
+        Some(d) => {
+            diagnostics.push(Diagnostic::new(
+                "rule-config",
+                idx_path,
+                &ri.id,
+                format!(
+                    "Invalid escalate_after '{}' for rule '{}' (expected YYYY-MM-DD)",
+                    d, ri.id
+                ),
+            ));
+            return;
+        }
+    };
     let pol_path = idx_path
         .parent()
         .unwrap_or_else(|| Path::new("."))
@@ -223,22 +467,32 @@ fn lint_rule(
         let pol_str = match fs::read_to_string(&pol_path) {
             Ok(s) => s,
             Err(_) => {
-                issues.push(Issue {
-                    file: pol_path.to_string_lossy().to_string(),
-                    rule: ri.id.clone(),
-                    severity: "error".into(),
-                    path: "$".into(),
-                    message: format!(
+                diagnostics.push(Diagnostic::new(
+                    "policy-missing",
+                    &pol_path,
+                    &ri.id,
+                    format!(
                         "Policy file not found for rule '{}': {}",
                         ri.id,
                         pol_path.to_string_lossy()
                     ),
-                });
+                ));
                 return;
             }
         };
         match toml::from_str::<Policy>(&pol_str) {
-            Ok(p) => {
+            Ok(mut p) => {
+                let valid = validate_pattern_sources(&p.checks)
+                    .and_then(|_| validate_preserve_matching(&p));
+                if let Err(msg) = valid {
+                    diagnostics.push(Diagnostic::new("rule-config", &pol_path, &ri.id, msg));
+                    return;
+                }
+                let pol_dir = pol_path.parent().unwrap_or_else(|| Path::new("."));
+                if let Err(msg) = resolve_regex_files(&mut p.checks, pol_dir, regex_files) {
+                    diagnostics.push(Diagnostic::new("regex-file", &pol_path, &ri.id, msg));
+                    return;
+                }
                 // Insert and then fetch without unwrap to avoid panic
                 policy_cache.insert(pol_path.clone(), p);
                 if let Some(pref) = policy_cache.get(&pol_path) {
@@ -248,23 +502,36 @@ fn lint_rule(
                 }
             }
             Err(_) => {
-                issues.push(Issue {
-                    file: pol_path.to_string_lossy().to_string(),
-                    rule: ri.id.clone(),
-                    severity: "error".into(),
-                    path: "$".into(),
-                    message: "Policy file is not valid TOML".into(),
-                });
+                diagnostics.push(Diagnostic::new(
+                    "policy-invalid",
+                    &pol_path,
+                    &ri.id,
+                    "Policy file is not valid TOML",
+                ));
                 return;
             }
         }
     };
+    let escalated_policy: Policy;
+    let policy: &Policy = match escalated {
+        Some(sev) => {
+            let mut p = policy.clone();
+            p.checks.iter_mut().for_each(|c| c.default_level(sev));
+            if let Some(order) = p.order.as_mut() {
+                order.level.get_or_insert_with(|| sev.to_string());
+            }
+            escalated_policy = p;
+            &escalated_policy
+        }
+        None => policy,
+    };
 
     // Choose patterns: override from rigra.toml if available, otherwise index defaults
     let use_patterns: Vec<String> = patterns_override
         .get(&ri.id)
         .cloned()
         .unwrap_or_else(|| ri.patterns.clone());
+    let walk = crate::profile::span("walk", || format!("lint {}", ri.id));
     let mut targets: Vec<PathBuf> = Vec::new();
     for pat in use_patterns.iter() {
         let abs_glob = root.join(pat);
@@ -272,75 +539,251 @@ fn lint_rule(
         let itr = match glob(&pattern) {
             Ok(it) => it,
             Err(e) => {
-                eprintln!(
-                    "{} {}",
-                    crate::utils::error_prefix(),
-                    format!(
-                        "Invalid glob pattern for rule '{}': {} — {}",
-                        ri.id, pattern, e
-                    )
-                );
+                crate::diag::error(format!(
+                    "Invalid glob pattern for rule '{}': {} — {}",
+                    ri.id, pattern, e
+                ));
                 continue;
             }
         };
         for entry in itr {
             if let Ok(p) = entry {
-                targets.push(p);
+                if crate::utils::within_depth(root, &p, max_depth) {
+                    targets.push(p);
+                }
             }
         }
     }
+    drop(walk);
+
+    crate::diag::debug(|| {
+        format!(
+            "rule '{}': policy {}, patterns [{}] matched {} file(s)",
+            ri.id,
+            pol_path.display(),
+            use_patterns.join(", "),
+            targets.len()
+        )
+    });
+    for t in targets.iter() {
+        crate::diag::debug(|| format!("rule '{}': {}", ri.id, crate::utils::rel_to_wd(t)));
+    }
+
+    // Cache key prefix for this rule: run-wide inputs + policy contents.
+    // `pathExists` depends on other files on disk, so such rules are never cached.
+    let fs_dependent = policy
+        .checks
+        .iter()
+        .flat_map(Check::walk)
+        .any(|c| matches!(c, Check::PathExists { .. }));
+    let rule_key = cache.filter(|_| !fs_dependent).map(|(c, global)| {
+        let mut key = global
+            .part(ri.id.as_bytes())
+            .part(&fs::read(&pol_path).unwrap_or_default())
+            .part(&[ri.multi_doc as u8])
+            .part(escalated.unwrap_or_default().as_bytes());
+        // Patterns loaded via `regex_from` are not part of the policy text
+        for chk in policy.checks.iter().flat_map(Check::walk) {
+            if let Check::Pattern {
+                regex,
+                regex_from: Some(_),
+                ..
+            } = chk
+            {
+                key = key.part(regex.as_bytes());
+            }
+        }
+        (c, key)
+    });
 
+    // Budget rules need every file's issues before deciding pass/fail, so
+    // fail-fast only applies once the budget has been evaluated
+    let file_stop = stop.filter(|_| ri.max_issues.is_none());
+    let conflicted: Mutex<Vec<RunError>> = Mutex::new(Vec::new());
     let mut per_file: Vec<(Vec<Issue>, usize)> = targets
         .par_iter()
         .map(|path| {
+            if file_stop.is_some_and(|s| s.load(Ordering::Relaxed)) {
+                return (Vec::new(), 0);
+            }
             let data = match fs::read_to_string(path) {
                 Ok(s) => s,
                 Err(_) => return (Vec::new(), 0),
             };
-            let json: Json = match serde_json::from_str(&data) {
-                Ok(v) => v,
-                Err(_) => return (Vec::new(), 0),
-            };
-            let mut file_issues: Vec<Issue> = Vec::new();
-            let mut found = run_checks(&policy.checks, &json, path, &ri.id);
-            file_issues.append(&mut found);
-            if let Some(ord) = policy.order.as_ref() {
-                if let Json::Object(obj) = &json {
-                    let actual: Vec<String> = obj.keys().cloned().collect();
-                    let mut expected: Vec<String> = Vec::new();
-                    for group in &ord.top {
-                        for key in group {
-                            if obj.contains_key(key.as_str()) {
-                                expected.push(key.clone());
-                            }
-                        }
+            if has_conflict_markers(&data) {
+                if let Ok(mut c) = conflicted.lock() {
+                    c.push(conflict_markers_error(path));
+                }
+                return (Vec::new(), 0);
+            }
+            let trip = |file_issues: &[Issue]| {
+                if let Some(s) = file_stop {
+                    if file_issues.iter().any(|i| i.severity == "error") {
+                        s.store(true, Ordering::Relaxed);
                     }
-                    let mut rest: Vec<String> = obj
-                        .keys()
-                        .filter(|k| !expected.contains(k))
-                        .cloned()
-                        .collect();
-                    rest.sort();
-                    expected.extend(rest);
-                    if expected != actual {
-                        file_issues.push(Issue {
-                            file: path.to_string_lossy().to_string(),
-                            rule: ri.id.clone(),
-                            severity: ord.level.clone().unwrap_or_else(|| "error".to_string()),
-                            path: "$".to_string(),
-                            message: ord.message.clone().unwrap_or_else(|| {
-                                "Object key order does not match policy".to_string()
-                            }),
-                        });
+                }
+            };
+            let cached = rule_key.map(|(c, key)| {
+                let k = key
+                    .part(path.to_string_lossy().as_bytes())
+                    .part(data.as_bytes())
+                    .finish();
+                (c, k)
+            });
+            if let Some((c, k)) = cached.as_ref() {
+                if let Some(hit) = c.get(k) {
+                    trip(&hit);
+                    return (hit, 1);
+                }
+            }
+            match lint_target(policy, &ri, path, &data) {
+                Some(file_issues) => {
+                    if let Some((c, k)) = cached.as_ref() {
+                        c.put(k, &file_issues);
                     }
+                    trip(&file_issues);
+                    (file_issues, 1)
                 }
+                None => (Vec::new(), 0),
             }
-            (file_issues, 1)
         })
         .collect();
     // Deterministic ordering of issues by file then message
     let mut combined: Vec<Issue> = per_file.iter_mut().flat_map(|(v, _)| v.drain(..)).collect();
+    // Cross-file checks see every parseable single-document target; they are
+    // recomputed each run since the per-file cache cannot capture them
+    if !ri.multi_doc && has_aggregate_checks(&policy.checks) {
+        let mut sorted = targets.clone();
+        sorted.sort();
+        let docs: Vec<(PathBuf, Json)> = sorted
+            .into_iter()
+            .filter_map(|p| {
+                let data = fs::read_to_string(&p).ok()?;
+                if has_conflict_markers(&data) {
+                    return None;
+                }
+                let json = serde_json::from_str(&data).ok()?;
+                Some((p, json))
+            })
+            .collect();
+        combined.extend(run_aggregate_checks(&policy.checks, &docs, &ri.id));
+    }
     combined.sort_by(|a, b| a.file.cmp(&b.file).then(a.message.cmp(&b.message)));
     *files_count += per_file.iter().map(|(_, c)| *c).sum::<usize>();
+    // Within budget, the rule passes and its issues are kept as info
+    if let Some(max) = ri.max_issues {
+        if combined.len() <= max {
+            for is in combined.iter_mut() {
+                is.severity = "info".into();
+            }
+        } else if let Some(s) = stop {
+            if combined.iter().any(|i| i.severity == "error") {
+                s.store(true, Ordering::Relaxed);
+            }
+        }
+    }
     issues.extend(combined);
+    let mut conflicted = conflicted.into_inner().unwrap_or_default();
+    conflicted.sort_by(|a, b| a.message.cmp(&b.message));
+    errors.extend(conflicted);
+}
+
+/// Lint the contents of one target file; `None` when it cannot be parsed.
+fn lint_target(policy: &Policy, ri: &RuleIndex, path: &PathBuf, data: &str) -> Option<Vec<Issue>> {
+    let parse = crate::profile::span("parse", || crate::utils::rel_to_wd(path));
+    if ri.multi_doc {
+        let docs = split_documents(path, data)?;
+        drop(parse);
+        let mut file_issues: Vec<Issue> = Vec::new();
+        for (i, doc) in docs.iter().enumerate() {
+            for mut is in lint_document(policy, doc, path, &ri.id, false) {
+                is.path = format!("[doc {}] {}", i + 1, is.path);
+                file_issues.push(is);
+            }
+        }
+        return Some(file_issues);
+    }
+    let json: Json = serde_json::from_str(data).ok()?;
+    drop(parse);
+    Some(lint_document(policy, &json, path, &ri.id, true))
+}
+
+/// Run policy checks and order lint against a single parsed document.
+///
+/// `order_fixable` marks order issues as auto-fixable by `rigra format`,
+/// which only applies to single-document JSON files.
+fn lint_document(
+    policy: &Policy,
+    json: &Json,
+    path: &PathBuf,
+    rule_id: &str,
+    order_fixable: bool,
+) -> Vec<Issue> {
+    let mut file_issues = run_checks(&policy.checks, json, path, rule_id);
+    if let Some(ord) = policy.order.as_ref() {
+        if let Json::Object(obj) = json {
+            let actual: Vec<String> = obj.keys().cloned().collect();
+            let mut expected: Vec<String> = Vec::new();
+            for group in &ord.top {
+                for key in group {
+                    if obj.contains_key(key.as_str()) {
+                        expected.push(key.clone());
+                    }
+                }
+            }
+            let mut rest: Vec<String> = obj
+                .keys()
+                .filter(|k| !expected.contains(k))
+                .cloned()
+                .collect();
+            rest.sort();
+            expected.extend(rest);
+            let listed = |k: &str| ord.top.iter().flatten().any(|t| t == k);
+            let expected =
+                keep_matching_in_place(&actual, expected, &ord.preserve_matching, &listed);
+            let expected = pin_keys(expected, &ord.pin_first, &ord.pin_last);
+            if expected != actual {
+                file_issues.push(Issue {
+                    file: crate::utils::rel_to_wd(path),
+                    rule: rule_id.to_string(),
+                    severity: ord.level.clone().unwrap_or_else(|| "error".to_string()),
+                    fixable: order_fixable,
+                    path: "$".to_string(),
+                    message: ord
+                        .message
+                        .clone()
+                        .unwrap_or_else(|| "Object key order does not match policy".to_string()),
+                    code: Some("order".into()),
+                });
+            }
+        }
+    }
+    file_issues
+}
+
+/// Split a multi-document file into JSON values.
+///
+/// `.yaml`/`.yml` files are read as `---`-separated YAML documents; anything
+/// else is read as a JSON stream (NDJSON or concatenated values). Returns
+/// `None` when any document fails to parse.
+fn split_documents(path: &Path, data: &str) -> Option<Vec<Json>> {
+    let is_yaml = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("yaml") || e.eq_ignore_ascii_case("yml"))
+        .unwrap_or(false);
+    if is_yaml {
+        use serde::Deserialize;
+        let mut docs = Vec::new();
+        for de in serde_yaml::Deserializer::from_str(data) {
+            let v = serde_yaml::Value::deserialize(de).ok()?;
+            docs.push(serde_json::to_value(v).ok()?);
+        }
+        Some(docs)
+    } else {
+        serde_json::Deserializer::from_str(data)
+            .into_iter::<Json>()
+            .collect::<Result<Vec<_>, _>>()
+            .ok()
+    }
 }
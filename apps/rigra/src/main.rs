@@ -1,14 +1,18 @@
 //! Rigra CLI binary entry point.
 //! Delegates to modules for lint/format/sync and prints results.
 
+mod cache;
 mod checks;
 mod cli;
 mod config;
 mod conv;
+mod diag;
+mod fix;
 mod format;
 mod lint;
 mod models;
 mod output;
+mod profile;
 mod sync;
 mod utils;
 
@@ -21,16 +25,48 @@ use std::fs;
 fn main() {
     // Early help handling to avoid surprises; prints long help and exits
     // Rely on Clap's auto help; no early manual printing
+    output::start_timer();
     let cli = Cli::parse();
+    if cli.silent {
+        diag::set_silent(true);
+    }
+    if cli.offline {
+        conv::set_offline(true);
+    }
+    if let Some(path) = cli.log_file.as_deref() {
+        if let Err(e) = diag::set_log_file(std::path::Path::new(path)) {
+            diag::error(format!("Failed to open log file '{}': {}", path, e));
+            std::process::exit(2);
+        }
+    }
+    if let Some(choice) = cli.color.as_deref() {
+        utils::set_color_choice(choice);
+    }
+    if let Some(path) = cli.profile_output.as_deref() {
+        profile::enable(std::path::Path::new(path));
+    }
+    // Flushes the profile on normal return; `exit` covers early exits
+    let _profile = ProfileGuard;
     match cli.cmd {
-        Commands::Version => {
-            println!("{}", env!("CARGO_PKG_VERSION"));
+        Commands::Version { verbose } => {
+            if verbose {
+                for line in output::compose_build_info() {
+                    output::print_line(line);
+                }
+            } else {
+                output::print_line(env!("CARGO_PKG_VERSION"));
+            }
         }
         Commands::Lint {
             repo_root,
             scope,
             output,
             index,
+            fix,
+            max_depth,
+            cache,
+            fail_fast,
+            show_source,
         } => {
             let eff = config::resolve_effective(
                 repo_root.as_deref(),
@@ -43,33 +79,27 @@ fn main() {
             );
             // Require index to be configured (no default)
             if !eff.index_configured {
-                eprintln!(
-                    "{} {}",
-                    crate::utils::error_prefix(),
-                    "Index is not configured. Pass --index or add rigra.toml."
-                );
-                std::process::exit(2);
+                crate::diag::error("Index is not configured. Pass --index or add rigra.toml.");
+                exit(2);
             }
             // Friendly note if no rigra config was found
             if config::load_config(&eff.repo_root).is_none() {
-                eprintln!(
-                    "{} {}",
-                    crate::utils::note_prefix(),
-                    "No rigra.toml found; using defaults."
-                );
+                crate::diag::note("No rigra.toml found; using defaults.");
+            }
+            // '-' reads an index bundle (index + inline policies) from stdin
+            let from_stdin = eff.index == "-";
+            if from_stdin && fix {
+                crate::diag::error("--fix cannot be combined with --index - (stdin bundle)");
+                exit(2);
             }
             // Friendly error if index file is missing
             let idx_path = eff.repo_root.join(&eff.index);
-            if !idx_path.exists() {
-                eprintln!(
-                    "{} {}",
-                    crate::utils::error_prefix(),
-                    format!(
-                        "Index file not found: {} (pass --index or configure rigra.toml)",
-                        idx_path.to_string_lossy()
-                    )
-                );
-                std::process::exit(2);
+            if !from_stdin && !idx_path.exists() {
+                crate::diag::error(format!(
+                    "Index file not found: {} (pass --index or configure rigra.toml)",
+                    idx_path.to_string_lossy()
+                ));
+                exit(2);
             }
             // Emit single top info when default patterns from index are used (no overrides in rigra.toml)
             if eff.output != "json" {
@@ -87,25 +117,79 @@ fn main() {
                         if !pat_set.is_empty() {
                             let joined =
                                 format!("[{}]", pat_set.into_iter().collect::<Vec<_>>().join(", "));
-                            eprintln!(
-                                "{} {}",
-                                crate::utils::info_prefix(),
-                                format!("Using default patterns: {}", joined)
-                            );
+                            crate::diag::info(format!("Using default patterns: {}", joined));
                         }
                     }
                 }
             }
             let repo_root_str = eff.repo_root.to_string_lossy().to_string();
-            let (result, errors) = lint::run_lint(
-                &repo_root_str,
-                &eff.index,
-                &eff.scope,
-                &eff.pattern_overrides,
-            );
-            output::print_lint(&result, &eff.output, &errors);
-            if result.summary.errors > 0 {
-                std::process::exit(1);
+            if fix {
+                let layout = format::Layout {
+                    strict_linebreak: eff.strict_linebreak,
+                    max_width: eff.max_width,
+                    normalize_strings: eff.normalize_strings,
+                    between_groups: eff.lb_between_groups,
+                    before_fields: &eff.lb_before_fields,
+                    in_fields: &eff.lb_in_fields,
+                };
+                let (fixed, fix_errors) = fix::run_fix(
+                    &repo_root_str,
+                    &eff.index,
+                    true,
+                    max_depth,
+                    &layout,
+                    &eff.pattern_overrides,
+                );
+                for e in fix_errors.iter() {
+                    crate::diag::error(&e.message);
+                }
+                if eff.output != "json" {
+                    for r in fixed.iter().filter(|r| r.wrote) {
+                        crate::diag::info(format!(
+                            "Fixed {} ({} edit(s))",
+                            crate::utils::rel_to_wd(std::path::Path::new(&r.file)),
+                            r.applied
+                        ));
+                    }
+                }
+            }
+            let (result, errors) = if from_stdin {
+                let mut bundle = String::new();
+                if let Err(e) = std::io::Read::read_to_string(&mut std::io::stdin(), &mut bundle) {
+                    crate::diag::error(format!("Failed to read stdin: {}", e));
+                    exit(2);
+                }
+                lint::run_lint_bundle(
+                    &repo_root_str,
+                    &bundle,
+                    &eff.scope,
+                    max_depth,
+                    cache,
+                    fail_fast,
+                    &eff.pattern_overrides,
+                )
+            } else {
+                lint::run_lint(
+                    &repo_root_str,
+                    &eff.index,
+                    &eff.scope,
+                    max_depth,
+                    cache,
+                    fail_fast,
+                    &eff.pattern_overrides,
+                )
+            };
+            diag::debug(|| {
+                format!(
+                    "lint: {} file(s), {} issue(s) in {} ms",
+                    result.summary.files,
+                    result.issues.len(),
+                    output::elapsed_ms().unwrap_or(0)
+                )
+            });
+            output::print_lint(&result, "lint", &eff.output, &errors, show_source);
+            if !result.passed {
+                exit(1);
             }
         }
         Commands::Format {
@@ -115,6 +199,8 @@ fn main() {
             check,
             output,
             index,
+            path,
+            stdout,
         } => {
             let eff = config::resolve_effective(
                 repo_root.as_deref(),
@@ -126,34 +212,22 @@ fn main() {
                 if check { Some(true) } else { None },
             );
             if !eff.index_configured {
-                eprintln!(
-                    "{} {}",
-                    crate::utils::error_prefix(),
-                    "Index is not configured. Pass --index or add rigra.toml."
-                );
-                std::process::exit(2);
+                crate::diag::error("Index is not configured. Pass --index or add rigra.toml.");
+                exit(2);
             }
             if config::load_config(&eff.repo_root).is_none() {
-                eprintln!(
-                    "{} {}",
-                    crate::utils::note_prefix(),
-                    "No rigra.toml found; using defaults."
-                );
+                crate::diag::note("No rigra.toml found; using defaults.");
             }
             let idx_path = eff.repo_root.join(&eff.index);
             if !idx_path.exists() {
-                eprintln!(
-                    "{} {}",
-                    crate::utils::error_prefix(),
-                    format!(
-                        "Index file not found: {} (pass --index or configure rigra.toml)",
-                        idx_path.to_string_lossy()
-                    )
-                );
-                std::process::exit(2);
+                crate::diag::error(format!(
+                    "Index file not found: {} (pass --index or configure rigra.toml)",
+                    idx_path.to_string_lossy()
+                ));
+                exit(2);
             }
             // Emit single top info when default patterns from index are used (no overrides in rigra.toml)
-            if eff.output != "json" {
+            if eff.output != "json" && path.is_none() {
                 if let Ok(s) = fs::read_to_string(&idx_path) {
                     if let Ok(ix) = toml::from_str::<Index>(&s) {
                         let mut pat_set: std::collections::BTreeSet<String> =
@@ -168,21 +242,39 @@ fn main() {
                         if !pat_set.is_empty() {
                             let joined =
                                 format!("[{}]", pat_set.into_iter().collect::<Vec<_>>().join(", "));
-                            eprintln!(
-                                "{} {}",
-                                crate::utils::info_prefix(),
-                                format!("Using default patterns: {}", joined)
-                            );
+                            crate::diag::info(format!("Using default patterns: {}", joined));
                         }
                     }
                 }
             }
+            // --path narrows every rule to that one file (or nothing)
+            let mut pattern_overrides = eff.pattern_overrides.clone();
+            if let Some(p) = path.as_deref() {
+                let rel = std::path::Path::new(p)
+                    .strip_prefix(&eff.repo_root)
+                    .unwrap_or(std::path::Path::new(p))
+                    .to_string_lossy()
+                    .to_string();
+                let index = fs::read_to_string(&idx_path)
+                    .ok()
+                    .and_then(|s| toml::from_str::<Index>(&s).ok());
+                let narrowed = index
+                    .and_then(|ix| format::patterns_for_path(&ix, &eff.pattern_overrides, &rel));
+                match narrowed {
+                    Some(o) => pattern_overrides = o,
+                    None => {
+                        crate::diag::error(format!("No format rule matches '{}'", rel));
+                        exit(2);
+                    }
+                }
+            }
             // CLI/config precedence at runtime:
             // - If diff or check is enabled, force write=false for this run.
+            // - With --stdout, never write; the content goes to stdout instead.
             // - Otherwise respect write.
-            let eff_diff = eff.diff;
-            let eff_check = eff.check;
-            let eff_write = if eff_diff || eff_check {
+            let eff_diff = eff.diff && !stdout;
+            let eff_check = eff.check && !stdout;
+            let eff_write = if eff_diff || eff_check || stdout {
                 false
             } else {
                 eff.write
@@ -192,16 +284,50 @@ fn main() {
                 &repo_root_str,
                 &eff.index,
                 eff_write,
-                eff_diff || eff_check,
+                eff_diff || eff_check || stdout,
                 eff.strict_linebreak,
+                eff.max_width,
+                eff.normalize_strings,
                 eff.lb_between_groups,
                 &eff.lb_before_fields,
                 &eff.lb_in_fields,
-                &eff.pattern_overrides,
+                &pattern_overrides,
             );
+            if stdout {
+                for e in errors.iter() {
+                    crate::diag::error(&e.message);
+                }
+                // Unchanged (or unparseable) files print as they are
+                match results.first() {
+                    Some(r) => match (&r.preview, &r.original) {
+                        (Some(p), _) => output::print_line(p),
+                        (None, Some(o)) => output::print_raw(o),
+                        (None, None) => exit(2),
+                    },
+                    None => {
+                        crate::diag::error(format!(
+                            "File not found: {}",
+                            path.as_deref().unwrap_or_default()
+                        ));
+                        exit(2);
+                    }
+                }
+                if !errors.is_empty() {
+                    exit(2);
+                }
+                return;
+            }
+            diag::debug(|| {
+                format!(
+                    "format: {} file(s), {} changed in {} ms",
+                    results.len(),
+                    results.iter().filter(|r| r.changed).count(),
+                    output::elapsed_ms().unwrap_or(0)
+                )
+            });
             output::print_format(&results, &eff.output, eff_write, eff_diff, &errors);
             if eff_check && results.iter().any(|r| r.changed) {
-                std::process::exit(1);
+                exit(1);
             }
         }
         Commands::Sync {
@@ -212,6 +338,9 @@ fn main() {
             write,
             dry_run,
             check,
+            only_scope_check,
+            force,
+            prune,
         } => {
             let eff = config::resolve_effective(
                 repo_root.as_deref(),
@@ -224,31 +353,37 @@ fn main() {
             );
             // Require index to be configured and point to a file
             if !eff.index_configured {
-                eprintln!(
-                    "{} {}",
-                    crate::utils::error_prefix(),
-                    "Index is not configured. Pass --index or add rigra.toml."
-                );
-                std::process::exit(2);
+                crate::diag::error("Index is not configured. Pass --index or add rigra.toml.");
+                exit(2);
             }
             if config::load_config(&eff.repo_root).is_none() {
-                eprintln!(
-                    "{} {}",
-                    crate::utils::note_prefix(),
-                    "No rigra.toml found; using defaults."
-                );
+                crate::diag::note("No rigra.toml found; using defaults.");
             }
             let idx_path = eff.repo_root.join(&eff.index);
             if !idx_path.exists() || !idx_path.is_file() {
-                eprintln!(
-                    "{} {}",
-                    crate::utils::error_prefix(),
-                    format!(
-                        "Index file not found: {} (pass --index or configure rigra.toml)",
-                        idx_path.to_string_lossy()
-                    )
-                );
-                std::process::exit(2);
+                crate::diag::error(format!(
+                    "Index file not found: {} (pass --index or configure rigra.toml)",
+                    idx_path.to_string_lossy()
+                ));
+                exit(2);
+            }
+            if only_scope_check {
+                let repo_root_str = eff.repo_root.to_string_lossy().to_string();
+                let (warnings, errors) = sync::check_sync_scopes(&repo_root_str, &eff.index);
+                for e in errors.iter() {
+                    crate::diag::error(&e.message);
+                }
+                for w in warnings.iter() {
+                    crate::diag::warn(w);
+                }
+                if !errors.is_empty() {
+                    exit(2);
+                }
+                if !warnings.is_empty() {
+                    exit(1);
+                }
+                crate::diag::info("All sync rule scopes are known.");
+                return;
             }
             let eff_diff = eff.diff;
             let eff_check = eff.check;
@@ -261,13 +396,66 @@ fn main() {
                 // CLI --write takes precedence; otherwise use [sync].write
                 write || cfg_sync_write
             };
+            let eff_prune = prune || cfg_sync.as_ref().and_then(|s| s.prune).unwrap_or(false);
             let repo_root_str = eff.repo_root.to_string_lossy().to_string();
-            let (actions, errors) =
-                sync::run_sync(&repo_root_str, &eff.index, &eff.scope, eff_write);
+            let (actions, errors) = sync::run_sync(
+                &repo_root_str,
+                &eff.index,
+                &eff.scope,
+                eff_write,
+                eff_diff,
+                force,
+                eff_prune,
+            );
             output::print_sync(&actions, &eff.output, &errors);
-            // In check mode, exit non-zero when any action would write
-            if eff_check && actions.iter().any(|a| a.would_write) {
-                std::process::exit(1);
+            // In check mode, exit non-zero when any action would write;
+            // conflicts and failed hooks always fail
+            if (eff_check && actions.iter().any(|a| a.would_write))
+                || actions.iter().any(|a| a.conflict || a.hook_failed)
+            {
+                exit(1);
+            }
+        }
+        Commands::TestPolicy {
+            policy,
+            input,
+            output,
+        } => {
+            let output = output.unwrap_or_else(|| "human".to_string());
+            // Input: '-' reads stdin, an existing path reads the file, otherwise a JSON literal
+            let (label, text) = if input == "-" {
+                let mut buf = String::new();
+                if let Err(e) = std::io::Read::read_to_string(&mut std::io::stdin(), &mut buf) {
+                    crate::diag::error(format!("Failed to read stdin: {}", e));
+                    exit(2);
+                }
+                ("<stdin>".to_string(), buf)
+            } else if std::path::Path::new(&input).is_file() {
+                match fs::read_to_string(&input) {
+                    Ok(s) => (input.clone(), s),
+                    Err(e) => {
+                        crate::diag::error(format!("Failed to read input '{}': {}", input, e));
+                        exit(2);
+                    }
+                }
+            } else {
+                ("<input>".to_string(), input.clone())
+            };
+            let (result, errors) =
+                lint::run_policy_test(std::path::Path::new(&policy), &text, &label);
+            if !errors.is_empty() {
+                if output != "json" {
+                    for e in errors.iter() {
+                        crate::diag::error(&e.message);
+                    }
+                } else {
+                    output::print_lint(&result, "test-policy", &output, &errors, false);
+                }
+                exit(2);
+            }
+            output::print_lint(&result, "test-policy", &output, &errors, false);
+            if result.summary.errors > 0 {
+                exit(1);
             }
         }
         Commands::Conv { cmd } => {
@@ -276,6 +464,7 @@ fn main() {
                     repo_root,
                     source,
                     name,
+                    sha256,
                 } => {
                     let eff = config::resolve_effective(
                         repo_root.as_deref(),
@@ -286,6 +475,33 @@ fn main() {
                         None,
                         None,
                     );
+                    // Bare `conv install` reinstalls exactly what conv.lock pins
+                    if source.is_none() && name.is_none() && sha256.is_none() {
+                        match conv::read_lock(&eff.repo_root) {
+                            Ok(Some(lock)) => {
+                                match conv::install_locked(&eff.repo_root, &lock) {
+                                    Ok(paths) => {
+                                        for p in paths {
+                                            output::print_line(format!(
+                                                "installed: {}",
+                                                p.to_string_lossy()
+                                            ));
+                                        }
+                                    }
+                                    Err(e) => {
+                                        crate::diag::error(format!("install failed: {}", e));
+                                        exit(2);
+                                    }
+                                }
+                                return;
+                            }
+                            Ok(None) => {}
+                            Err(e) => {
+                                crate::diag::error(e);
+                                exit(2);
+                            }
+                        }
+                    }
                     // Prefer CLI overrides; otherwise pull from rigra.toml [conv]
                     let cfg = config::load_config(&eff.repo_root).unwrap_or_default();
                     let cfg_conv = cfg.conv.as_ref();
@@ -297,8 +513,8 @@ fn main() {
                         if pkg.rsplit_once('@').is_some() {
                             pkg
                         } else {
-                            eprintln!("[conv.package] must include @version");
-                            std::process::exit(2);
+                            crate::diag::emit("[conv.package] must include @version");
+                            exit(2);
                         }
                     } else if let Some(src) = source.as_ref().and_then(|s| conv::parse_source(s)) {
                         match src {
@@ -308,21 +524,13 @@ fn main() {
                                 tag,
                             } => format!("{}@{}", repo, tag),
                             _ => {
-                                eprintln!(
-                                    "{} {}",
-                                    crate::utils::error_prefix(),
-                                    "--name is required when using file: source without [conv.package]"
-                                );
-                                std::process::exit(2);
+                                crate::diag::error("--name is required when using file:/git:/https: sources without [conv.package]");
+                                exit(2);
                             }
                         }
                     } else {
-                        eprintln!(
-                            "{} {}",
-                            crate::utils::error_prefix(),
-                            "missing install context: set [conv.package] in rigra.toml or pass --name"
-                        );
-                        std::process::exit(2);
+                        crate::diag::error("missing install context: set [conv.package] in rigra.toml or pass --name");
+                        exit(2);
                     };
 
                     // Determine source string
@@ -331,12 +539,10 @@ fn main() {
                     } else if let Some(s) = cfg_conv.and_then(|c| c.source.clone()) {
                         s
                     } else {
-                        eprintln!(
-                            "{} {}",
-                            crate::utils::error_prefix(),
-                            "missing source: set [conv.source] in rigra.toml or pass --source"
+                        crate::diag::error(
+                            "missing source: set [conv.source] in rigra.toml or pass --source",
                         );
-                        std::process::exit(2);
+                        exit(2);
                     };
                     // If shorthand "github" is used, derive gh:owner/repo@tag from package
                     let src_str = if src_str == "github" {
@@ -353,19 +559,75 @@ fn main() {
                         src_str
                     };
 
-                    match conv::install(&eff.repo_root, &name_ver, &src_str) {
-                        Ok(path) => println!("installed: {}", path.to_string_lossy()),
+                    match conv::install_entry(
+                        &eff.repo_root,
+                        &name_ver,
+                        &src_str,
+                        sha256.as_deref(),
+                    ) {
+                        Ok((path, entry)) => {
+                            if let Some(entry) = entry {
+                                if let Err(e) = conv::record_lock(&eff.repo_root, entry) {
+                                    crate::diag::warn(format!("conv.lock not updated: {}", e));
+                                }
+                            }
+                            output::print_line(format!("installed: {}", path.to_string_lossy()))
+                        }
+                        Err(e) => {
+                            crate::diag::error(format!("install failed: {}", e));
+                            exit(2);
+                        }
+                    }
+                }
+                cli::ConvCmd::Update {
+                    repo_root,
+                    name,
+                    source,
+                } => {
+                    let eff = config::resolve_effective(
+                        repo_root.as_deref(),
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                    );
+                    let pinned = || -> Option<String> {
+                        let (n, v) = name.rsplit_once('@')?;
+                        let lock = conv::read_lock(&eff.repo_root).ok()??;
+                        lock.entries
+                            .into_iter()
+                            .find(|e| e.name == n && e.version == v)
+                            .map(|e| e.source)
+                    };
+                    let Some(src_str) = source.or_else(pinned) else {
+                        crate::diag::error(format!(
+                            "no source for {}: pass one or pin it in conv.lock",
+                            name
+                        ));
+                        exit(2);
+                    };
+                    match conv::update(&eff.repo_root, &name, &src_str) {
+                        Ok((path, entry)) => {
+                            if let Some(entry) = entry {
+                                if let Err(e) = conv::record_lock(&eff.repo_root, entry) {
+                                    crate::diag::warn(format!("conv.lock not updated: {}", e));
+                                }
+                            }
+                            output::print_line(format!("updated: {}", path.to_string_lossy()))
+                        }
                         Err(e) => {
-                            eprintln!(
-                                "{} {}",
-                                crate::utils::error_prefix(),
-                                format!("install failed: {}", e)
-                            );
-                            std::process::exit(2);
+                            crate::diag::error(format!("update failed: {}", e));
+                            exit(2);
                         }
                     }
                 }
-                cli::ConvCmd::Ls { repo_root } => {
+                cli::ConvCmd::Ls {
+                    repo_root,
+                    tree,
+                    depth,
+                } => {
                     let eff = config::resolve_effective(
                         repo_root.as_deref(),
                         None,
@@ -376,7 +638,12 @@ fn main() {
                         None,
                     );
                     for it in conv::list(&eff.repo_root) {
-                        println!("{}", it);
+                        if tree {
+                            let dir = conv::cache_root(&eff.repo_root).join(&it);
+                            output::print_conv_tree(&it, &conv::tree(&dir, depth));
+                        } else {
+                            output::print_line(&it);
+                        }
                     }
                 }
                 cli::ConvCmd::Prune { repo_root } => {
@@ -390,14 +657,50 @@ fn main() {
                         None,
                     );
                     if let Err(e) = conv::prune(&eff.repo_root) {
-                        eprintln!(
-                            "{} {}",
-                            crate::utils::error_prefix(),
-                            format!("prune failed: {}", e)
-                        );
-                        std::process::exit(2);
+                        crate::diag::error(format!("prune failed: {}", e));
+                        exit(2);
                     } else {
-                        println!("pruned");
+                        output::print_line("pruned");
+                    }
+                }
+                cli::ConvCmd::Pack { dir, out } => {
+                    match conv::pack(std::path::Path::new(&dir), std::path::Path::new(&out)) {
+                        Ok(sha) => {
+                            output::print_line(format!("packed: {}", out));
+                            output::print_line(format!("sha256: {}", sha));
+                        }
+                        Err(e) => {
+                            crate::diag::error(format!("pack failed: {}", e));
+                            exit(2);
+                        }
+                    }
+                }
+                cli::ConvCmd::Info {
+                    repo_root,
+                    output,
+                    conv: conv_str,
+                } => {
+                    let eff = config::resolve_effective(
+                        repo_root.as_deref(),
+                        None,
+                        None,
+                        output.as_deref(),
+                        None,
+                        None,
+                        None,
+                    );
+                    let Some(cr) = conv::parse_conv_ref(&conv_str) else {
+                        crate::diag::error("invalid conv string");
+                        exit(2);
+                    };
+                    match conv::resolve_version(&eff.repo_root, &cr)
+                        .and_then(|cr| conv::info(&eff.repo_root, &cr))
+                    {
+                        Ok(info) => output::print_conv_info(&info, &eff.output),
+                        Err(e) => {
+                            crate::diag::error(format!("info failed: {}", e));
+                            exit(2);
+                        }
                     }
                 }
                 cli::ConvCmd::Path {
@@ -414,14 +717,37 @@ fn main() {
                         None,
                     );
                     if let Some(cr) = conv::parse_conv_ref(&conv_str) {
+                        let cr = match conv::resolve_version(&eff.repo_root, &cr) {
+                            Ok(cr) => cr,
+                            Err(e) => {
+                                crate::diag::error(e);
+                                exit(2);
+                            }
+                        };
                         let p = conv::resolve_path(&eff.repo_root, &cr);
-                        println!("{}", p.to_string_lossy());
+                        output::print_line(p.to_string_lossy());
                     } else {
-                        eprintln!("{} {}", crate::utils::error_prefix(), "invalid conv string");
-                        std::process::exit(2);
+                        crate::diag::error("invalid conv string");
+                        exit(2);
                     }
                 }
             }
         }
     }
 }
+
+/// Writes the `--profile-output` trace when `main` returns.
+struct ProfileGuard;
+
+impl Drop for ProfileGuard {
+    fn drop(&mut self) {
+        profile::finish();
+    }
+}
+
+/// Exit with `code` after writing the `--profile-output` trace, since
+/// `std::process::exit` skips destructors.
+fn exit(code: i32) -> ! {
+    profile::finish();
+    std::process::exit(code)
+}
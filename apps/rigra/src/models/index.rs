@@ -1,6 +1,8 @@
 //! Index schema: lists rules for lint/format targets and sync operations.
 
+use super::policy::Policy;
 use serde::Deserialize;
+use std::collections::HashMap;
 
 #[derive(Deserialize)]
 /// Top-level index configuration.
@@ -10,6 +12,10 @@ pub struct Index {
     /// External sync policy file path relative to this index
     #[serde(default, rename = "sync")]
     pub sync_ref: Option<String>,
+    /// Inline policies keyed by name (index bundles); a rule whose `policy`
+    /// names one uses it instead of reading a file
+    #[serde(default)]
+    pub policies: HashMap<String, Policy>,
 }
 
 #[derive(Deserialize)]
@@ -18,6 +24,17 @@ pub struct RuleIndex {
     pub id: String,
     pub patterns: Vec<String>,
     pub policy: String,
+    /// Treat each target as a stream of documents (NDJSON or multi-doc YAML)
+    #[serde(default)]
+    pub multi_doc: bool,
+    /// Issue budget: when the rule yields at most this many issues they are
+    /// demoted to info and the rule passes; beyond it they keep their levels
+    #[serde(default)]
+    pub max_issues: Option<usize>,
+    /// Staged rollout: checks without an explicit `level` report `warn`
+    /// until this date (`YYYY-MM-DD`, UTC) and `error` once it has passed
+    #[serde(default)]
+    pub escalate_after: Option<String>,
 }
 
 // Sync rules are now defined in external policy files
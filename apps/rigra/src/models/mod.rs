@@ -4,16 +4,23 @@ pub mod index;
 pub mod policy;
 pub mod sync_policy;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, Clone)]
 /// A single lint issue with severity and location.
 pub struct Issue {
     pub file: String,
     pub rule: String,
     pub severity: String,
+    /// Whether rigra can resolve the issue automatically (format/sync/const)
+    pub fixable: bool,
     pub path: String,
     pub message: String,
+    /// Stable code for grouping (a check's `code`, else its kind; `order` for order lint)
+    #[serde(default)]
+    pub code: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -25,9 +32,89 @@ pub struct Summary {
     pub files: usize,
 }
 
+#[derive(Serialize, Clone)]
+/// A problem with the convention itself rather than a lint target: an
+/// unreadable index or policy, a bad `regex_from`, or an invalid rule option.
+pub struct Diagnostic {
+    /// `index-missing`|`index-invalid`|`policy-missing`|`policy-invalid`|`regex-file`|`rule-config`
+    pub kind: String,
+    pub file: String,
+    pub rule: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(kind: &str, file: &Path, rule: &str, message: impl Into<String>) -> Self {
+        Diagnostic {
+            kind: kind.into(),
+            file: file.to_string_lossy().to_string(),
+            rule: rule.into(),
+            message: message.into(),
+        }
+    }
+}
+
 #[derive(Serialize)]
 /// Lint results container.
 pub struct LintResult {
     pub issues: Vec<Issue>,
+    /// Convention (index/policy) problems, kept apart from target `issues`
+    pub diagnostics: Vec<Diagnostic>,
     pub summary: Summary,
+    /// True when no issue has error severity and there are no diagnostics
+    /// (the lint exit-code threshold)
+    pub passed: bool,
+    /// `"pass"`/`"fail"` per evaluated rule id; a rule fails on any error issue
+    pub rules: BTreeMap<String, String>,
+}
+
+impl LintResult {
+    /// Build a result, deriving `passed` and the per-rule map from `issues`.
+    ///
+    /// `rule_ids` lists every rule that ran so clean rules report `"pass"`;
+    /// rules that only appear on issues (e.g. `sync:<id>`) are added too.
+    pub fn new(
+        issues: Vec<Issue>,
+        summary: Summary,
+        rule_ids: impl IntoIterator<Item = String>,
+    ) -> Self {
+        let mut rules: BTreeMap<String, String> = rule_ids
+            .into_iter()
+            .map(|id| (id, "pass".to_string()))
+            .collect();
+        for is in &issues {
+            let status = rules
+                .entry(is.rule.clone())
+                .or_insert_with(|| "pass".into());
+            if is.severity == "error" {
+                *status = "fail".into();
+            }
+        }
+        LintResult {
+            passed: summary.errors == 0,
+            issues,
+            diagnostics: Vec::new(),
+            summary,
+            rules,
+        }
+    }
+
+    /// Attach convention diagnostics; any diagnostic fails the run and the
+    /// rule it belongs to.
+    pub fn with_diagnostics(mut self, diagnostics: Vec<Diagnostic>) -> Self {
+        for d in &diagnostics {
+            if let Some(status) = self.rules.get_mut(&d.rule) {
+                *status = "fail".into();
+            }
+        }
+        self.passed &= diagnostics.is_empty();
+        self.diagnostics = diagnostics;
+        self
+    }
+}
+
+#[derive(Serialize)]
+/// Non-issue runtime error surfaced alongside command results.
+pub struct RunError {
+    pub message: String,
 }
@@ -2,7 +2,10 @@
 //!
 //! Key components:
 //! - `order`: Declares top-level key groups and optional sub-orders, plus
-//!   lint `message` and `level` (info|warn|error).
+//!   lint `message` and `level` (info|warn|error). `pin_first`/`pin_last`
+//!   force keys such as `$schema` to the ends of the object.
+//! - Check `level` also accepts `off` (or `ignore`) to disable a check
+//!   without removing it from the policy.
 //! - `linebreak`: Controls line breaks between top-level groups and inside
 //!   specific object fields via `before_fields` and `in_fields` maps.
 //! - `checks`: Validation rules (required/type/const/pattern/enum/length...).
@@ -11,9 +14,9 @@
 
 use serde::Deserialize;
 use serde_json::Value as Json;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 /// Root policy loaded from TOML files referenced by the index.
 pub struct Policy {
     #[serde(default)]
@@ -22,6 +25,9 @@ pub struct Policy {
     pub order: Option<OrderSpec>,
     #[serde(default)]
     pub linebreak: Option<LineBreakSpec>,
+    /// Trailing-comma style for `.jsonc`/`.json5` targets (plain JSON never gets them)
+    #[serde(default)]
+    pub trailing_comma: Option<TrailingComma>,
 }
 
 #[derive(Deserialize, Clone)]
@@ -31,6 +37,16 @@ pub struct OrderSpec {
     pub top: Vec<Vec<String>>,
     #[serde(default)]
     pub sub: HashMap<String, Vec<String>>,
+    /// Regexes for keys (e.g. `^x-`) that keep their original relative
+    /// positions instead of being sorted; explicitly listed keys still move
+    #[serde(default)]
+    pub preserve_matching: Vec<String>,
+    /// Keys forced to the front of the object (in this order), e.g. `$schema`
+    #[serde(default)]
+    pub pin_first: Vec<String>,
+    /// Keys forced to the back of the object (in this order)
+    #[serde(default)]
+    pub pin_last: Vec<String>,
     #[serde(default)]
     pub message: Option<String>,
     #[serde(default)]
@@ -48,6 +64,18 @@ pub struct LineBreakSpec {
     pub in_fields: HashMap<String, LineBreakRule>,
 }
 
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+/// Trailing-comma style for `.jsonc`/`.json5` targets.
+pub enum TrailingComma {
+    /// Never emit trailing commas.
+    None,
+    /// After the last entry of every non-empty object/array.
+    All,
+    /// Only where the closing bracket sits on its own line.
+    Multiline,
+}
+
 #[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 /// Rule applied to line-break handling.
@@ -59,61 +87,577 @@ pub enum LineBreakRule {
 #[derive(Deserialize, Clone)]
 #[serde(tag = "kind")]
 /// Lint checks supported by the engine.
+///
+/// Every kind also takes `message`, `level`, and `code`: a stable issue code
+/// for machine consumers that defaults to the kind name.
 pub enum Check {
     #[serde(rename = "required")]
     Required {
+        fields: Vec<String>,
+        message: Option<String>,
+        /// Optional per-field messages keyed by field path; falls back to `message`
+        #[serde(default)]
+        messages: Option<HashMap<String, String>>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "forbidden")]
+    /// Inverse of `required`: each listed path must be absent
+    Forbidden {
+        fields: Vec<String>,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "nonEmpty")]
+    /// Listed strings, when present, must not be blank after trimming
+    NonEmpty {
         fields: Vec<String>,
         message: Option<String>,
         #[serde(default)]
         level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
     },
     #[serde(rename = "type")]
     Type {
         #[serde(default)]
-        /// Map of JSON paths to expected kinds (string|number|integer|boolean|array|object|null)
+        /// Map of JSON paths to expected kinds (string|number|integer|boolean|array|object|null,
+        /// or positive-integer|nonnegative-integer|positive-number|nonnegative-number);
+        /// a `|`-separated union such as `string|object` accepts any listed kind
         fields: HashMap<String, String>,
         message: Option<String>,
         #[serde(default)]
         level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
     },
     #[serde(rename = "const")]
     Const {
         field: String,
         value: Json,
+        /// Compare strings with ASCII case folding; other values stay exact
+        #[serde(default)]
+        ignore_case: Option<bool>,
         message: Option<String>,
         #[serde(default)]
         level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
     },
     #[serde(rename = "pattern")]
     Pattern {
         field: String,
+        /// Exactly one of `regex` and `regex_from` must be set
+        #[serde(default)]
         regex: String,
+        /// Load the regex from a file, resolved relative to the policy file
+        #[serde(default)]
+        regex_from: Option<String>,
+        /// Inline flags: `i` (case-insensitive), `m` (multi-line anchors),
+        /// `s` (`.` matches newline), `x` (verbose); any other character
+        /// disables all flags
+        #[serde(default)]
+        flags: Option<String>,
         message: Option<String>,
         #[serde(default)]
         level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
     },
     #[serde(rename = "enum")]
     Enum {
         field: String,
         values: Vec<Json>,
+        /// Values that still pass but raise a `warn` issue steering to `prefer`
+        #[serde(default)]
+        deprecated: Vec<Json>,
+        /// Replacement per deprecated value, keyed by the string value (or its JSON text)
+        #[serde(default)]
+        prefer: HashMap<String, Json>,
+        /// Compare strings with ASCII case folding; other values stay exact
+        #[serde(default)]
+        ignore_case: Option<bool>,
         message: Option<String>,
         #[serde(default)]
         level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
     },
     #[serde(rename = "minLength")]
+    /// Length is Unicode scalar values for strings, items for arrays, and keys for objects
     MinLength {
         field: String,
         min: usize,
         message: Option<String>,
         #[serde(default)]
         level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
     },
     #[serde(rename = "maxLength")]
+    /// Length is Unicode scalar values for strings, items for arrays, and keys for objects
     MaxLength {
         field: String,
         max: usize,
         message: Option<String>,
         #[serde(default)]
         level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "minItems")]
+    /// Array must have at least `min` elements
+    MinItems {
+        field: String,
+        min: usize,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "maxItems")]
+    /// Array must have at most `max` elements
+    MaxItems {
+        field: String,
+        max: usize,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "objectValues")]
+    /// Every value of the object at `field` must be of `value_kind` (same
+    /// kinds and `|` unions as `type`); keys are not constrained
+    ObjectValues {
+        field: String,
+        value_kind: String,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "uniqueItems")]
+    /// Array must not contain two equal elements (deep equality)
+    UniqueItems {
+        field: String,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "format")]
+    /// String must match a well-known format: `email`, `uri`, `date`,
+    /// `date-time` (RFC 3339), `uuid`, or `semver`. Unknown names are ignored
+    Format {
+        field: String,
+        format: String,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "url")]
+    /// String must parse as a URL whose scheme is in `schemes` (default
+    /// `["https"]`) and, when `hosts` is non-empty, whose host is listed.
+    /// Syntax only; nothing is fetched.
+    Url {
+        field: String,
+        #[serde(default)]
+        schemes: Vec<String>,
+        #[serde(default)]
+        hosts: Vec<String>,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "minimum")]
+    /// Numeric value must be >= `min` (> with `exclusive`)
+    Minimum {
+        field: String,
+        min: f64,
+        #[serde(default)]
+        exclusive: Option<bool>,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
     },
+    #[serde(rename = "maximum")]
+    /// Numeric value must be <= `max` (< with `exclusive`)
+    Maximum {
+        field: String,
+        max: f64,
+        #[serde(default)]
+        exclusive: Option<bool>,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "multipleOf")]
+    /// Numeric value must be a multiple of `divisor` (a zero divisor never fails)
+    MultipleOf {
+        field: String,
+        divisor: f64,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "noAdditionalProperties", alias = "allowedProperties")]
+    /// Keys of the object at `field` must be in `allowed` or match a pattern
+    NoAdditionalProperties {
+        /// Path of the object to inspect (empty for the document root)
+        #[serde(default)]
+        field: String,
+        /// Keys permitted in the object
+        #[serde(default)]
+        allowed: Vec<String>,
+        /// Regexes for additionally permitted keys
+        #[serde(default, alias = "patternProperties")]
+        pattern_properties: Vec<String>,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "dependency")]
+    /// When `if_field` is present (and equals `if_equals`, if given), every
+    /// path in `require` must be present too
+    Dependency {
+        if_field: String,
+        #[serde(default)]
+        if_equals: Option<Json>,
+        require: Vec<String>,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "consistentField")]
+    /// Cross-file: the value at `field` must be the same in every file the
+    /// rule matches; files disagreeing with the most common value are flagged.
+    /// Not evaluated for `multi_doc` rules
+    ConsistentField {
+        field: String,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "propertyNames")]
+    /// Every key of the object at `field` must match `regex`
+    PropertyNames {
+        /// Path of the object to inspect (empty for the document root)
+        #[serde(default)]
+        field: String,
+        regex: String,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "each")]
+    /// Run the nested `checks` against every element of the array at
+    /// `field`; issue paths gain the element index (`$.a[3].b`). `message`
+    /// and `level` apply when the value is present but not an array
+    Each {
+        field: String,
+        checks: Vec<Check>,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "anyOf")]
+    /// Passes when at least one nested check reports nothing; otherwise one
+    /// combined issue lists what each alternative reported. Disabled
+    /// (`level = "off"`) alternatives are left out
+    AnyOf {
+        checks: Vec<Check>,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "allOf")]
+    /// Runs every nested check and reports their issues as-is; with
+    /// `message`, any failures collapse into one issue carrying it
+    AllOf {
+        checks: Vec<Check>,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "not")]
+    /// Reports only when the nested check reports nothing; skipped when the
+    /// nested check is disabled or its field is absent
+    Not {
+        check: Box<Check>,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "discriminated")]
+    /// Tagged union: the value at `discriminator` (a string, or the JSON text
+    /// of other values) picks the `variants` entry whose checks must hold.
+    /// A missing or unlisted value is reported as an unknown variant
+    Discriminated {
+        discriminator: String,
+        variants: BTreeMap<String, Vec<Check>>,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "semverGt")]
+    SemverGt {
+        /// Path of the version that must be strictly greater
+        field: String,
+        /// Path of the version to compare against (e.g. previous release)
+        than_field: String,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "sameAs")]
+    /// Value at `field` must deep-equal the value at `reference`
+    SameAs {
+        field: String,
+        reference: String,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(rename = "pathExists")]
+    /// String (or array/object of strings) at `field` must name an existing
+    /// file or directory
+    PathExists {
+        field: String,
+        /// Directory paths resolve from, relative to the target file's
+        /// directory (defaults to that directory)
+        #[serde(default)]
+        base: Option<String>,
+        message: Option<String>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+}
+
+impl Check {
+    /// Whether `level` is `off`/`ignore`, i.e. the check never reports.
+    pub fn is_off(&self) -> bool {
+        let level = match self {
+            Check::Required { level, .. }
+            | Check::Forbidden { level, .. }
+            | Check::NonEmpty { level, .. }
+            | Check::Type { level, .. }
+            | Check::Const { level, .. }
+            | Check::Pattern { level, .. }
+            | Check::Enum { level, .. }
+            | Check::MinLength { level, .. }
+            | Check::MaxLength { level, .. }
+            | Check::MinItems { level, .. }
+            | Check::MaxItems { level, .. }
+            | Check::UniqueItems { level, .. }
+            | Check::ObjectValues { level, .. }
+            | Check::Format { level, .. }
+            | Check::Url { level, .. }
+            | Check::Minimum { level, .. }
+            | Check::Maximum { level, .. }
+            | Check::MultipleOf { level, .. }
+            | Check::NoAdditionalProperties { level, .. }
+            | Check::Dependency { level, .. }
+            | Check::ConsistentField { level, .. }
+            | Check::PropertyNames { level, .. }
+            | Check::Each { level, .. }
+            | Check::AnyOf { level, .. }
+            | Check::AllOf { level, .. }
+            | Check::Not { level, .. }
+            | Check::Discriminated { level, .. }
+            | Check::SemverGt { level, .. }
+            | Check::SameAs { level, .. }
+            | Check::PathExists { level, .. } => level,
+        };
+        matches!(level.as_deref(), Some("off" | "ignore"))
+    }
+
+    /// Give this check and every check nested under it `level` where none
+    /// is set explicitly.
+    pub fn default_level(&mut self, sev: &str) {
+        let level = match self {
+            Check::Required { level, .. }
+            | Check::Forbidden { level, .. }
+            | Check::NonEmpty { level, .. }
+            | Check::Type { level, .. }
+            | Check::Const { level, .. }
+            | Check::Pattern { level, .. }
+            | Check::Enum { level, .. }
+            | Check::MinLength { level, .. }
+            | Check::MaxLength { level, .. }
+            | Check::MinItems { level, .. }
+            | Check::MaxItems { level, .. }
+            | Check::UniqueItems { level, .. }
+            | Check::ObjectValues { level, .. }
+            | Check::Format { level, .. }
+            | Check::Url { level, .. }
+            | Check::Minimum { level, .. }
+            | Check::Maximum { level, .. }
+            | Check::MultipleOf { level, .. }
+            | Check::NoAdditionalProperties { level, .. }
+            | Check::Dependency { level, .. }
+            | Check::ConsistentField { level, .. }
+            | Check::PropertyNames { level, .. }
+            | Check::Each { level, .. }
+            | Check::AnyOf { level, .. }
+            | Check::AllOf { level, .. }
+            | Check::Not { level, .. }
+            | Check::Discriminated { level, .. }
+            | Check::SemverGt { level, .. }
+            | Check::SameAs { level, .. }
+            | Check::PathExists { level, .. } => level,
+        };
+        if level.is_none() {
+            *level = Some(sev.to_string());
+        }
+        match self {
+            Check::Each { checks, .. }
+            | Check::AnyOf { checks, .. }
+            | Check::AllOf { checks, .. } => {
+                checks.iter_mut().for_each(|c| c.default_level(sev));
+            }
+            Check::Not { check, .. } => check.default_level(sev),
+            Check::Discriminated { variants, .. } => {
+                variants
+                    .values_mut()
+                    .flatten()
+                    .for_each(|c| c.default_level(sev));
+            }
+            _ => {}
+        }
+    }
+
+    /// Issue code for this check: the configured `code`, else the kind name.
+    pub fn code(&self) -> &str {
+        let code = match self {
+            Check::Required { code, .. }
+            | Check::Forbidden { code, .. }
+            | Check::NonEmpty { code, .. }
+            | Check::Type { code, .. }
+            | Check::Const { code, .. }
+            | Check::Pattern { code, .. }
+            | Check::Enum { code, .. }
+            | Check::MinLength { code, .. }
+            | Check::MaxLength { code, .. }
+            | Check::MinItems { code, .. }
+            | Check::MaxItems { code, .. }
+            | Check::UniqueItems { code, .. }
+            | Check::ObjectValues { code, .. }
+            | Check::Format { code, .. }
+            | Check::Url { code, .. }
+            | Check::Minimum { code, .. }
+            | Check::Maximum { code, .. }
+            | Check::MultipleOf { code, .. }
+            | Check::NoAdditionalProperties { code, .. }
+            | Check::Dependency { code, .. }
+            | Check::ConsistentField { code, .. }
+            | Check::PropertyNames { code, .. }
+            | Check::Each { code, .. }
+            | Check::AnyOf { code, .. }
+            | Check::AllOf { code, .. }
+            | Check::Not { code, .. }
+            | Check::Discriminated { code, .. }
+            | Check::SemverGt { code, .. }
+            | Check::SameAs { code, .. }
+            | Check::PathExists { code, .. } => code,
+        };
+        code.as_deref().unwrap_or_else(|| self.kind())
+    }
+
+    /// This check followed by every check nested under it (`each`, `anyOf`,
+    /// `allOf`, `not`, `discriminated`), depth first.
+    pub fn walk(&self) -> Vec<&Check> {
+        let mut out = vec![self];
+        match self {
+            Check::Each { checks, .. }
+            | Check::AnyOf { checks, .. }
+            | Check::AllOf { checks, .. } => {
+                out.extend(checks.iter().flat_map(Check::walk));
+            }
+            Check::Not { check, .. } => out.extend(check.walk()),
+            Check::Discriminated { variants, .. } => {
+                out.extend(variants.values().flatten().flat_map(Check::walk));
+            }
+            _ => {}
+        }
+        out
+    }
+
+    /// The `kind` name this check is declared with in policy TOML.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Check::Required { .. } => "required",
+            Check::Forbidden { .. } => "forbidden",
+            Check::NonEmpty { .. } => "nonEmpty",
+            Check::Type { .. } => "type",
+            Check::Const { .. } => "const",
+            Check::Pattern { .. } => "pattern",
+            Check::Enum { .. } => "enum",
+            Check::MinLength { .. } => "minLength",
+            Check::MaxLength { .. } => "maxLength",
+            Check::MinItems { .. } => "minItems",
+            Check::MaxItems { .. } => "maxItems",
+            Check::UniqueItems { .. } => "uniqueItems",
+            Check::ObjectValues { .. } => "objectValues",
+            Check::Format { .. } => "format",
+            Check::Url { .. } => "url",
+            Check::Minimum { .. } => "minimum",
+            Check::Maximum { .. } => "maximum",
+            Check::MultipleOf { .. } => "multipleOf",
+            Check::NoAdditionalProperties { .. } => "noAdditionalProperties",
+            Check::Dependency { .. } => "dependency",
+            Check::ConsistentField { .. } => "consistentField",
+            Check::PropertyNames { .. } => "propertyNames",
+            Check::Each { .. } => "each",
+            Check::AnyOf { .. } => "anyOf",
+            Check::AllOf { .. } => "allOf",
+            Check::Not { .. } => "not",
+            Check::Discriminated { .. } => "discriminated",
+            Check::SemverGt { .. } => "semverGt",
+            Check::SameAs { .. } => "sameAs",
+            Check::PathExists { .. } => "pathExists",
+        }
+    }
 }
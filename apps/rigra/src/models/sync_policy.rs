@@ -8,6 +8,10 @@ pub struct SyncPolicy {
     pub lint: Option<SyncLintDefaults>,
     #[serde(default)]
     pub sync: Vec<SyncRule>,
+    /// Optional set of valid scope names; when non-empty, `when` tokens
+    /// outside this set are reported as likely typos.
+    #[serde(default)]
+    pub scopes: Vec<String>,
 }
 
 #[derive(Deserialize, Default)]
@@ -30,4 +34,22 @@ pub struct SyncRule {
     pub level: Option<String>,
     #[serde(default)]
     pub message: Option<String>,
+    /// Replace targets that already exist (default true). With `false` the
+    /// rule only scaffolds missing files and skips existing ones.
+    #[serde(default)]
+    pub overwrite: Option<bool>,
+    /// Only apply when this path (relative to the repo root) exists
+    #[serde(default)]
+    pub if_exists: Option<String>,
+    /// Only apply when this path (relative to the repo root) does not exist
+    #[serde(default)]
+    pub unless_exists: Option<String>,
+    /// `copy` (default) or `symlink`: link the target to the source with a
+    /// relative symlink instead of duplicating bytes
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Also copy files a `.gitignore` inside a source directory excludes
+    /// (default false)
+    #[serde(default)]
+    pub include_ignored: Option<bool>,
 }
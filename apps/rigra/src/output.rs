@@ -1,13 +1,32 @@
 //! Output rendering for lint, format, and sync commands.
 //!
 //! Supports `human` (default) and `json` outputs. The JSON form includes
-//! per-item fields and a top-level summary.
+//! per-item fields and a top-level summary. Lint `issues` are emitted in the
+//! stable `(file, path, rule)` order produced by `run_lint`.
 
 use crate::models::{LintResult, RunError};
-use crate::{format::FormatResult, sync::SyncAction};
+use crate::{
+    format::FormatResult,
+    sync::{SyncAction, SyncDiff},
+};
 use owo_colors::OwoColorize;
 use serde_json::json;
 use serde_json::Value as JsonVal;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// Wall-clock start of the command, reported as `durationMs` in JSON output.
+static RUN_START: OnceLock<Instant> = OnceLock::new();
+
+/// Mark the start of the command; call once, first thing in `main`.
+pub fn start_timer() {
+    let _ = RUN_START.set(Instant::now());
+}
+
+/// Milliseconds since `start_timer`, if it was called.
+pub fn elapsed_ms() -> Option<u64> {
+    RUN_START.get().map(|s| s.elapsed().as_millis() as u64)
+}
 
 fn try_print_json(val: &serde_json::Value) {
     match serde_json::to_string_pretty(val) {
@@ -24,15 +43,170 @@ fn try_print_json(val: &serde_json::Value) {
     }
 }
 
+/// Stamp the JSON envelope with the producing tool and subcommand for traceability,
+/// plus `durationMs` (time since `start_timer`) when the timer was started.
+fn with_tool_meta(mut root: JsonVal, command: &str) -> JsonVal {
+    if let Some(obj) = root.as_object_mut() {
+        obj.insert(
+            "tool".to_string(),
+            json!({"name": "rigra", "version": env!("CARGO_PKG_VERSION")}),
+        );
+        obj.insert("command".to_string(), json!(command));
+        if let Some(ms) = elapsed_ms() {
+            obj.insert("durationMs".to_string(), json!(ms));
+        }
+    }
+    root
+}
+
+/// Trailing marker for issues that rigra can fix automatically.
+fn fixable_mark(fixable: bool, color: bool) -> String {
+    match (fixable, color) {
+        (false, _) => String::new(),
+        (true, true) => format!(" {}", "✎ fixable".green()),
+        (true, false) => " ✎ fixable".to_string(),
+    }
+}
+
 fn use_colors(output: &str) -> bool {
-    output != "json" && std::env::var_os("NO_COLOR").is_none()
+    use std::io::IsTerminal;
+    output != "json" && crate::utils::color_enabled(std::io::stdout().is_terminal())
+}
+
+/// Version plus build metadata embedded by `build.rs`, one entry per line.
+pub fn compose_build_info() -> Vec<String> {
+    let features = match env!("RIGRA_FEATURES") {
+        "" => "(none)",
+        f => f,
+    };
+    vec![
+        format!("rigra {}", env!("CARGO_PKG_VERSION")),
+        format!("commit:   {}", env!("RIGRA_GIT_COMMIT")),
+        format!("rustc:    {}", env!("RIGRA_RUSTC_VERSION")),
+        format!("target:   {}", env!("RIGRA_TARGET")),
+        format!("features: {}", features),
+    ]
+}
+
+/// Print a plain result line to stdout unless `--silent` is active.
+pub fn print_line(line: impl std::fmt::Display) {
+    if !crate::diag::is_silent() {
+        println!("{}", line);
+    }
+}
+
+/// Print text verbatim on stdout (no added newline), unless `--silent`.
+pub fn print_raw(text: &str) {
+    if !crate::diag::is_silent() {
+        print!("{}", text);
+    }
+}
+
+/// Print one installed convention and its file tree, highlighting the index
+/// and files it references.
+pub fn print_conv_tree(name: &str, lines: &[crate::conv::TreeLine]) {
+    if crate::diag::is_silent() {
+        return;
+    }
+    let color = use_colors("human");
+    if color {
+        println!("▣ {}", name.bold());
+    } else {
+        println!("{}", name);
+    }
+    for line in lines {
+        match line.role {
+            Some(role) if color => println!(
+                "{} {}",
+                line.text.green().bold(),
+                format!("⟦{}⟧", role).dimmed()
+            ),
+            Some(role) => println!("{} ⟦{}⟧", line.text, role),
+            None => println!("{}", line.text),
+        }
+    }
+}
+
+/// JSON shape for `rigra conv info`.
+pub fn compose_conv_info_json(info: &crate::conv::ConvInfo) -> JsonVal {
+    let rules: Vec<JsonVal> = info
+        .rules
+        .iter()
+        .map(|(id, patterns)| json!({"id": id, "patterns": patterns}))
+        .collect();
+    with_tool_meta(
+        json!({
+            "name": info.name,
+            "version": info.version,
+            "path": info.path.to_string_lossy(),
+            "source": info.source,
+            "sha256": info.sha256,
+            "installed": info.installed,
+            "sizeBytes": info.size,
+            "rules": rules,
+        }),
+        "conv info",
+    )
+}
+
+/// Print `rigra conv info` in the requested format.
+pub fn print_conv_info(info: &crate::conv::ConvInfo, output: &str) {
+    if crate::diag::is_silent() {
+        return;
+    }
+    if output == "json" {
+        try_print_json(&compose_conv_info_json(info));
+        return;
+    }
+    let color = use_colors(output);
+    let title = format!("{}@{}", info.name, info.version);
+    if color {
+        println!("▣ {}", title.bold());
+    } else {
+        println!("{}", title);
+    }
+    let unknown = "unknown (not pinned in conv.lock)";
+    println!("  path:      {}", info.path.to_string_lossy());
+    println!("  source:    {}", info.source.as_deref().unwrap_or(unknown));
+    if let Some(sha) = &info.sha256 {
+        println!("  sha256:    {}", sha);
+    }
+    println!(
+        "  installed: {}",
+        info.installed.as_deref().unwrap_or("unknown")
+    );
+    println!("  size:      {} bytes", info.size);
+    println!("  rules:     {}", info.rules.len());
+    for (id, patterns) in &info.rules {
+        let id = if color {
+            id.green().bold().to_string()
+        } else {
+            id.clone()
+        };
+        println!("    {} {}", id, patterns.join(", "));
+    }
 }
 
 /// Print lint results in the requested format.
-pub fn print_lint(res: &LintResult, output: &str, errors: &[RunError]) {
+///
+/// With `show_source`, human output adds the offending source line under
+/// each issue with a caret marking the key at its JSON path. Convention
+/// diagnostics are listed before the issues; JSON carries them in
+/// `diagnostics` (each with a `kind`), separate from `issues`. `command`
+/// names the subcommand in the JSON envelope (`lint` or `test-policy`).
+pub fn print_lint(
+    res: &LintResult,
+    command: &str,
+    output: &str,
+    errors: &[RunError],
+    show_source: bool,
+) {
+    if crate::diag::is_silent() {
+        return;
+    }
     match output {
         "json" => {
-            let mut root = compose_lint_json(res);
+            let mut root = compose_lint_json(res, command);
             let errs: Vec<_> = errors
                 .iter()
                 .map(|e| json!({"message": e.message}))
@@ -64,6 +238,20 @@ pub fn print_lint(res: &LintResult, output: &str, errors: &[RunError]) {
                 };
                 groups.entry(dir).or_default().push(is);
             }
+            // Convention problems first: they are not about any target file
+            for d in &res.diagnostics {
+                println!(
+                    "{} {} ⟦{}⟧ {} ❲{}❳ — {}",
+                    crate::utils::icon_error(color),
+                    crate::utils::tag_error(color),
+                    d.kind,
+                    d.file,
+                    d.rule,
+                    d.message
+                );
+            }
+            // Source text per file, read lazily for --show-source
+            let mut sources: BTreeMap<String, Option<String>> = BTreeMap::new();
             for (dir, items) in groups {
                 if color {
                     println!("▣ {}", dir.bold());
@@ -87,11 +275,29 @@ pub fn print_lint(res: &LintResult, output: &str, errors: &[RunError]) {
                         .map(|f| f.to_string_lossy().to_string())
                         .unwrap_or_else(|| is.file.clone());
                     let base = if color { base.bold().to_string() } else { base };
-                    println!("  {} {} {} ❲{}❳ — {}", icon, sev, base, is.rule, is.message);
+                    println!(
+                        "  {} {} {} ❲{}❳ — {}{}",
+                        icon,
+                        sev,
+                        base,
+                        is.rule,
+                        is.message,
+                        fixable_mark(is.fixable, color)
+                    );
+                    if show_source {
+                        let text = sources
+                            .entry(is.file.clone())
+                            .or_insert_with(|| std::fs::read_to_string(&is.file).ok());
+                        if let Some(text) = text.as_deref() {
+                            for l in source_snippet(&is.file, text, &is.path, color) {
+                                println!("{}", l);
+                            }
+                        }
+                    }
                 }
             }
             // Emit pass message when there are no errors or warnings
-            if res.summary.errors == 0 && res.summary.warnings == 0 {
+            if res.passed && res.summary.warnings == 0 {
                 if color {
                     println!(
                         "{} {}",
@@ -102,10 +308,13 @@ pub fn print_lint(res: &LintResult, output: &str, errors: &[RunError]) {
                     println!("✔ ⟦perfect⟧ Validation passed. No convention violations detected.");
                 }
             }
-            let summary = format!(
+            let mut summary = format!(
                 "— Summary — errors={} warnings={} infos={} files={}",
                 res.summary.errors, res.summary.warnings, res.summary.infos, res.summary.files
             );
+            if !res.diagnostics.is_empty() {
+                summary.push_str(&format!(" diagnostics={}", res.diagnostics.len()));
+            }
             if color {
                 println!("{}", summary.bold());
             } else {
@@ -115,6 +324,36 @@ pub fn print_lint(res: &LintResult, output: &str, errors: &[RunError]) {
     }
 }
 
+/// Render a rustc-style source excerpt for an issue: location, the line
+/// holding the key at `path`, and a caret underline. Empty when the path
+/// cannot be located in `text`.
+fn source_snippet(file: &str, text: &str, path: &str, color: bool) -> Vec<String> {
+    let Some((line, col, width)) = crate::utils::locate_json_path(text, path) else {
+        return Vec::new();
+    };
+    let Some(src) = text.lines().nth(line - 1) else {
+        return Vec::new();
+    };
+    let num = line.to_string();
+    let pad = " ".repeat(num.len());
+    let caret = format!("{}{}", " ".repeat(col - 1), "^".repeat(width));
+    let (bar, arrow, caret) = if color {
+        (
+            "|".blue().bold().to_string(),
+            "-->".blue().bold().to_string(),
+            caret.red().bold().to_string(),
+        )
+    } else {
+        ("|".to_string(), "-->".to_string(), caret)
+    };
+    vec![
+        format!("    {}{} {}:{}:{}", pad, arrow, file, line, col),
+        format!("    {} {}", pad, bar),
+        format!("    {} {} {}", num, bar, src),
+        format!("    {} {} {}", pad, bar, caret),
+    ]
+}
+
 /// Print formatting results. When `write` is false, previews and diffs
 /// can be emitted; otherwise only file statuses are shown.
 // removed duplicate import to avoid name redefinition warnings
@@ -126,6 +365,9 @@ pub fn print_format(
     diff: bool,
     errors: &[RunError],
 ) {
+    if crate::diag::is_silent() {
+        return;
+    }
     match output {
         "json" => {
             let out = compose_format_json(results, write, diff);
@@ -155,6 +397,9 @@ pub fn print_format(
                 } else {
                     println!("✔ ⟦stable⟧ Everything is tidy. No changes.");
                 }
+                if write {
+                    emit_write_summary(changed_count, results.len(), color);
+                }
                 return;
             }
             for r in results {
@@ -192,42 +437,113 @@ pub fn print_format(
                     }
                 }
             }
+            if write {
+                emit_write_summary(changed_count, results.len(), color);
+            }
+        }
+    }
+}
+
+/// Trailing `format --write` total, sent to stderr so stdout keeps only per-file lines.
+fn emit_write_summary(changed: usize, total: usize, color: bool) {
+    let line = format_write_summary(changed, total);
+    if color {
+        crate::diag::emit(&line.bold().to_string());
+    } else {
+        crate::diag::emit(&line);
+    }
+}
+
+fn format_write_summary(changed: usize, total: usize) -> String {
+    format!(
+        "— Summary — Formatted {} of {} file{}",
+        changed,
+        total,
+        if total == 1 { "" } else { "s" }
+    )
+}
+
+/// Compose the sync JSON document.
+///
+/// Each result reports `wrote`, `wouldWrite`, and a derived `skipped` (the
+/// target was already up to date, or kept by an `overwrite = false` rule, so
+/// nothing was or would be written), plus `merged` (JSON merge rather than
+/// copy) and the `files`/`bytes` written or pending. The summary totals them.
+/// `conflict` marks merged targets edited outside sync since the last run
+/// (left alone without `--force`); the summary counts them as `conflicts`.
+/// In dry-run mode, `diff` lists `{ target, hunks }` per pending file (same
+/// hunk shape as format); otherwise it is `null`.
+pub fn compose_sync_json(actions: &[SyncAction], errors: &[RunError]) -> JsonVal {
+    let items: Vec<_> = actions
+        .iter()
+        .map(|a| {
+            json!({
+                "rule": a.rule_id,
+                "source": a.source,
+                "target": a.target,
+                "format": a.format,
+                "wrote": a.wrote,
+                "wouldWrite": a.would_write,
+                "skipped": !a.wrote && !a.would_write,
+                "merged": a.merged,
+                "files": a.files,
+                "bytes": a.bytes,
+                "conflict": a.conflict,
+                "hookFailed": a.hook_failed,
+                "pruned": a.pruned,
+                "diff": compose_sync_diffs(&a.diffs),
+            })
+        })
+        .collect();
+    let summary = json!({
+        "wrote": actions.iter().filter(|a| a.wrote).count(),
+        "wouldWrite": actions.iter().filter(|a| a.would_write && !a.wrote).count(),
+        "skipped": actions.iter().filter(|a| !a.wrote && !a.would_write).count(),
+        "merged": actions.iter().filter(|a| a.merged).count(),
+        "files": actions.iter().map(|a| a.files).sum::<usize>(),
+        "bytes": actions.iter().map(|a| a.bytes).sum::<u64>(),
+        "conflicts": actions.iter().filter(|a| a.conflict).count(),
+        "hookFailures": actions.iter().filter(|a| a.hook_failed).count(),
+        "pruned": actions.iter().filter(|a| a.pruned).count(),
+        "total": actions.len(),
+    });
+    let errs: Vec<_> = errors
+        .iter()
+        .map(|e| json!({"message": e.message}))
+        .collect();
+    let mut out = with_tool_meta(json!({"results": items, "summary": summary}), "sync");
+    if !errs.is_empty() {
+        if let Some(obj) = out.as_object_mut() {
+            obj.insert("errors".to_string(), json!(errs));
         }
     }
+    out
+}
+
+fn compose_sync_diffs(diffs: &[SyncDiff]) -> Option<JsonVal> {
+    if diffs.is_empty() {
+        return None;
+    }
+    let items: Vec<_> = diffs
+        .iter()
+        .map(|d| {
+            json!({
+                "target": d.target,
+                "hunks": build_diff_hunks(Some(d.old.as_deref().unwrap_or("")), Some(&d.new)),
+            })
+        })
+        .collect();
+    Some(JsonVal::Array(items))
 }
 
 /// Print sync actions summarizing writes and skips.
 pub fn print_sync(actions: &[SyncAction], output: &str, errors: &[RunError]) {
+    if crate::diag::is_silent() {
+        return;
+    }
     match output {
         "json" => {
-            let items: Vec<_> = actions
-                .iter()
-                .map(|a| {
-                    json!({
-                        "rule": a.rule_id,
-                        "source": a.source,
-                        "target": a.target,
-                        "format": a.format,
-                        "wrote": a.wrote,
-                        "wouldWrite": a.would_write,
-                    })
-                })
-                .collect();
-            let summary = json!({
-                "wrote": actions.iter().filter(|a| a.wrote).count(),
-                "wouldWrite": actions.iter().filter(|a| a.would_write && !a.wrote).count(),
-                "total": actions.len(),
-            });
-            let errs: Vec<_> = errors
-                .iter()
-                .map(|e| json!({"message": e.message}))
-                .collect();
-            let mut out = json!({"results": items, "summary": summary});
-            if !errs.is_empty() {
-                if let Some(obj) = out.as_object_mut() {
-                    obj.insert("errors".to_string(), json!(errs));
-                }
-            }
+            let out = compose_sync_json(actions, errors);
             try_print_json(&out);
         }
         _ => {
@@ -235,7 +551,8 @@ pub fn print_sync(actions: &[SyncAction], output: &str, errors: &[RunError]) {
             // If nothing changed or pending, emit a concise info message
             let wrote_count = actions.iter().filter(|a| a.wrote).count();
             let pending_count = actions.iter().filter(|a| a.would_write).count();
-            if wrote_count == 0 && pending_count == 0 {
+            let hook_failures = actions.iter().filter(|a| a.hook_failed).count();
+            if wrote_count == 0 && pending_count == 0 && hook_failures == 0 {
                 if color {
                     println!(
                         "{} {}",
@@ -281,7 +598,18 @@ pub fn print_sync(actions: &[SyncAction], output: &str, errors: &[RunError]) {
                 }
             };
             for a in actions {
-                if a.wrote {
+                if a.pruned {
+                    let tag = if a.wrote {
+                        "✂ ⟦pruned⟧"
+                    } else {
+                        "✂ ⟦would prune⟧"
+                    };
+                    if color {
+                        println!("{} {} (rule={})", tag.yellow().bold(), a.target, a.rule_id);
+                    } else {
+                        println!("{} {} (rule={})", tag, a.target, a.rule_id);
+                    }
+                } else if a.wrote {
                     if color {
                         println!(
                             "{} {} -> {} (rule={})",
@@ -298,6 +626,38 @@ pub fn print_sync(actions: &[SyncAction], output: &str, errors: &[RunError]) {
                             a.rule_id
                         );
                     }
+                } else if a.conflict {
+                    let line = format!(
+                        "{} -> {} (rule={}) — edited outside sync; rerun with --force to overwrite",
+                        shorten(&a.source),
+                        a.target,
+                        a.rule_id
+                    );
+                    if color {
+                        println!("{} {}", "✖ ⟦conflict⟧".red().bold(), line);
+                    } else {
+                        println!("✖ ⟦conflict⟧ {}", line);
+                    }
+                } else if a.would_write && !a.diffs.is_empty() {
+                    if color {
+                        println!(
+                            "{} {} -> {} (rule={})",
+                            "↻ ⟦would sync⟧".cyan().bold(),
+                            shorten(&a.source),
+                            a.target,
+                            a.rule_id
+                        );
+                    } else {
+                        println!(
+                            "↻ ⟦would sync⟧ {} -> {} (rule={})",
+                            shorten(&a.source),
+                            a.target,
+                            a.rule_id
+                        );
+                    }
+                    for d in &a.diffs {
+                        print!("{}", build_unified_diff(d));
+                    }
                 } else if a.would_write {
                     if color {
                         println!(
@@ -317,10 +677,68 @@ pub fn print_sync(actions: &[SyncAction], output: &str, errors: &[RunError]) {
                     }
                 }
             }
+            let summary = format_sync_summary(actions);
+            if color {
+                println!("{}", summary.bold());
+            } else {
+                println!("{}", summary);
+            }
         }
     }
 }
 
+/// Trailing sync totals: files written (or pending), how many rules merged
+/// JSON vs copied, rules skipped as unchanged, and bytes, plus failed hooks
+/// and pruned files when there are any.
+fn format_sync_summary(actions: &[SyncAction]) -> String {
+    let changed = actions.iter().filter(|a| a.would_write && !a.pruned);
+    let files: usize = changed.clone().map(|a| a.files).sum();
+    let bytes: u64 = changed.clone().map(|a| a.bytes).sum();
+    let merged = changed.clone().filter(|a| a.merged).count();
+    let copied = changed.count() - merged;
+    let skipped = actions
+        .iter()
+        .filter(|a| !a.wrote && !a.would_write)
+        .count();
+    let verb = if actions.iter().any(|a| a.wrote) {
+        "written"
+    } else {
+        "pending"
+    };
+    let mut line = format!(
+        "— Summary — {}={} merged={} copied={} skipped={} bytes={}",
+        verb, files, merged, copied, skipped, bytes
+    );
+    let hook_failures = actions.iter().filter(|a| a.hook_failed).count();
+    if hook_failures > 0 {
+        line.push_str(&format!(" hook_failures={}", hook_failures));
+    }
+    let pruned = actions.iter().filter(|a| a.pruned).count();
+    if pruned > 0 {
+        line.push_str(&format!(" pruned={}", pruned));
+    }
+    line
+}
+
+/// Line-level unified diff of a sync target (current vs. synced content).
+fn build_unified_diff(d: &SyncDiff) -> String {
+    let old = d.old.as_deref().unwrap_or("");
+    let from = if d.old.is_some() {
+        format!("{} (current)", d.target)
+    } else {
+        "/dev/null".to_string()
+    };
+    let mut out = similar::TextDiff::from_lines(old, &d.new)
+        .unified_diff()
+        .context_radius(3)
+        .header(&from, &format!("{} (synced)", d.target))
+        .to_string();
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
 fn build_naive_diff(old: Option<&str>, new: Option<&str>) -> Option<String> {
     let old = old?;
     let new = new?;
@@ -333,16 +751,59 @@ fn build_naive_diff(old: Option<&str>, new: Option<&str>) -> Option<String> {
     Some(out)
 }
 
+/// Build structured unified-diff hunks (3 lines of context) for JSON output.
+///
+/// Each hunk is `{ oldStart, oldLines, newStart, newLines, lines }` with
+/// 1-based starts and `lines` entries of `{ op: equal|delete|insert, text }`.
+fn build_diff_hunks(old: Option<&str>, new: Option<&str>) -> Option<JsonVal> {
+    use similar::{ChangeTag, TextDiff};
+    let old = old?;
+    let new = new?;
+    let diff = TextDiff::from_lines(old, new);
+    let mut hunks = Vec::new();
+    for group in diff.grouped_ops(3) {
+        let (first, last) = match (group.first(), group.last()) {
+            (Some(f), Some(l)) => (f, l),
+            _ => continue,
+        };
+        let old_start = first.old_range().start;
+        let new_start = first.new_range().start;
+        let old_lines = last.old_range().end - old_start;
+        let new_lines = last.new_range().end - new_start;
+        let mut lines = Vec::new();
+        for op in &group {
+            for change in diff.iter_changes(op) {
+                let tag = match change.tag() {
+                    ChangeTag::Equal => "equal",
+                    ChangeTag::Delete => "delete",
+                    ChangeTag::Insert => "insert",
+                };
+                let text = change.value().trim_end_matches('\n').to_string();
+                lines.push(json!({"op": tag, "text": text}));
+            }
+        }
+        hunks.push(json!({
+            "oldStart": old_start + 1,
+            "oldLines": old_lines,
+            "newStart": new_start + 1,
+            "newLines": new_lines,
+            "lines": lines,
+        }));
+    }
+    Some(JsonVal::Array(hunks))
+}
+
 /// Compose lint JSON object (pure) for testing/snapshot purposes.
-pub fn compose_lint_json(res: &LintResult) -> JsonVal {
+pub fn compose_lint_json(res: &LintResult, command: &str) -> JsonVal {
     // Directly serialize LintResult as JSON, keeping stable shape without unwraps
-    match serde_json::to_value(res) {
+    let root = match serde_json::to_value(res) {
         Ok(v) => v,
         Err(_) => json!({
             "issues": [],
             "summary": {"errors": 0, "warnings": 0, "infos": 0, "files": 0}
         }),
-    }
+    };
+    with_tool_meta(root, command)
 }
 
 /// Compose grouped human-readable lint lines (excluding summary) for testing.
@@ -407,8 +868,13 @@ pub fn compose_lint_grouped_lines(res: &LintResult, color: bool) -> Vec<String>
                 .unwrap_or_else(|| is.file.clone());
             let base = if color { base.bold().to_string() } else { base };
             lines.push(format!(
-                "  {} {} {} ❲{}❳ — {}",
-                icon, sev, base, is.rule, is.message
+                "  {} {} {} ❲{}❳ — {}{}",
+                icon,
+                sev,
+                base,
+                is.rule,
+                is.message,
+                fixable_mark(is.fixable, color)
             ));
         }
     }
@@ -425,7 +891,7 @@ pub fn compose_format_json(results: &[FormatResult], write: bool, diff: bool) ->
                 "changed": r.changed,
                 "wrote": write && r.changed,
                 "preview": if !write { r.preview.as_ref() } else { None },
-                "diff": if diff && !write { build_naive_diff(r.original.as_deref(), r.preview.as_deref()) } else { None }
+                "diff": if diff && !write { build_diff_hunks(r.original.as_deref(), r.preview.as_deref()) } else { None }
             })
         })
         .collect();
@@ -434,7 +900,7 @@ pub fn compose_format_json(results: &[FormatResult], write: bool, diff: bool) ->
         "total": results.len(),
         "wrote": if write { results.iter().filter(|r| r.changed).count() } else { 0 },
     });
-    json!({"results": items, "summary": summary})
+    with_tool_meta(json!({"results": items, "summary": summary}), "format")
 }
 
 #[cfg(test)]
@@ -462,69 +928,120 @@ mod tests {
         assert_eq!(out["summary"]["changed"], 1);
         assert_eq!(out["summary"]["wrote"], 0);
         assert!(out["results"][0]["preview"].is_string());
-        assert!(out["results"][0]["diff"].is_string());
+        let hunks = out["results"][0]["diff"].as_array().unwrap();
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0]["oldStart"], 1);
+        assert_eq!(hunks[0]["oldLines"], 3);
+        assert_eq!(hunks[0]["newLines"], 3);
+        let ops: Vec<_> = hunks[0]["lines"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|l| (l["op"].as_str().unwrap(), l["text"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                ("equal", "{"),
+                ("delete", "  \"x\":1"),
+                ("insert", "  \"x\": 1"),
+                ("equal", "}"),
+            ]
+        );
         // Case: write=true ⇒ no preview/diff, wrote equals changed
         let out2 = compose_format_json(&results, true, false);
         assert_eq!(out2["summary"]["wrote"], 1);
         assert!(out2["results"][0]["preview"].is_null());
         assert!(out2["results"][0]["diff"].is_null());
+        assert_eq!(out2["command"], "format");
     }
 
     #[test]
     fn test_compose_lint_json_shape() {
-        let res = crate::models::LintResult {
-            issues: vec![crate::models::Issue {
+        let res = crate::models::LintResult::new(
+            vec![crate::models::Issue {
                 file: "p.json".into(),
                 rule: "r".into(),
                 severity: "warn".into(),
+                fixable: false,
                 path: "$.x".into(),
                 message: "msg".into(),
+                code: Some("pkg-x".into()),
             }],
-            summary: crate::models::Summary {
+            crate::models::Summary {
                 errors: 0,
                 warnings: 1,
                 infos: 0,
                 files: 1,
             },
-        };
-        let out = compose_lint_json(&res);
+            Vec::new(),
+        );
+        let out = compose_lint_json(&res, "lint");
         assert_eq!(out["summary"]["warnings"], 1);
+        assert_eq!(out["passed"], true);
+        assert_eq!(out["rules"]["r"], "pass");
         assert_eq!(out["issues"][0]["path"], "$.x");
+        assert_eq!(out["issues"][0]["fixable"], false);
+        assert_eq!(out["issues"][0]["code"], "pkg-x");
+        assert_eq!(out["diagnostics"], json!([]));
+        let broken = res.with_diagnostics(vec![crate::models::Diagnostic::new(
+            "policy-missing",
+            std::path::Path::new("conv/p.toml"),
+            "r",
+            "Policy file not found",
+        )]);
+        let out = compose_lint_json(&broken, "lint");
+        assert_eq!(out["passed"], false);
+        assert_eq!(out["rules"]["r"], "fail");
+        assert_eq!(out["diagnostics"][0]["kind"], "policy-missing");
+        assert_eq!(out["issues"].as_array().unwrap().len(), 1);
+        assert_eq!(out["tool"]["name"], "rigra");
+        assert_eq!(out["tool"]["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(out["command"], "lint");
+        let out = compose_lint_json(&broken, "test-policy");
+        assert_eq!(out["command"], "test-policy");
     }
 
     #[test]
     fn test_compose_lint_grouped_lines_headers_and_basenames() {
-        let res = crate::models::LintResult {
-            issues: vec![
+        let res = crate::models::LintResult::new(
+            vec![
                 crate::models::Issue {
                     file: "conventions/hyperedge/ts-base/package.json".into(),
                     rule: "pkgjson-sub".into(),
                     severity: "error".into(),
+                    fixable: false,
                     path: "$.repository.directory".into(),
                     message: "Field 'repository.directory' is required".into(),
+                    code: None,
                 },
                 crate::models::Issue {
                     file: "conventions/hyperedge/ts-lib-mono/package.json".into(),
                     rule: "pkgjson-sub".into(),
                     severity: "error".into(),
+                    fixable: false,
                     path: "$.author".into(),
                     message: "Author must be in the format 'Name <email> (url)'".into(),
+                    code: None,
                 },
                 crate::models::Issue {
                     file: "package.json".into(),
                     rule: "pkgjson-root".into(),
                     severity: "warn".into(),
+                    fixable: true,
                     path: "$.name".into(),
                     message: "Type mismatch at $.name, got string".into(),
+                    code: None,
                 },
             ],
-            summary: crate::models::Summary {
+            crate::models::Summary {
                 errors: 2,
                 warnings: 1,
                 infos: 0,
                 files: 3,
             },
-        };
+            Vec::new(),
+        );
         let lines = compose_lint_grouped_lines(&res, false);
         // Expect three headers (two nested dirs + '.') and three item lines
         assert!(lines.iter().any(|l| l == "conventions/hyperedge/ts-base"));
@@ -540,5 +1057,79 @@ mod tests {
         assert!(lines
             .iter()
             .any(|l| l.contains(" package.json ❲pkgjson-root❳ — Type mismatch at $.name")));
+        assert!(lines.iter().any(|l| l.ends_with("got string ✎ fixable")));
+        assert!(!lines.iter().any(|l| l.contains("is required ✎")));
+    }
+
+    #[test]
+    fn test_source_snippet_points_at_key() {
+        let text = "{\n  \"name\": \"x\",\n  \"version\": \"1\"\n}\n";
+        let lines = source_snippet("pkg.json", text, "$.version", false);
+        assert_eq!(
+            lines,
+            vec![
+                "     --> pkg.json:3:3",
+                "      |",
+                "    3 |   \"version\": \"1\"",
+                "      |   ^^^^^^^^^",
+            ]
+        );
+        assert!(source_snippet("pkg.json", text, "$.missing", false).is_empty());
+    }
+
+    #[test]
+    fn test_tool_meta_reports_duration_once_timer_started() {
+        start_timer();
+        let out = with_tool_meta(json!({"results": []}), "lint");
+        assert!(out["durationMs"].is_u64());
+    }
+
+    #[test]
+    fn test_format_write_summary_counts() {
+        assert_eq!(
+            format_write_summary(2, 5),
+            "— Summary — Formatted 2 of 5 files"
+        );
+        assert_eq!(
+            format_write_summary(0, 1),
+            "— Summary — Formatted 0 of 1 file"
+        );
+    }
+
+    #[test]
+    fn test_compose_build_info_lines() {
+        let lines = compose_build_info();
+        assert_eq!(lines[0], format!("rigra {}", env!("CARGO_PKG_VERSION")));
+        assert!(lines[1].starts_with("commit:"));
+        assert!(lines[3].starts_with("target:"));
+        assert!(!lines[3].ends_with(' '));
+    }
+
+    #[test]
+    fn test_format_sync_summary_totals() {
+        let action = |merged: bool, wrote: bool, files: usize, bytes: u64| SyncAction {
+            rule_id: "r".into(),
+            source: "s".into(),
+            target: "t".into(),
+            wrote,
+            format: None,
+            would_write: wrote,
+            merged,
+            files,
+            bytes,
+            diffs: Vec::new(),
+            conflict: false,
+            hook_failed: false,
+            pruned: false,
+        };
+        let actions = vec![
+            action(true, true, 1, 120),
+            action(false, true, 3, 30),
+            action(false, false, 0, 0),
+        ];
+        assert_eq!(
+            format_sync_summary(&actions),
+            "— Summary — written=4 merged=1 copied=1 skipped=1 bytes=150"
+        );
     }
 }
@@ -0,0 +1,125 @@
+//! Optional timing trace for performance work (`--profile-output <path>`).
+//!
+//! When enabled, `span` returns a guard that records a Chrome trace
+//! "complete" event when dropped; otherwise it returns `None` without reading
+//! the clock, so instrumented code costs one atomic load. `finish` writes the
+//! collected events as `{"traceEvents": [...]}`, which loads directly in
+//! Perfetto or `chrome://tracing`.
+//!
+//! Categories in use: `walk` (glob expansion per rule), `parse` (reading and
+//! parsing one target), `check` (one check kind on one document), and
+//! `format` (formatting one target).
+
+use serde_json::{json, Value as Json};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static OUTPUT: Mutex<Option<PathBuf>> = Mutex::new(None);
+static EVENTS: Mutex<Vec<Json>> = Mutex::new(Vec::new());
+static START: OnceLock<Instant> = OnceLock::new();
+static NEXT_TID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    /// Small stable per-thread id for the trace's `tid` column.
+    static TID: u64 = NEXT_TID.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Start collecting events, to be written to `path` by `finish`.
+pub fn enable(path: &Path) {
+    let _ = START.set(Instant::now());
+    if let Ok(mut out) = OUTPUT.lock() {
+        *out = Some(path.to_path_buf());
+    }
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// An open span; records its duration when dropped.
+pub struct Span {
+    cat: &'static str,
+    name: String,
+    begin: Instant,
+}
+
+/// Time a region under `cat`. The name is only built when profiling is on.
+pub fn span<S: Into<String>>(cat: &'static str, name: impl FnOnce() -> S) -> Option<Span> {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    Some(Span {
+        cat,
+        name: name().into(),
+        begin: Instant::now(),
+    })
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some(start) = START.get() else {
+            return;
+        };
+        let ts = self.begin.duration_since(*start).as_micros() as u64;
+        let dur = self.begin.elapsed().as_micros() as u64;
+        let tid = TID.with(|t| *t);
+        let event = json!({
+            "name": std::mem::take(&mut self.name),
+            "cat": self.cat,
+            "ph": "X",
+            "ts": ts,
+            "dur": dur,
+            "pid": 1,
+            "tid": tid,
+        });
+        if let Ok(mut events) = EVENTS.lock() {
+            events.push(event);
+        }
+    }
+}
+
+/// Write the trace if profiling was enabled; safe to call more than once.
+pub fn finish() {
+    let Some(path) = OUTPUT.lock().ok().and_then(|mut o| o.take()) else {
+        return;
+    };
+    let events = EVENTS
+        .lock()
+        .map(|mut e| std::mem::take(&mut *e))
+        .unwrap_or_default();
+    let trace = json!({"traceEvents": events, "displayTimeUnit": "ms"});
+    let written = serde_json::to_string(&trace)
+        .map_err(|e| e.to_string())
+        .and_then(|s| std::fs::write(&path, s).map_err(|e| e.to_string()));
+    if let Err(e) = written {
+        crate::diag::error(format!(
+            "Failed to write profile '{}': {}",
+            path.to_string_lossy(),
+            e
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_spans_written_as_chrome_trace() {
+        let tmp = tempdir().unwrap();
+        let out = tmp.path().join("trace.json");
+        assert!(span("check", || "before-enable").is_none());
+        enable(&out);
+        {
+            let _s = span("parse", || "package.json");
+        }
+        finish();
+        let trace: Json = serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert!(events
+            .iter()
+            .any(|e| e["name"] == "package.json" && e["cat"] == "parse" && e["ph"] == "X"));
+        assert!(events.iter().all(|e| e["name"] != "before-enable"));
+    }
+}
@@ -1,14 +1,23 @@
 //! Template synchronization based on index `sync` rules.
 //!
 //! Applies file/dir copy operations conditionally per `when` scope tokens.
-//! Uses simple recursive copying for directories.
+//! Uses simple recursive copying for directories, or a single relative
+//! symlink per rule with `mode = "symlink"`.
+//!
+//! Writing runs record each rule's synced target files in
+//! `.rigra/sync/manifest.json`. With `prune`, files a rule synced before but
+//! no longer produces (or whose rule was removed) are deleted; only paths in
+//! that manifest are ever removed.
 
 use crate::models::index::Index;
 use crate::models::sync_policy::{SyncPolicy, SyncRule};
 use crate::models::RunError;
 use crate::{config, utils};
 // colorization handled via utils::error_prefix; keep local color uses minimal
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use serde_json::Value as Json;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -19,14 +28,136 @@ pub struct SyncAction {
     pub wrote: bool,
     pub format: Option<String>,
     pub would_write: bool,
+    /// Produced by a structured JSON merge rather than a copy
+    pub merged: bool,
+    /// Files written (or, without `--write`, that would be written)
+    pub files: usize,
+    /// Bytes written (or that would be written)
+    pub bytes: u64,
+    /// Per-file content previews, filled only in dry-run (`preview`) mode
+    pub diffs: Vec<SyncDiff>,
+    /// The merged target was edited outside sync since the last run and was
+    /// left alone (pass `force` to overwrite)
+    pub conflict: bool,
+    /// A pre hook (rule skipped) or post hook of this rule exited non-zero
+    pub hook_failed: bool,
+    /// `target` is a stale file from an earlier sync that was (or, without
+    /// `write`, would be) deleted by `prune`
+    pub pruned: bool,
+}
+
+/// Current and resulting content of one target file in a dry-run preview.
+/// `old` is `None` when the target does not exist yet.
+#[derive(Clone)]
+pub struct SyncDiff {
+    pub target: String,
+    pub old: Option<String>,
+    pub new: String,
+}
+
+/// What applying one sync rule did (or, without `write`, would do).
+#[derive(Default, Clone)]
+pub struct SyncOutcome {
+    pub wrote: bool,
+    pub would_write: bool,
+    pub merged: bool,
+    pub files: usize,
+    pub bytes: u64,
+    pub diffs: Vec<SyncDiff>,
+    pub conflict: bool,
+    /// Every target file the rule maps to, paired with whether it now holds
+    /// what sync produces (written or already identical)
+    pub targets: Vec<(PathBuf, bool)>,
+}
+
+/// Merge array `src` into `dst` by the `key` field: elements whose `key`
+/// values match are deep-merged (source wins on conflicting leaves), other
+/// source elements are appended unless already present.
+fn merge_by_key(dst: &[Json], src: &[Json], key: &str) -> Vec<Json> {
+    let mut merged: Vec<Json> = dst.to_vec();
+    for item in src {
+        let slot = item.get(key).and_then(|k| {
+            merged
+                .iter()
+                .position(|m| m.get(key).is_some_and(|mk| mk == k))
+        });
+        match slot {
+            Some(i) => deep_merge(&mut merged[i], item),
+            None if !merged.contains(item) => merged.push(item.clone()),
+            None => {}
+        }
+    }
+    merged
+}
+
+/// Recursively merge objects; any non-object value in `over` replaces `base`.
+fn deep_merge(base: &mut Json, over: &Json) {
+    match (base, over) {
+        (Json::Object(b), Json::Object(o)) => {
+            for (k, v) in o {
+                match b.get_mut(k) {
+                    Some(slot) => deep_merge(slot, v),
+                    None => {
+                        b.insert(k.clone(), v.clone());
+                    }
+                }
+            }
+        }
+        (b, o) => *b = o.clone(),
+    }
 }
 
 /// Run sync actions for the given `scope`, producing a list of results.
+///
+/// With `preview` (dry-run), pending actions also carry the current and
+/// resulting content of each target so callers can render a diff. `force`
+/// overwrites merged targets that were edited outside sync (conflicts).
+/// `prune` deletes stale targets recorded in the sync manifest.
+/// With the `parallel` feature, rules are applied concurrently, except those
+/// whose targets coincide or nest, which apply in policy order.
 pub fn run_sync(
     repo_root: &str,
     index_path: &str,
     scope: &str,
     write: bool,
+    preview: bool,
+    force: bool,
+    prune: bool,
+) -> (Vec<SyncAction>, Vec<RunError>) {
+    run_sync_with(
+        repo_root,
+        index_path,
+        scope,
+        write,
+        preview,
+        force,
+        prune,
+        cfg!(feature = "parallel"),
+    )
+}
+
+/// One unit of sync work planned by `run_sync_with`.
+enum Planned {
+    /// Rule skipped before applying (failed pre hook)
+    Skipped(SyncAction),
+    /// Apply rule `rule` (index into the policy) from `src` to `dst`
+    Apply {
+        rule: usize,
+        src: PathBuf,
+        dst: PathBuf,
+    },
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_sync_with(
+    repo_root: &str,
+    index_path: &str,
+    scope: &str,
+    write: bool,
+    preview: bool,
+    force: bool,
+    prune: bool,
+    parallel: bool,
 ) -> (Vec<SyncAction>, Vec<RunError>) {
     let root = PathBuf::from(repo_root);
     let idx_path = root.join(index_path);
@@ -34,15 +165,11 @@ pub fn run_sync(
     let idx_str = match fs::read_to_string(&idx_path) {
         Ok(s) => s,
         Err(e) => {
-            eprintln!(
-                "{} {}",
-                crate::utils::error_prefix(),
-                format!(
-                    "Failed to read index: {} — {}. Pass --index or configure rigra.toml.",
-                    idx_path.to_string_lossy(),
-                    e
-                )
-            );
+            crate::diag::error(format!(
+                "Failed to read index: {} — {}. Pass --index or configure rigra.toml.",
+                idx_path.to_string_lossy(),
+                e
+            ));
             errors.push(RunError {
                 message: format!(
                     "Failed to read index: {} — {}",
@@ -56,15 +183,11 @@ pub fn run_sync(
     let index: Index = match toml::from_str(&idx_str) {
         Ok(ix) => ix,
         Err(e) => {
-            eprintln!(
-                "{} {}",
-                crate::utils::error_prefix(),
-                format!(
-                    "Failed to parse index TOML: {} — {}",
-                    idx_path.to_string_lossy(),
-                    e
-                )
-            );
+            crate::diag::error(format!(
+                "Failed to parse index TOML: {} — {}",
+                idx_path.to_string_lossy(),
+                e
+            ));
             errors.push(RunError {
                 message: format!(
                     "Failed to parse index TOML: {} — {}",
@@ -88,6 +211,11 @@ pub fn run_sync(
         .as_ref()
         .and_then(|s| s.ignore.clone())
         .unwrap_or_default();
+    let pre_hooks = client_cfg
+        .sync
+        .as_ref()
+        .and_then(|s| s.hooks.as_ref().and_then(|h| h.pre.clone()))
+        .unwrap_or_default();
     let post_hooks = client_cfg
         .sync
         .as_ref()
@@ -98,10 +226,8 @@ pub fn run_sync(
     let pol_path_rel = match index.sync_ref.as_ref() {
         Some(r) => r,
         None => {
-            eprintln!(
-                "{} {}",
-                crate::utils::error_prefix(),
-                "Index missing 'sync' policy reference. Add sync = \"sync.toml\" in index.toml."
+            crate::diag::error(
+                "Index missing 'sync' policy reference. Add sync = \"sync.toml\" in index.toml.",
             );
             errors.push(RunError {
                 message: "Index missing 'sync' policy reference".to_string(),
@@ -116,15 +242,11 @@ pub fn run_sync(
     let pol_str = match fs::read_to_string(&pol_path) {
         Ok(s) => s,
         Err(e) => {
-            eprintln!(
-                "{} {}",
-                crate::utils::error_prefix(),
-                format!(
-                    "Failed to read sync policy: {} — {}",
-                    pol_path.to_string_lossy(),
-                    e
-                )
-            );
+            crate::diag::error(format!(
+                "Failed to read sync policy: {} — {}",
+                pol_path.to_string_lossy(),
+                e
+            ));
             errors.push(RunError {
                 message: format!(
                     "Failed to read sync policy: {} — {}",
@@ -138,15 +260,11 @@ pub fn run_sync(
     let policy: SyncPolicy = match toml::from_str(&pol_str) {
         Ok(p) => p,
         Err(e) => {
-            eprintln!(
-                "{} {}",
-                crate::utils::error_prefix(),
-                format!(
-                    "Invalid sync policy TOML: {} — {}",
-                    pol_path.to_string_lossy(),
-                    e
-                )
-            );
+            crate::diag::error(format!(
+                "Invalid sync policy TOML: {} — {}",
+                pol_path.to_string_lossy(),
+                e
+            ));
             errors.push(RunError {
                 message: format!(
                     "Invalid sync policy TOML: {} — {}",
@@ -158,65 +276,432 @@ pub fn run_sync(
         }
     };
 
-    let mut actions = Vec::new();
-    for rule in policy.sync {
+    for w in unknown_scope_warnings(&policy) {
+        crate::diag::warn(w);
+    }
+
+    // Plan every (source, target) pair first; pre hooks run here, in order
+    let base = idx_path.parent().unwrap_or_else(|| Path::new("."));
+    let mut planned: Vec<Planned> = Vec::new();
+    // Rules applied this run; only their manifest entries are refreshed
+    let mut ran: BTreeSet<String> = BTreeSet::new();
+    for (ri, rule) in policy.sync.iter().enumerate() {
         if ignore_ids.contains(&rule.id) {
             continue;
         }
-        if !is_rule_enabled(&rule.when, scope) {
+        if !is_rule_enabled(&rule.when, scope) || !conditions_met(&root, rule) {
+            continue;
+        }
+        // Pre hooks only run when writing; a failing one skips the rule
+        let pre_cmds = pre_hooks.get(&rule.id).filter(|_| write);
+        if let Some(Err(e)) = pre_cmds.map(|cmds| run_hooks(&root, cmds)) {
+            let msg = format!(
+                "Pre-sync hook failed for rule '{}': {} (rule skipped)",
+                rule.id, e
+            );
+            crate::diag::error(msg.clone());
+            errors.push(RunError { message: msg });
+            planned.push(Planned::Skipped(SyncAction {
+                rule_id: rule.id.clone(),
+                source: base.join(&rule.source).to_string_lossy().to_string(),
+                target: root.join(&rule.target).to_string_lossy().to_string(),
+                wrote: false,
+                format: rule.format.clone(),
+                would_write: false,
+                merged: false,
+                files: 0,
+                bytes: 0,
+                diffs: Vec::new(),
+                conflict: false,
+                hook_failed: true,
+                pruned: false,
+            }));
             continue;
         }
-        let src = resolve_path(&idx_path, &rule.source);
         // Allow per-id target override from client config
         let dst_target = sync_cfg_map
             .get(&rule.id)
             .and_then(|c| c.target.clone())
             .unwrap_or_else(|| rule.target.clone());
         let dst = root.join(&dst_target);
-        let (wrote, would_write) = apply_sync(
+        let pairs = expand_source(base, &rule.id, &rule.source, &dst, &mut errors);
+        // A source that resolves to nothing says nothing about what the rule
+        // produces, so its earlier targets must not be treated as stale
+        if pairs.iter().any(|(src, _)| src.exists()) {
+            ran.insert(rule.id.clone());
+        } else if prune {
+            let msg = format!(
+                "Sync source '{}' for rule '{}' matched no files; not pruning its targets",
+                rule.source, rule.id
+            );
+            crate::diag::error(msg.clone());
+            errors.push(RunError { message: msg });
+        }
+        for (src, dst) in pairs {
+            planned.push(Planned::Apply { rule: ri, src, dst });
+        }
+    }
+
+    let apply = |p: Planned| -> (SyncAction, Vec<RunError>, Vec<(PathBuf, bool)>) {
+        let (ri, src, dst) = match p {
+            Planned::Skipped(a) => return (a, Vec::new(), Vec::new()),
+            Planned::Apply { rule, src, dst } => (rule, src, dst),
+        };
+        let rule = &policy.sync[ri];
+        let mut errs = Vec::new();
+        let outcome = apply_sync(
             &root,
-            &rule,
+            rule,
             &src,
             &dst,
             sync_cfg_map.get(&rule.id),
             write,
-            Some(&mut errors),
+            preview,
+            force,
+            Some(&mut errs),
         );
-        actions.push(SyncAction {
-            rule_id: rule.id,
+        let action = SyncAction {
+            rule_id: rule.id.clone(),
             source: src.to_string_lossy().to_string(),
             target: dst.to_string_lossy().to_string(),
-            wrote,
+            wrote: outcome.wrote,
             format: rule.format.clone(),
-            would_write,
-        });
+            would_write: outcome.would_write,
+            merged: outcome.merged,
+            files: outcome.files,
+            bytes: outcome.bytes,
+            diffs: outcome.diffs,
+            conflict: outcome.conflict,
+            hook_failed: false,
+            pruned: false,
+        };
+        (action, errs, outcome.targets)
+    };
+    // Pairs with distinct targets apply independently; those sharing a target
+    // (or a directory around it) run sequentially so the last rule wins as it
+    // would without `parallel`. Results are put back in policy order.
+    let shared = overlapping_targets(&planned);
+    let (serial, independent): (Vec<_>, Vec<_>) = planned
+        .into_iter()
+        .enumerate()
+        .partition(|(i, _)| !parallel || shared[*i]);
+    let mut results: Vec<_> = independent
+        .into_par_iter()
+        .map(|(i, p)| (i, apply(p)))
+        .collect();
+    results.extend(serial.into_iter().map(|(i, p)| (i, apply(p))));
+    results.sort_by_key(|(i, _)| *i);
+    let mut actions = Vec::with_capacity(results.len());
+    let mut targets: BTreeMap<String, Vec<(PathBuf, bool)>> = BTreeMap::new();
+    for (_, (action, errs, t)) in results {
+        targets.entry(action.rule_id.clone()).or_default().extend(t);
+        actions.push(action);
+        errors.extend(errs);
     }
+    let rule_ids: BTreeSet<&str> = policy.sync.iter().map(|r| r.id.as_str()).collect();
+    actions.extend(reconcile_manifest(
+        &root,
+        &rule_ids,
+        &ran,
+        &targets,
+        write,
+        prune,
+        force,
+        &mut errors,
+    ));
 
-    // Run post hooks for wrote actions
-    for a in &actions {
-        if a.wrote {
-            if let Some(cmds) = post_hooks.get(&a.rule_id) {
-                for cmd in cmds {
-                    let _ = std::process::Command::new("sh")
-                        .arg("-lc")
-                        .arg(cmd)
-                        .current_dir(&root)
-                        .status();
-                }
+    // Run post hooks once per rule that wrote (glob rules yield several actions)
+    let mut hooked: Vec<String> = Vec::new();
+    for i in 0..actions.len() {
+        let id = actions[i].rule_id.clone();
+        if !actions[i].wrote || hooked.contains(&id) {
+            continue;
+        }
+        if let Some(Err(e)) = post_hooks.get(&id).map(|cmds| run_hooks(&root, cmds)) {
+            let msg = format!("Post-sync hook failed for rule '{}': {}", id, e);
+            crate::diag::error(msg.clone());
+            errors.push(RunError { message: msg });
+            for a in actions.iter_mut().filter(|a| a.rule_id == id) {
+                a.hook_failed = true;
             }
         }
+        hooked.push(id);
     }
     (actions, errors)
 }
 
-/// Resolve a path relative to the index file location.
-fn resolve_path(idx_path: &Path, rel: &str) -> PathBuf {
-    let base = idx_path.parent().unwrap_or_else(|| Path::new("."));
-    base.join(rel)
+/// For each planned pair, whether its target equals or nests inside (or
+/// around) another pair's target.
+fn overlapping_targets(planned: &[Planned]) -> Vec<bool> {
+    let dsts: Vec<Option<&Path>> = planned
+        .iter()
+        .map(|p| match p {
+            Planned::Apply { dst, .. } => Some(dst.as_path()),
+            Planned::Skipped(_) => None,
+        })
+        .collect();
+    dsts.iter()
+        .enumerate()
+        .map(|(i, a)| {
+            a.is_some_and(|a| {
+                dsts.iter()
+                    .enumerate()
+                    .any(|(j, b)| j != i && b.is_some_and(|b| a.starts_with(b) || b.starts_with(a)))
+            })
+        })
+        .collect()
 }
 
-/// Copy one rule's source to target. Honors `overwrite` for files and
-/// performs recursive copies for directories.
+/// Per-rule target files (relative to the repo root) written by earlier
+/// syncs, each with the sha256 of the content sync left there.
+#[derive(Default, Serialize, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    rules: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+fn manifest_path(root: &Path) -> PathBuf {
+    root.join(".rigra/sync/manifest.json")
+}
+
+/// Fold this run's targets into the sync manifest and handle stale entries:
+/// files recorded for a rule that ran but no longer maps to them, or for a
+/// rule removed from the policy. With `prune` they are deleted (or reported
+/// as pending without `write`); otherwise they stay recorded for a later
+/// prune. Files edited since sync wrote them are only deleted with `force`.
+/// Rules that did not run keep their entries. The manifest is only saved
+/// when writing.
+#[allow(clippy::too_many_arguments)]
+fn reconcile_manifest(
+    root: &Path,
+    rule_ids: &BTreeSet<&str>,
+    ran: &BTreeSet<String>,
+    targets: &BTreeMap<String, Vec<(PathBuf, bool)>>,
+    write: bool,
+    prune: bool,
+    force: bool,
+    errors: &mut Vec<RunError>,
+) -> Vec<SyncAction> {
+    let rel = |p: &Path| {
+        p.strip_prefix(root)
+            .unwrap_or(p)
+            .to_string_lossy()
+            .replace('\\', "/")
+    };
+    let path = manifest_path(root);
+    let old: Manifest = read_to_string(&path)
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    let mapped: BTreeSet<String> = targets.values().flatten().map(|(p, _)| rel(p)).collect();
+
+    let mut next = Manifest::default();
+    let mut actions = Vec::new();
+    for (id, files) in &old.rules {
+        let ran_now = ran.contains(id);
+        if !ran_now && rule_ids.contains(id.as_str()) {
+            next.rules.insert(id.clone(), files.clone());
+            continue;
+        }
+        for (f, digest) in files {
+            let full = root.join(f);
+            let exists = fs::symlink_metadata(&full).is_ok_and(|m| !m.is_dir());
+            // Still produced (maybe by another rule now) or already gone
+            if mapped.contains(f) || !exists {
+                continue;
+            }
+            let keep = |next: &mut Manifest| {
+                let entry = next.rules.entry(id.clone()).or_default();
+                entry.insert(f.clone(), digest.clone());
+            };
+            if !prune {
+                keep(&mut next);
+                continue;
+            }
+            if !force && content_digest(&full).as_ref() != Some(digest) {
+                let msg = format!(
+                    "Not pruning '{}': edited since rule '{}' synced it (rerun with --force to delete)",
+                    full.to_string_lossy(),
+                    id
+                );
+                crate::diag::error(msg.clone());
+                errors.push(RunError { message: msg });
+                keep(&mut next);
+                continue;
+            }
+            let mut action = SyncAction {
+                rule_id: id.clone(),
+                source: String::new(),
+                target: full.to_string_lossy().to_string(),
+                wrote: false,
+                format: None,
+                would_write: true,
+                merged: false,
+                files: 0,
+                bytes: 0,
+                diffs: Vec::new(),
+                conflict: false,
+                hook_failed: false,
+                pruned: true,
+            };
+            if write {
+                match fs::remove_file(&full) {
+                    Ok(()) => action.wrote = true,
+                    Err(e) => {
+                        let msg = format!("Failed to prune '{}': {}", full.to_string_lossy(), e);
+                        crate::diag::error(msg.clone());
+                        errors.push(RunError { message: msg });
+                        keep(&mut next);
+                    }
+                }
+            }
+            actions.push(action);
+        }
+    }
+    for (id, ts) in targets {
+        let prev = old.rules.get(id);
+        let entry = next.rules.entry(id.clone()).or_default();
+        for (p, synced) in ts {
+            let f = rel(p);
+            // Targets sync never wrote (e.g. kept user files) stay unrecorded
+            let digest = if *synced {
+                content_digest(p)
+            } else {
+                prev.and_then(|s| s.get(&f)).cloned()
+            };
+            if let Some(digest) = digest {
+                entry.insert(f, digest);
+            }
+        }
+    }
+    next.rules.retain(|_, files| !files.is_empty());
+
+    if write {
+        ensure_parent(&path);
+        let saved = serde_json::to_string_pretty(&next)
+            .map_err(|e| e.to_string())
+            .and_then(|s| fs::write(&path, s).map_err(|e| e.to_string()));
+        if let Err(e) = saved {
+            let msg = format!(
+                "Failed to write sync manifest '{}': {}",
+                path.to_string_lossy(),
+                e
+            );
+            crate::diag::error(msg.clone());
+            errors.push(RunError { message: msg });
+        }
+    }
+    actions
+}
+
+/// sha256 of a target as sync left it: the file content, or the link path
+/// for a symlinked target.
+fn content_digest(p: &Path) -> Option<String> {
+    let bytes = match fs::read_link(p) {
+        Ok(link) => link.to_string_lossy().into_owned().into_bytes(),
+        Err(_) => fs::read(p).ok()?,
+    };
+    Some(sha256_hex(&bytes))
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Run hook commands in order through `sh -lc` from the repo root, stopping
+/// at the first failure. Output is captured: successful hooks only show it
+/// under `RIGRA_LOG=debug`; a failure returns the command, exit code, and
+/// stderr.
+fn run_hooks(root: &Path, cmds: &[String]) -> Result<(), String> {
+    for cmd in cmds {
+        let out = std::process::Command::new("sh")
+            .arg("-lc")
+            .arg(cmd)
+            .current_dir(root)
+            .output()
+            .map_err(|e| format!("`{}` could not start: {}", cmd, e))?;
+        if !out.status.success() {
+            let code = out
+                .status
+                .code()
+                .map_or_else(|| "a signal".to_string(), |c| format!("exit {}", c));
+            return Err(format!(
+                "`{}` failed with {}: {}",
+                cmd,
+                code,
+                String::from_utf8_lossy(&out.stderr).trim()
+            ));
+        }
+        crate::diag::debug(|| {
+            format!(
+                "hook `{}`: {}",
+                cmd,
+                String::from_utf8_lossy(&out.stdout).trim()
+            )
+        });
+    }
+    Ok(())
+}
+
+/// Whether the rule's `if_exists`/`unless_exists` conditions hold for the
+/// repo at `root`. Rules without conditions always apply.
+pub fn conditions_met(root: &Path, rule: &SyncRule) -> bool {
+    let exists = |p: &String| root.join(p).exists();
+    rule.if_exists.as_ref().is_none_or(exists) && !rule.unless_exists.as_ref().is_some_and(exists)
+}
+
+/// Expand a glob `source` (e.g. `templates/*.json`, relative to `base`) into
+/// one `(source, target)` pair per match, in sorted order, each landing in
+/// the `dst` directory under its own file name. A source without glob
+/// characters maps to `dst` as is. An invalid pattern is recorded in
+/// `errors`; one that matches nothing is warned about.
+pub fn expand_source(
+    base: &Path,
+    rule_id: &str,
+    source: &str,
+    dst: &Path,
+    errors: &mut Vec<RunError>,
+) -> Vec<(PathBuf, PathBuf)> {
+    if !source.contains(['*', '?', '[']) {
+        return vec![(base.join(source), dst.to_path_buf())];
+    }
+    let pattern = format!(
+        "{}/{}",
+        glob::Pattern::escape(&base.to_string_lossy()),
+        source
+    );
+    let mut matches: Vec<PathBuf> = match glob::glob(&pattern) {
+        Ok(paths) => paths.flatten().collect(),
+        Err(e) => {
+            let msg = format!(
+                "Invalid sync source glob for rule '{}': {} — {}",
+                rule_id, source, e
+            );
+            crate::diag::error(msg.clone());
+            errors.push(RunError { message: msg });
+            return Vec::new();
+        }
+    };
+    if matches.is_empty() {
+        crate::diag::warn(format!(
+            "Sync source glob for rule '{}' matched no files: {}",
+            rule_id, source
+        ));
+    }
+    matches.sort();
+    matches
+        .into_iter()
+        .filter_map(|m| {
+            let name = m.file_name()?.to_os_string();
+            Some((m, dst.join(name)))
+        })
+        .collect()
+}
+
+/// Whether two files exist and hold identical bytes.
 fn same_content(src: &Path, dst: &Path) -> bool {
     if !dst.exists() || !src.exists() {
         return false;
@@ -234,40 +719,57 @@ fn same_content(src: &Path, dst: &Path) -> bool {
     }
 }
 
+/// Copy one rule's source to target. Honors `overwrite` for files and
+/// performs recursive copies for directories; in symlink mode the target is
+/// linked to the source instead.
 fn copy_rule(
     rule: &SyncRule,
     src: &PathBuf,
     dst: &PathBuf,
     write: bool,
+    preview: bool,
+    backup: bool,
     errors: Option<&mut Vec<RunError>>,
-) -> (bool, bool) {
-    let mut wrote = false;
-    let mut would_write = false;
+) -> SyncOutcome {
+    if is_symlink_mode(rule) {
+        return link_rule(rule, src, dst, write, backup, errors);
+    }
+    let mut errors = errors;
+    let mut out = SyncOutcome::default();
     if src.is_file() {
-        if same_content(src, dst) {
-            wrote = false;
-            would_write = false;
-        } else {
-            would_write = true;
+        let identical = same_content(src, dst);
+        if !identical && !keeps_existing(rule, dst) {
+            out.would_write = true;
+            out.files = 1;
+            out.bytes = fs::metadata(src).map(|m| m.len()).unwrap_or(0);
+            // Binary sources have no line diff; they still count as pending
+            if let (true, Some(new)) = (preview, read_to_string(src)) {
+                out.diffs.push(SyncDiff {
+                    target: dst.to_string_lossy().to_string(),
+                    old: read_to_string(dst),
+                    new,
+                });
+            }
             if let Some(parent) = dst.parent() {
                 let _ = fs::create_dir_all(parent);
             }
-            if write {
+            if write && backup && !backup_target(dst, errors.as_deref_mut()) {
+                out.files = 0;
+                out.bytes = 0;
+            } else if write {
                 match fs::copy(src, dst) {
-                    Ok(_) => {
-                        wrote = true;
+                    Ok(n) => {
+                        out.wrote = true;
+                        out.bytes = n;
+                        copy_permissions(src, dst);
                     }
                     Err(e) => {
-                        eprintln!(
-                            "{} {}",
-                            crate::utils::error_prefix(),
-                            format!(
-                                "Failed to copy file '{}' -> '{}': {}",
-                                src.to_string_lossy(),
-                                dst.to_string_lossy(),
-                                e
-                            )
-                        );
+                        crate::diag::error(format!(
+                            "Failed to copy file '{}' -> '{}': {}",
+                            src.to_string_lossy(),
+                            dst.to_string_lossy(),
+                            e
+                        ));
                         // capture as runtime error on copy failure
                         // Note: still mark would_write as true to signal intended change
                         // wrote remains false
@@ -287,69 +789,255 @@ fn copy_rule(
                                 ),
                             });
                         }
-                        wrote = false;
+                        out.files = 0;
+                        out.bytes = 0;
                     }
                 }
             }
         }
+        out.targets.push((dst.clone(), identical || out.wrote));
     } else if src.is_dir() {
         if write {
             let _ = fs::create_dir_all(dst);
         }
-        if let Ok(entries) = fs::read_dir(src) {
-            let mut errs_opt = errors;
-            for entry in entries.flatten() {
-                let p = entry.path();
-                let t = dst.join(entry.file_name());
-                let (_w, _would) = copy_rule(rule, &p, &t, write, errs_opt.as_deref_mut());
-                if _would {
-                    would_write = true;
-                }
-                if _w {
-                    wrote = true;
-                }
+        let mut errs_opt = errors;
+        for (p, t) in dir_entries(rule, src, dst, write) {
+            let child = copy_rule(
+                rule,
+                &p,
+                &t,
+                write,
+                preview,
+                backup,
+                errs_opt.as_deref_mut(),
+            );
+            out.would_write |= child.would_write;
+            out.wrote |= child.wrote;
+            out.files += child.files;
+            out.bytes += child.bytes;
+            out.diffs.extend(child.diffs);
+            out.targets.extend(child.targets);
+        }
+    }
+    out
+}
+
+/// `(source, target)` pairs to copy for a directory rule. By default every
+/// file below `src` that its `.gitignore` files keep is listed (creating
+/// subdirectories when writing); with `include_ignored = true` the direct
+/// children are listed and directories recurse through `copy_rule`.
+fn dir_entries(rule: &SyncRule, src: &Path, dst: &Path, write: bool) -> Vec<(PathBuf, PathBuf)> {
+    if rule.include_ignored.unwrap_or(false) {
+        return match fs::read_dir(src) {
+            Ok(entries) => entries
+                .flatten()
+                .map(|e| (e.path(), dst.join(e.file_name())))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+    }
+    let walker = ignore::WalkBuilder::new(src)
+        .standard_filters(false)
+        .git_ignore(true)
+        .require_git(false)
+        .parents(false)
+        .build();
+    let mut out = Vec::new();
+    for entry in walker.flatten() {
+        let p = entry.path();
+        let rel = match p.strip_prefix(src) {
+            Ok(rel) if !rel.as_os_str().is_empty() => rel,
+            _ => continue,
+        };
+        let t = dst.join(rel);
+        if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+            if write {
+                let _ = fs::create_dir_all(&t);
             }
+        } else {
+            out.push((p.to_path_buf(), t));
         }
     }
-    (wrote, would_write)
+    out
 }
 
 /// Apply sync for a rule, performing copy or smart merge depending on rule.format and client config.
+#[allow(clippy::too_many_arguments)]
 pub fn apply_sync(
-    _root: &Path,
+    root: &Path,
     rule: &SyncRule,
     src: &PathBuf,
     dst: &PathBuf,
     client: Option<&config::SyncClientCfg>,
     write: bool,
+    preview: bool,
+    force: bool,
     errors: Option<&mut Vec<RunError>>,
-) -> (bool, bool) {
-    // Structured merge only when format=json and client merge config is present
-    if let Some(ct) = rule.format.as_ref() {
+) -> SyncOutcome {
+    let backup = client.and_then(|c| c.backup).unwrap_or(false);
+    // Structured merge only when format=json and client merge config is
+    // present; a symlinked target mirrors the source and is never merged
+    if let Some(ct) = rule.format.as_ref().filter(|_| !is_symlink_mode(rule)) {
+        if src.is_file() && keeps_existing(rule, dst) {
+            return SyncOutcome {
+                targets: vec![(dst.clone(), false)],
+                ..Default::default()
+            };
+        }
         if ct.as_str().eq_ignore_ascii_case("json") {
             if let Some(mcfg) = client.and_then(|c| c.merge.as_ref()) {
-                return apply_json_merge(rule, src, dst, mcfg, write, errors);
+                return apply_json_merge(
+                    root, rule, src, dst, mcfg, write, preview, force, backup, errors,
+                );
             }
         }
     }
-    copy_rule(rule, src, dst, write, errors)
+    copy_rule(rule, src, dst, write, preview, backup, errors)
 }
 
-fn read_to_string(p: &Path) -> Option<String> {
-    fs::read_to_string(p).ok()
+/// Copy the current `dst` to `<dst>.rigra.bak` ahead of an overwrite.
+/// Returns false (recording the error) when the backup could not be made,
+/// in which case the caller must leave `dst` untouched.
+fn backup_target(dst: &Path, errors: Option<&mut Vec<RunError>>) -> bool {
+    if !dst.is_file() {
+        return true;
+    }
+    let mut bak = dst.as_os_str().to_owned();
+    bak.push(".rigra.bak");
+    match fs::copy(dst, &bak) {
+        Ok(_) => true,
+        Err(e) => {
+            let message = format!(
+                "Failed to back up '{}' before overwrite: {}",
+                dst.to_string_lossy(),
+                e
+            );
+            crate::diag::error(&message);
+            if let Some(errs) = errors {
+                errs.push(RunError { message });
+            }
+            false
+        }
+    }
 }
 
-fn fingerprint(s: &str) -> String {
-    use std::hash::{Hash, Hasher};
-    let mut h = std::collections::hash_map::DefaultHasher::new();
-    s.hash(&mut h);
-    format!("{:016x}-{}", h.finish(), s.len())
+/// Give `dst` the mode bits of `src` (e.g. the executable bit of synced
+/// scripts), which `fs::copy` does not carry over everywhere.
+#[cfg(unix)]
+fn copy_permissions(src: &Path, dst: &Path) {
+    if let Ok(md) = fs::metadata(src) {
+        let _ = fs::set_permissions(dst, md.permissions());
+    }
 }
 
+#[cfg(not(unix))]
+fn copy_permissions(_src: &Path, _dst: &Path) {}
+
+fn is_symlink_mode(rule: &SyncRule) -> bool {
+    rule.mode
+        .as_deref()
+        .is_some_and(|m| m.eq_ignore_ascii_case("symlink"))
+}
+
+/// Point `dst` at `src` with a relative symlink. Up to date when `dst`
+/// already links there; otherwise an existing file or link is replaced only
+/// when `write` is set (real directories are never removed), after backing
+/// it up when `backup` is set.
+fn link_rule(
+    rule: &SyncRule,
+    src: &Path,
+    dst: &Path,
+    write: bool,
+    backup: bool,
+    errors: Option<&mut Vec<RunError>>,
+) -> SyncOutcome {
+    let mut errors = errors;
+    let mut out = SyncOutcome::default();
+    let link = link_target(src, dst);
+    let linked = fs::read_link(dst).is_ok_and(|cur| cur == link);
+    out.targets.push((dst.to_path_buf(), linked));
+    if linked || keeps_existing(rule, dst) {
+        return out;
+    }
+    out.would_write = true;
+    out.files = 1;
+    if !write {
+        return out;
+    }
+    if backup && !backup_target(dst, errors.as_deref_mut()) {
+        out.files = 0;
+        return out;
+    }
+    ensure_parent(dst);
+    let res = match fs::symlink_metadata(dst) {
+        Ok(md) if md.is_dir() => Err(std::io::Error::other("target is an existing directory")),
+        Ok(_) => fs::remove_file(dst).and_then(|_| make_symlink(&link, src, dst)),
+        Err(_) => make_symlink(&link, src, dst),
+    };
+    match res {
+        Ok(()) => {
+            out.wrote = true;
+            out.targets[0].1 = true;
+        }
+        Err(e) => {
+            let msg = format!(
+                "Failed to symlink '{}' -> '{}': {}",
+                dst.to_string_lossy(),
+                src.to_string_lossy(),
+                e
+            );
+            crate::diag::error(msg.clone());
+            if let Some(errs) = errors {
+                errs.push(RunError { message: msg });
+            }
+            out.files = 0;
+        }
+    }
+    out
+}
+
+/// Path of `src` relative to the directory holding `dst`, falling back to
+/// the absolute source when no relative form exists.
+fn link_target(src: &Path, dst: &Path) -> PathBuf {
+    let abs = |p: &Path| fs::canonicalize(p).or_else(|_| std::path::absolute(p));
+    let src_abs = abs(src).unwrap_or_else(|_| src.to_path_buf());
+    let dir = dst.parent().unwrap_or_else(|| Path::new("."));
+    abs(dir)
+        .ok()
+        .and_then(|d| pathdiff::diff_paths(&src_abs, d))
+        .unwrap_or(src_abs)
+}
+
+#[cfg(unix)]
+fn make_symlink(link: &Path, _src: &Path, dst: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(link, dst)
+}
+
+#[cfg(windows)]
+fn make_symlink(link: &Path, src: &Path, dst: &Path) -> std::io::Result<()> {
+    if src.is_dir() {
+        std::os::windows::fs::symlink_dir(link, dst)
+    } else {
+        std::os::windows::fs::symlink_file(link, dst)
+    }
+}
+
+/// Whether an existing target must be left alone (`overwrite = false`).
+fn keeps_existing(rule: &SyncRule, dst: &Path) -> bool {
+    !rule.overwrite.unwrap_or(true) && dst.exists()
+}
+
+fn read_to_string(p: &Path) -> Option<String> {
+    fs::read_to_string(p).ok()
+}
+
+/// Where the digest of what sync last merged into `target` is kept: under
+/// the repo root, keyed by the repo-relative target path.
 fn checksum_path(root: &Path, target: &Path) -> PathBuf {
-    let rel = utils::rel_to_wd(target).replace('/', "__");
+    let rel = target.strip_prefix(root).unwrap_or(target);
+    let key = rel.to_string_lossy().replace(['/', '\\'], "__");
     root.join(".rigra/sync/checksums")
-        .join(format!("{}.chk", rel))
+        .join(format!("{}.chk", key))
 }
 
 fn ensure_parent(p: &Path) {
@@ -358,26 +1046,37 @@ fn ensure_parent(p: &Path) {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn apply_json_merge(
+    root: &Path,
     rule: &SyncRule,
     src: &PathBuf,
     dst: &PathBuf,
     mcfg: &config::SyncClientMergeCfg,
     write: bool,
+    preview: bool,
+    force: bool,
+    backup: bool,
     errors: Option<&mut Vec<RunError>>,
-) -> (bool, bool) {
-    let mut wrote = false;
+) -> SyncOutcome {
     let mut errs_opt = errors;
     // will compute `would_write` only when differing from current
     let src_str = match read_to_string(src) {
         Some(s) => s,
-        None => return (wrote, false),
+        None => return SyncOutcome::default(),
     };
     let src_json: Json = match serde_json::from_str(&src_str) {
         Ok(j) => j,
         Err(_) => {
-            let (w, ww) = copy_rule(rule, src, dst, write, errs_opt.as_deref_mut());
-            return (w, ww);
+            return copy_rule(
+                rule,
+                src,
+                dst,
+                write,
+                preview,
+                backup,
+                errs_opt.as_deref_mut(),
+            )
         }
     };
     let dst_json: Json = if let Some(s) = read_to_string(dst) {
@@ -446,7 +1145,18 @@ fn apply_json_merge(
     // Array strategies
     if let Some(arr) = mcfg.array.as_ref() {
         for (path, strat) in arr.iter() {
-            if strat == "union" {
+            if let Some(key) = strat.merge_key() {
+                if let Some(Json::Array(sa)) = utils::get_json_path(&src_json, path) {
+                    let da = utils::get_json_path(&dst_json, path)
+                        .and_then(|v| v.as_array())
+                        .map(|a| a.as_slice())
+                        .unwrap_or_default();
+                    let merged = merge_by_key(da, sa, key);
+                    set_path(&mut result, path, Some(Json::Array(merged)));
+                }
+                continue;
+            }
+            if matches!(strat, config::ArrayStrategy::Named(s) if s == "union") {
                 if let Some(Json::Array(sa)) = utils::get_json_path(&src_json, path) {
                     let da = utils::get_json_path(&dst_json, path).and_then(|v| v.as_array());
                     let mut merged = Vec::new();
@@ -474,25 +1184,48 @@ fn apply_json_merge(
         Ok(s) => s,
         Err(_) => src_str,
     };
-    let out_fp = fingerprint(&out_str);
-    let cur_fp = read_to_string(dst).map(|s| fingerprint(&s));
+    let out_fp = sha256_hex(out_str.as_bytes());
+    let cur_fp = read_to_string(dst).map(|s| sha256_hex(s.as_bytes()));
     if Some(out_fp.clone()) == cur_fp {
-        return (false, false);
+        return SyncOutcome {
+            targets: vec![(dst.clone(), true)],
+            ..Default::default()
+        };
     }
-    let would_write = true;
-    if write {
-        let cpath = checksum_path(&src.parent().unwrap_or_else(|| Path::new(".")), dst);
+    let mut out = SyncOutcome {
+        wrote: false,
+        would_write: true,
+        merged: true,
+        files: 1,
+        bytes: out_str.len() as u64,
+        diffs: Vec::new(),
+        conflict: false,
+        targets: Vec::new(),
+    };
+    // Three-way check: the checksum records what sync last wrote, so a
+    // different current target means it was edited by hand since then
+    let cpath = checksum_path(root, dst);
+    if let (Some(cur), Some(last)) = (cur_fp.as_ref(), read_to_string(&cpath)) {
+        out.conflict = !force && *cur != last.trim();
+    }
+    if preview {
+        out.diffs.push(SyncDiff {
+            target: dst.to_string_lossy().to_string(),
+            old: read_to_string(dst),
+            new: out_str.clone(),
+        });
+    }
+    if write && !out.conflict && backup && !backup_target(dst, errs_opt.as_deref_mut()) {
+        out.files = 0;
+        out.bytes = 0;
+    } else if write && !out.conflict {
         ensure_parent(&cpath);
         if let Err(e) = fs::write(&cpath, &out_fp) {
-            eprintln!(
-                "{} {}",
-                crate::utils::error_prefix(),
-                format!(
-                    "Failed to write checksum '{}': {}",
-                    cpath.to_string_lossy(),
-                    e
-                )
-            );
+            crate::diag::error(format!(
+                "Failed to write checksum '{}': {}",
+                cpath.to_string_lossy(),
+                e
+            ));
             if let Some(errs) = errs_opt.as_deref_mut() {
                 errs.push(RunError {
                     message: format!(
@@ -505,17 +1238,13 @@ fn apply_json_merge(
         }
         ensure_parent(dst);
         match fs::write(dst, out_str) {
-            Ok(_) => wrote = true,
+            Ok(_) => out.wrote = true,
             Err(e) => {
-                eprintln!(
-                    "{} {}",
-                    crate::utils::error_prefix(),
-                    format!(
-                        "Failed to write merged file '{}': {}",
-                        dst.to_string_lossy(),
-                        e
-                    )
-                );
+                crate::diag::error(format!(
+                    "Failed to write merged file '{}': {}",
+                    dst.to_string_lossy(),
+                    e
+                ));
                 if let Some(errs) = errs_opt.as_deref_mut() {
                     errs.push(RunError {
                         message: format!(
@@ -525,11 +1254,89 @@ fn apply_json_merge(
                         ),
                     });
                 }
-                wrote = false;
+                out.files = 0;
+                out.bytes = 0;
             }
         }
     }
-    (wrote, would_write)
+    out.targets.push((dst.clone(), out.wrote));
+    out
+}
+
+/// Validate sync rule `when` tokens against the policy's declared `scopes`
+/// without applying any rule. Returns warnings for unknown tokens.
+pub fn check_sync_scopes(repo_root: &str, index_path: &str) -> (Vec<String>, Vec<RunError>) {
+    let root = PathBuf::from(repo_root);
+    let idx_path = root.join(index_path);
+    let mut errors: Vec<RunError> = Vec::new();
+    let index: Index = match fs::read_to_string(&idx_path)
+        .ok()
+        .and_then(|s| toml::from_str(&s).ok())
+    {
+        Some(ix) => ix,
+        None => {
+            errors.push(RunError {
+                message: format!("Failed to load index: {}", idx_path.to_string_lossy()),
+            });
+            return (Vec::new(), errors);
+        }
+    };
+    let Some(pol_rel) = index.sync_ref.as_ref() else {
+        errors.push(RunError {
+            message: "Index missing 'sync' policy reference".to_string(),
+        });
+        return (Vec::new(), errors);
+    };
+    let pol_path = idx_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(pol_rel);
+    match fs::read_to_string(&pol_path)
+        .ok()
+        .and_then(|s| toml::from_str::<SyncPolicy>(&s).ok())
+    {
+        Some(policy) => (unknown_scope_warnings(&policy), errors),
+        None => {
+            errors.push(RunError {
+                message: format!("Failed to load sync policy: {}", pol_path.to_string_lossy()),
+            });
+            (Vec::new(), errors)
+        }
+    }
+}
+
+/// Warnings for rules whose `when` references scopes not declared in
+/// `policy.scopes`. Empty when no scopes are declared.
+pub fn unknown_scope_warnings(policy: &SyncPolicy) -> Vec<String> {
+    if policy.scopes.is_empty() {
+        return Vec::new();
+    }
+    let mut out = Vec::new();
+    for rule in policy.sync.iter() {
+        let unknown = unknown_scopes(&rule.when, &policy.scopes);
+        if !unknown.is_empty() {
+            out.push(format!(
+                "Sync rule '{}' references unknown scope(s) in when: {} (known: {})",
+                rule.id,
+                unknown.join(", "),
+                policy.scopes.join(", ")
+            ));
+        }
+    }
+    out
+}
+
+/// Tokens of `when` that are not wildcards and not in `known` (case-insensitive).
+fn unknown_scopes(when: &str, known: &[String]) -> Vec<String> {
+    let w = when.trim();
+    if w.is_empty() || w == "*" || w.eq_ignore_ascii_case("any") || w.eq_ignore_ascii_case("all") {
+        return Vec::new();
+    }
+    w.split([',', '|'])
+        .map(|s| s.trim())
+        .filter(|tok| !tok.is_empty() && !known.iter().any(|k| k.eq_ignore_ascii_case(tok)))
+        .map(|tok| tok.to_string())
+        .collect()
 }
 
 /// Check whether a rule is enabled for a given scope value.
@@ -584,6 +1391,9 @@ mod tests {
             &format!("{}/index.toml", conv.file_name().unwrap().to_string_lossy()),
             "repo",
             true,
+            false,
+            false,
+            false,
         );
         // only r1 should write; r2 filtered out by `when`
         assert!(actions.iter().any(|a| a.rule_id == "r1" && a.wrote));
@@ -591,4 +1401,694 @@ mod tests {
         assert!(root.join("out/repo.txt").exists());
         assert!(!root.join("out/lib.txt").exists());
     }
+
+    #[test]
+    fn test_check_sync_scopes_flags_unknown_when_tokens() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+        let conv = root.join("conv");
+        std::fs::create_dir_all(&conv).unwrap();
+        let pol = r#"
+    scopes = ["repo", "lib"]
+
+    [[sync]]
+    id = "ok"
+    source = "a.txt"
+    target = "a.txt"
+    when = "repo|LIB"
+
+    [[sync]]
+    id = "typo"
+    source = "a.txt"
+    target = "b.txt"
+    when = "reppo, lib"
+
+    [[sync]]
+    id = "any"
+    source = "a.txt"
+    target = "c.txt"
+    when = "*"
+    "#;
+        std::fs::write(conv.join("sync.toml"), pol).unwrap();
+        std::fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+
+        let (warnings, errs) = check_sync_scopes(root.to_str().unwrap(), "conv/index.toml");
+        assert!(errs.is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("'typo'"));
+        assert!(warnings[0].contains("reppo"));
+        assert!(!warnings[0].contains("when: reppo, lib"));
+    }
+
+    #[test]
+    fn test_merge_by_key_deep_merges_and_appends() {
+        let dst = vec![
+            serde_json::json!({"name": "test", "os": "linux", "env": {"CI": "1", "LOCAL": "x"}}),
+            serde_json::json!({"name": "local-only"}),
+        ];
+        let src = vec![
+            serde_json::json!({"name": "test", "os": "macos", "env": {"CI": "true"}}),
+            serde_json::json!({"name": "lint"}),
+            serde_json::json!("plain"),
+        ];
+        let merged = merge_by_key(&dst, &src, "name");
+        assert_eq!(
+            merged,
+            vec![
+                serde_json::json!({"name": "test", "os": "macos", "env": {"CI": "true", "LOCAL": "x"}}),
+                serde_json::json!({"name": "local-only"}),
+                serde_json::json!({"name": "lint"}),
+                serde_json::json!("plain"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_by_key_keeps_unmatched_destination_entries() {
+        let dst = vec![
+            serde_json::json!({"name": "a", "opt": 2}),
+            serde_json::json!({"name": "b"}),
+        ];
+        let src = vec![serde_json::json!({"name": "a", "opt": 1})];
+        assert_eq!(
+            merge_by_key(&dst, &src, "name"),
+            vec![
+                serde_json::json!({"name": "a", "opt": 1}),
+                serde_json::json!({"name": "b"}),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_mode_links_target_to_source() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+        let conv = root.join("conv");
+        std::fs::create_dir_all(conv.join("templates")).unwrap();
+        std::fs::write(conv.join("templates/a.txt"), b"hello").unwrap();
+        std::fs::write(
+            conv.join("sync.toml"),
+            "[[sync]]\nid = \"l\"\nsource = \"templates/a.txt\"\ntarget = \"out/a.txt\"\nwhen = \"repo\"\nmode = \"symlink\"\n",
+        )
+        .unwrap();
+        std::fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+        std::fs::create_dir_all(root.join("out")).unwrap();
+        std::fs::write(root.join("out/a.txt"), b"stale").unwrap();
+        let run = |write| {
+            run_sync(
+                root.to_str().unwrap(),
+                "conv/index.toml",
+                "repo",
+                write,
+                false,
+                false,
+                false,
+            )
+            .0
+        };
+
+        // Dry run leaves the regular file alone
+        assert!(run(false)[0].would_write);
+        assert!(!root.join("out/a.txt").is_symlink());
+
+        assert!(run(true)[0].wrote);
+        let dst = root.join("out/a.txt");
+        assert!(dst.is_symlink());
+        assert_eq!(
+            std::fs::read_link(&dst).unwrap(),
+            PathBuf::from("../conv/templates/a.txt")
+        );
+        assert_eq!(std::fs::read_to_string(&dst).unwrap(), "hello");
+        // Already linked: nothing pending
+        assert!(!run(false)[0].would_write);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_mode_backs_up_replaced_file() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+        let conv = root.join("conv");
+        std::fs::create_dir_all(conv.join("templates")).unwrap();
+        std::fs::write(conv.join("templates/a.txt"), b"hello").unwrap();
+        std::fs::write(
+            conv.join("sync.toml"),
+            "[[sync]]\nid = \"l\"\nsource = \"templates/a.txt\"\ntarget = \"out/a.txt\"\nwhen = \"repo\"\nmode = \"symlink\"\n",
+        )
+        .unwrap();
+        std::fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+        std::fs::write(root.join("rigra.toml"), "[sync.config.l]\nbackup = true\n").unwrap();
+        std::fs::create_dir_all(root.join("out")).unwrap();
+        std::fs::write(root.join("out/a.txt"), b"hand edit").unwrap();
+
+        let (actions, errs) = run_sync(
+            root.to_str().unwrap(),
+            "conv/index.toml",
+            "repo",
+            true,
+            false,
+            false,
+            false,
+        );
+        assert!(errs.is_empty());
+        assert!(actions[0].wrote);
+        assert!(root.join("out/a.txt").is_symlink());
+        assert_eq!(
+            std::fs::read_to_string(root.join("out/a.txt.rigra.bak")).unwrap(),
+            "hand edit"
+        );
+    }
+
+    #[test]
+    fn test_glob_source_copies_each_match_into_target_dir() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+        let conv = root.join("conv");
+        std::fs::create_dir_all(conv.join("templates/configs")).unwrap();
+        std::fs::write(conv.join("templates/configs/a.json"), b"{}").unwrap();
+        std::fs::write(conv.join("templates/configs/b.json"), b"[]").unwrap();
+        std::fs::write(conv.join("templates/configs/notes.md"), b"-").unwrap();
+        std::fs::write(
+            conv.join("sync.toml"),
+            "[[sync]]\nid = \"cfg\"\nsource = \"templates/configs/*.json\"\ntarget = \"configs\"\nwhen = \"repo\"\n",
+        )
+        .unwrap();
+        std::fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+
+        let (actions, _errs) = run_sync(
+            root.to_str().unwrap(),
+            "conv/index.toml",
+            "repo",
+            true,
+            false,
+            false,
+            false,
+        );
+        assert_eq!(actions.len(), 2);
+        assert!(actions.iter().all(|a| a.rule_id == "cfg" && a.wrote));
+        assert_eq!(
+            std::fs::read_to_string(root.join("configs/a.json")).unwrap(),
+            "{}"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("configs/b.json")).unwrap(),
+            "[]"
+        );
+        assert!(!root.join("configs/notes.md").exists());
+    }
+
+    #[test]
+    fn test_expand_source_reports_invalid_glob() {
+        let tmp = tempdir().unwrap();
+        let base = tmp.path();
+        std::fs::write(base.join("a.json"), b"{}").unwrap();
+        let mut errors = Vec::new();
+        let pairs = expand_source(base, "cfg", "*.json", Path::new("out"), &mut errors);
+        assert_eq!(pairs, [(base.join("a.json"), PathBuf::from("out/a.json"))]);
+        // Matching nothing only warns
+        assert!(expand_source(base, "cfg", "*.yaml", Path::new("out"), &mut errors).is_empty());
+        assert!(errors.is_empty());
+
+        assert!(expand_source(base, "cfg", "[*.json", Path::new("out"), &mut errors).is_empty());
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0]
+            .message
+            .starts_with("Invalid sync source glob for rule 'cfg': [*.json"));
+    }
+
+    #[test]
+    fn test_merge_reports_conflict_after_out_of_band_edit() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+        let src = root.join("conv/a.json");
+        let dst = root.join("out/a.json");
+        std::fs::create_dir_all(root.join("conv")).unwrap();
+        std::fs::write(&src, r#"{"a": 1}"#).unwrap();
+        let rule: SyncRule = toml::from_str(
+            "id = \"m\"\nsource = \"a.json\"\ntarget = \"out/a.json\"\nwhen = \"*\"\nformat = \"json\"\n",
+        )
+        .unwrap();
+        let client = config::SyncClientCfg {
+            target: None,
+            merge: Some(Default::default()),
+            backup: None,
+        };
+        let apply = |write, force| {
+            apply_sync(
+                root,
+                &rule,
+                &src,
+                &dst,
+                Some(&client),
+                write,
+                false,
+                force,
+                None,
+            )
+        };
+
+        assert!(apply(true, false).wrote);
+        // The sha256 of the merged output lives under the repo root
+        let chk = root.join(".rigra/sync/checksums/out__a.json.chk");
+        assert_eq!(std::fs::read_to_string(&chk).unwrap().len(), 64);
+        assert!(!root.join("conv/.rigra").exists());
+        // A hand edit since the last sync must not be overwritten silently
+        std::fs::write(&dst, r#"{"a": 2}"#).unwrap();
+        let out = apply(true, false);
+        assert!(out.conflict && !out.wrote);
+        assert_eq!(std::fs::read_to_string(&dst).unwrap(), r#"{"a": 2}"#);
+
+        let out = apply(true, true);
+        assert!(!out.conflict && out.wrote);
+        assert!(!apply(true, false).would_write);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failing_pre_hook_skips_rule() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+        let conv = root.join("conv");
+        std::fs::create_dir_all(conv.join("templates")).unwrap();
+        std::fs::write(conv.join("templates/a.txt"), b"hello").unwrap();
+        std::fs::write(
+            conv.join("sync.toml"),
+            "[[sync]]\nid = \"bad\"\nsource = \"templates/a.txt\"\ntarget = \"out/bad.txt\"\nwhen = \"repo\"\n\n[[sync]]\nid = \"ok\"\nsource = \"templates/a.txt\"\ntarget = \"out/ok.txt\"\nwhen = \"repo\"\n",
+        )
+        .unwrap();
+        std::fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+        std::fs::write(
+            root.join("rigra.toml"),
+            "[sync.hooks.pre]\nbad = [\"exit 3\"]\nok = [\"touch pre-ran\"]\n",
+        )
+        .unwrap();
+
+        let (actions, errs) = run_sync(
+            root.to_str().unwrap(),
+            "conv/index.toml",
+            "repo",
+            true,
+            false,
+            false,
+            false,
+        );
+        let by_rule = |id: &str| actions.iter().find(|a| a.rule_id == id).unwrap();
+        assert!(by_rule("ok").wrote && !by_rule("ok").hook_failed);
+        assert!(!by_rule("bad").wrote && by_rule("bad").hook_failed);
+        assert!(!root.join("out/bad.txt").exists());
+        assert!(root.join("out/ok.txt").exists());
+        assert!(root.join("pre-ran").exists());
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].message.contains("rule 'bad'"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failing_post_hook_is_reported_with_stderr() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+        let conv = root.join("conv");
+        std::fs::create_dir_all(conv.join("templates")).unwrap();
+        std::fs::write(conv.join("templates/a.txt"), b"hello").unwrap();
+        std::fs::write(
+            conv.join("sync.toml"),
+            "[[sync]]\nid = \"fmt\"\nsource = \"templates/a.txt\"\ntarget = \"out/a.txt\"\nwhen = \"repo\"\n",
+        )
+        .unwrap();
+        std::fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+        std::fs::write(
+            root.join("rigra.toml"),
+            "[sync.hooks.post]\nfmt = [\"echo broken >&2; exit 1\"]\n",
+        )
+        .unwrap();
+
+        let (actions, errs) = run_sync(
+            root.to_str().unwrap(),
+            "conv/index.toml",
+            "repo",
+            true,
+            false,
+            false,
+            false,
+        );
+        assert!(actions[0].wrote && actions[0].hook_failed);
+        assert_eq!(errs.len(), 1);
+        let msg = &errs[0].message;
+        assert!(msg.contains("rule 'fmt'") && msg.contains("exit 1") && msg.contains("broken"));
+    }
+
+    #[test]
+    fn test_parallel_sync_matches_sequential() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+        let conv = root.join("conv");
+        std::fs::create_dir_all(conv.join("templates")).unwrap();
+        let mut pol = String::new();
+        for i in 0..12 {
+            std::fs::write(conv.join(format!("templates/t{i}.txt")), format!("v{i}")).unwrap();
+            pol.push_str(&format!(
+                "[[sync]]\nid = \"r{i}\"\nsource = \"templates/t{i}.txt\"\ntarget = \"out/t{i}.txt\"\nwhen = \"*\"\n\n"
+            ));
+        }
+        std::fs::write(conv.join("sync.toml"), pol).unwrap();
+        std::fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+        let summarize = |parallel| {
+            let (actions, errs) = run_sync_with(
+                root.to_str().unwrap(),
+                "conv/index.toml",
+                "repo",
+                false,
+                false,
+                false,
+                false,
+                parallel,
+            );
+            assert!(errs.is_empty());
+            actions
+                .iter()
+                .map(|a| (a.rule_id.clone(), a.target.clone(), a.would_write, a.bytes))
+                .collect::<Vec<_>>()
+        };
+        let sequential = summarize(false);
+        assert_eq!(sequential.len(), 12);
+        assert_eq!(sequential[0].0, "r0");
+        assert_eq!(summarize(true), sequential);
+    }
+
+    #[test]
+    fn test_overlapping_targets_are_flagged() {
+        let apply = |dst: &str| Planned::Apply {
+            rule: 0,
+            src: PathBuf::from("src"),
+            dst: PathBuf::from(dst),
+        };
+        let planned = vec![
+            apply("out/a.txt"),
+            apply("out/dir"),
+            apply("out/b.txt"),
+            apply("out/dir/x.txt"),
+            apply("out/a.txt"),
+            apply("out/dir2/y.txt"),
+        ];
+        assert_eq!(
+            overlapping_targets(&planned),
+            [true, true, false, true, true, false]
+        );
+    }
+
+    #[test]
+    fn test_parallel_sync_applies_overlapping_targets_in_policy_order() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+        let conv = root.join("conv");
+        std::fs::create_dir_all(conv.join("templates/dir")).unwrap();
+        std::fs::write(conv.join("templates/dir/x.txt"), "from dir").unwrap();
+        std::fs::write(conv.join("templates/x.txt"), "from file").unwrap();
+        std::fs::write(
+            conv.join("sync.toml"),
+            "[[sync]]\nid = \"dir\"\nsource = \"templates/dir\"\ntarget = \"out\"\nwhen = \"*\"\n\n[[sync]]\nid = \"file\"\nsource = \"templates/x.txt\"\ntarget = \"out/x.txt\"\nwhen = \"*\"\n",
+        )
+        .unwrap();
+        std::fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+        let (actions, errs) = run_sync_with(
+            root.to_str().unwrap(),
+            "conv/index.toml",
+            "repo",
+            true,
+            false,
+            false,
+            false,
+            true,
+        );
+        assert!(errs.is_empty());
+        assert_eq!(actions.len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(root.join("out/x.txt")).unwrap(),
+            "from file"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_keeps_executable_bit() {
+        use std::os::unix::fs::PermissionsExt;
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+        let conv = root.join("conv");
+        std::fs::create_dir_all(conv.join("templates")).unwrap();
+        let script = conv.join("templates/run.sh");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::write(
+            conv.join("sync.toml"),
+            "[[sync]]\nid = \"s\"\nsource = \"templates/run.sh\"\ntarget = \"scripts/run.sh\"\nwhen = \"*\"\n",
+        )
+        .unwrap();
+        std::fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+
+        run_sync(
+            root.to_str().unwrap(),
+            "conv/index.toml",
+            "repo",
+            true,
+            false,
+            false,
+            false,
+        );
+        let mode = std::fs::metadata(root.join("scripts/run.sh"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o111, 0o111);
+    }
+
+    #[test]
+    fn test_dir_sync_skips_gitignored_files_unless_included() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+        let conv = root.join("conv");
+        let tpl = conv.join("templates/app");
+        std::fs::create_dir_all(tpl.join("src")).unwrap();
+        std::fs::create_dir_all(tpl.join("node_modules/dep")).unwrap();
+        std::fs::write(tpl.join(".gitignore"), "node_modules/\n*.log\n").unwrap();
+        std::fs::write(tpl.join("src/main.ts"), "x").unwrap();
+        std::fs::write(tpl.join("debug.log"), "noise").unwrap();
+        std::fs::write(tpl.join("node_modules/dep/index.js"), "dep").unwrap();
+        std::fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+        let sync = |extra: &str| {
+            std::fs::write(
+                conv.join("sync.toml"),
+                format!(
+                    "[[sync]]\nid = \"app\"\nsource = \"templates/app\"\ntarget = \"app\"\nwhen = \"*\"\n{extra}"
+                ),
+            )
+            .unwrap();
+            run_sync(
+                root.to_str().unwrap(),
+                "conv/index.toml",
+                "repo",
+                true,
+                false,
+                false,
+                false,
+            );
+        };
+
+        sync("");
+        assert!(root.join("app/src/main.ts").exists());
+        assert!(root.join("app/.gitignore").exists());
+        assert!(!root.join("app/debug.log").exists());
+        assert!(!root.join("app/node_modules").exists());
+
+        sync("include_ignored = true\n");
+        assert!(root.join("app/debug.log").exists());
+        assert!(root.join("app/node_modules/dep/index.js").exists());
+    }
+
+    #[test]
+    fn test_backup_keeps_previous_target_on_overwrite() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+        let conv = root.join("conv");
+        std::fs::create_dir_all(conv.join("templates")).unwrap();
+        std::fs::write(conv.join("templates/a.txt"), "new").unwrap();
+        std::fs::write(
+            conv.join("sync.toml"),
+            "[[sync]]\nid = \"s\"\nsource = \"templates/a.txt\"\ntarget = \"out/a.txt\"\nwhen = \"*\"\n",
+        )
+        .unwrap();
+        std::fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+        std::fs::write(root.join("rigra.toml"), "[sync.config.s]\nbackup = true\n").unwrap();
+        std::fs::create_dir_all(root.join("out")).unwrap();
+        std::fs::write(root.join("out/a.txt"), "hand edit").unwrap();
+
+        let sync = || {
+            run_sync(
+                root.to_str().unwrap(),
+                "conv/index.toml",
+                "repo",
+                true,
+                false,
+                false,
+                false,
+            )
+        };
+        sync();
+        let bak = root.join("out/a.txt.rigra.bak");
+        assert_eq!(
+            std::fs::read_to_string(root.join("out/a.txt")).unwrap(),
+            "new"
+        );
+        assert_eq!(std::fs::read_to_string(&bak).unwrap(), "hand edit");
+
+        // Unchanged targets are not backed up again
+        std::fs::remove_file(&bak).unwrap();
+        sync();
+        assert!(!bak.exists());
+
+        // Merged JSON targets are backed up the same way
+        let src = conv.join("b.json");
+        let dst = root.join("out/b.json");
+        std::fs::write(&src, r#"{"a": 1}"#).unwrap();
+        std::fs::write(&dst, r#"{"a": 0}"#).unwrap();
+        let rule: SyncRule = toml::from_str(
+            "id = \"m\"\nsource = \"b.json\"\ntarget = \"out/b.json\"\nwhen = \"*\"\nformat = \"json\"\n",
+        )
+        .unwrap();
+        let client = config::SyncClientCfg {
+            target: None,
+            merge: Some(Default::default()),
+            backup: Some(true),
+        };
+        let out = apply_sync(
+            root,
+            &rule,
+            &src,
+            &dst,
+            Some(&client),
+            true,
+            false,
+            false,
+            None,
+        );
+        assert!(out.wrote && out.merged);
+        assert_eq!(
+            std::fs::read_to_string(root.join("out/b.json.rigra.bak")).unwrap(),
+            r#"{"a": 0}"#
+        );
+    }
+
+    #[test]
+    fn test_prune_removes_targets_of_removed_rules_and_files() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+        let conv = root.join("conv");
+        std::fs::create_dir_all(conv.join("templates/dir")).unwrap();
+        std::fs::write(conv.join("templates/a.txt"), "a").unwrap();
+        std::fs::write(conv.join("templates/dir/x.txt"), "x").unwrap();
+        std::fs::write(conv.join("templates/dir/y.txt"), "y").unwrap();
+        std::fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+        let rule_a = "[[sync]]\nid = \"a\"\nsource = \"templates/a.txt\"\ntarget = \"out/a.txt\"\nwhen = \"*\"\n";
+        let rule_d = "[[sync]]\nid = \"d\"\nsource = \"templates/dir\"\ntarget = \"out/dir\"\nwhen = \"*\"\n";
+        std::fs::write(conv.join("sync.toml"), format!("{}\n{}", rule_a, rule_d)).unwrap();
+        // A file sync never wrote must survive pruning
+        std::fs::create_dir_all(root.join("out/dir")).unwrap();
+        std::fs::write(root.join("out/dir/mine.txt"), "mine").unwrap();
+
+        let sync = |write, prune| {
+            run_sync(
+                root.to_str().unwrap(),
+                "conv/index.toml",
+                "repo",
+                write,
+                false,
+                false,
+                prune,
+            )
+        };
+        sync(true, false);
+        assert!(root.join("out/a.txt").is_file());
+        assert!(root.join(".rigra/sync/manifest.json").is_file());
+
+        // Drop rule `a` and shrink the directory source
+        std::fs::write(conv.join("sync.toml"), rule_d).unwrap();
+        std::fs::remove_file(conv.join("templates/dir/y.txt")).unwrap();
+
+        // Without --prune stale files are left (and stay recorded)
+        let (actions, _) = sync(true, false);
+        assert!(actions.iter().all(|a| !a.pruned));
+        assert!(root.join("out/a.txt").is_file());
+
+        // Dry run reports what would go
+        let (actions, _) = sync(false, true);
+        let pending: Vec<&str> = actions
+            .iter()
+            .filter(|a| a.pruned && !a.wrote)
+            .map(|a| a.rule_id.as_str())
+            .collect();
+        assert_eq!(pending, ["a", "d"]);
+        assert!(root.join("out/a.txt").is_file());
+
+        let (actions, errs) = sync(true, true);
+        assert!(errs.is_empty());
+        assert_eq!(actions.iter().filter(|a| a.pruned && a.wrote).count(), 2);
+        assert!(!root.join("out/a.txt").exists());
+        assert!(!root.join("out/dir/y.txt").exists());
+        assert!(root.join("out/dir/x.txt").is_file());
+        assert!(root.join("out/dir/mine.txt").is_file());
+
+        // Nothing left to prune
+        let (actions, _) = sync(true, true);
+        assert!(actions.iter().all(|a| !a.pruned));
+    }
+
+    #[test]
+    fn test_prune_keeps_unresolved_sources_and_edited_files() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+        let conv = root.join("conv");
+        std::fs::create_dir_all(conv.join("templates")).unwrap();
+        std::fs::write(conv.join("templates/a.txt"), "a").unwrap();
+        std::fs::write(conv.join("templates/b.txt"), "b").unwrap();
+        std::fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+        let rule_a = "[[sync]]\nid = \"a\"\nsource = \"templates/a.txt\"\ntarget = \"out/a.txt\"\nwhen = \"*\"\n";
+        let rule_b = "[[sync]]\nid = \"b\"\nsource = \"templates/b.txt\"\ntarget = \"out/b.txt\"\nwhen = \"*\"\n";
+        std::fs::write(conv.join("sync.toml"), format!("{}\n{}", rule_a, rule_b)).unwrap();
+
+        let sync = |force| {
+            run_sync(
+                root.to_str().unwrap(),
+                "conv/index.toml",
+                "repo",
+                true,
+                false,
+                force,
+                true,
+            )
+        };
+        sync(false);
+        assert!(root.join("out/a.txt").is_file());
+
+        // A missing source does not make its earlier target stale
+        std::fs::remove_file(conv.join("templates/a.txt")).unwrap();
+        let (actions, errs) = sync(false);
+        assert!(actions.iter().all(|a| !a.pruned));
+        assert!(errs.iter().any(|e| e.message.contains("matched no files")));
+        assert!(root.join("out/a.txt").is_file());
+
+        // Dropping the rule makes it stale, but a hand edit keeps it
+        std::fs::write(conv.join("sync.toml"), rule_b).unwrap();
+        std::fs::write(root.join("out/a.txt"), "edited").unwrap();
+        let (actions, errs) = sync(false);
+        assert!(actions.iter().all(|a| !a.pruned));
+        assert!(errs.iter().any(|e| e.message.contains("--force")));
+        assert!(root.join("out/a.txt").is_file());
+
+        let (actions, errs) = sync(true);
+        assert!(errs.is_empty());
+        assert_eq!(actions.iter().filter(|a| a.pruned && a.wrote).count(), 1);
+        assert!(!root.join("out/a.txt").exists());
+        assert!(root.join("out/b.txt").is_file());
+    }
 }
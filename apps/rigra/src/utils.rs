@@ -1,8 +1,11 @@
 //! Utility helpers for paths and JSON navigation.
 
+use crate::models::RunError;
 use owo_colors::OwoColorize;
 use serde_json::Value as Json;
+use std::io::IsTerminal;
 use std::path::Path;
+use std::sync::atomic::{AtomicU8, Ordering};
 
 /// Return a path relative to the current working directory when possible.
 pub fn rel_to_wd(p: &Path) -> String {
@@ -16,40 +19,427 @@ pub fn rel_to_wd(p: &Path) -> String {
 }
 
 /// Get nested value by a simple JSONPath-like string: `$.a.b.c` or `a.b.c`.
+///
+/// Array elements are addressed as `a[0]` or `a.0`; out-of-range, negative,
+/// or non-numeric indices resolve to `None` like a missing key. Keys holding
+/// `.`, `[` or `]` are quoted: `$.exports["./utils"]`.
 pub fn get_json_path<'a>(json: &'a Json, path: &str) -> Option<&'a Json> {
+    let mut cur = json;
+    for seg in path_segments(path) {
+        cur = match cur {
+            Json::Object(map) => map.get(seg)?,
+            Json::Array(items) => items.get(seg.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(cur)
+}
+
+/// Resolve path segments that may be `*` (every array element or object
+/// value) into the concrete `(path, value)` pairs they match, in document
+/// order. Paths are rendered as `$.a.b[0]` (keys quoted per `path_key`);
+/// without `*` this yields at most one match, like `get_json_path`.
+pub fn get_json_paths<'a>(json: &'a Json, segs: &[&str]) -> Vec<(String, &'a Json)> {
+    let mut cur: Vec<(String, &Json)> = vec![("$".to_string(), json)];
+    for &seg in segs {
+        let mut next = Vec::new();
+        for (at, v) in cur {
+            match (seg, v) {
+                ("*", Json::Object(map)) => next.extend(
+                    map.iter()
+                        .map(|(k, c)| (format!("{}{}", at, path_key(k)), c)),
+                ),
+                ("*", Json::Array(items)) => next.extend(
+                    items
+                        .iter()
+                        .enumerate()
+                        .map(|(i, c)| (format!("{}[{}]", at, i), c)),
+                ),
+                (_, Json::Array(items)) => {
+                    if let Some(c) = seg.parse::<usize>().ok().and_then(|i| items.get(i)) {
+                        next.push((format!("{}[{}]", at, seg), c));
+                    }
+                }
+                (_, Json::Object(map)) => {
+                    if let Some(c) = map.get(seg) {
+                        next.push((format!("{}{}", at, path_key(seg)), c));
+                    }
+                }
+                _ => {}
+            }
+        }
+        cur = next;
+    }
+    cur
+}
+
+/// Split a `$.a.b[0]` style path into segments (`["a", "b", "0"]`). A
+/// quoted `["a.b"]` segment is taken verbatim.
+pub fn path_segments(path: &str) -> Vec<&str> {
     let trimmed = path.trim();
-    let p = if let Some(stripped) = trimmed.strip_prefix("$") {
+    let mut p = if let Some(stripped) = trimmed.strip_prefix("$") {
         stripped.trim_start_matches('.')
     } else {
         trimmed
     };
-    let mut cur = json;
-    if p.is_empty() {
-        return Some(cur);
+    let mut segs = Vec::new();
+    while let Some(at) = p.find("[\"") {
+        let Some(len) = p[at + 2..].find("\"]") else {
+            break;
+        };
+        segs.extend(p[..at].split(['.', '[', ']']).filter(|s| !s.is_empty()));
+        segs.push(&p[at + 2..at + 2 + len]);
+        p = &p[at + 2 + len + 2..];
     }
-    for seg in p.split('.') {
-        if seg.is_empty() {
-            continue;
-        }
+    segs.extend(p.split(['.', '[', ']']).filter(|s| !s.is_empty()));
+    segs
+}
+
+/// A key as a path segment: `.key`, or `["key"]` when it holds `.`, `[`
+/// or `]` (or is empty).
+pub fn path_key(key: &str) -> String {
+    if key.is_empty() || key.contains(['.', '[', ']']) {
+        format!("[\"{}\"]", key)
+    } else {
+        format!(".{}", key)
+    }
+}
+
+/// Set a nested value by the same path syntax as `get_json_path`, creating
+/// intermediate objects. Returns false when a non-object blocks the path.
+pub fn set_json_path(json: &mut Json, path: &str, value: Json) -> bool {
+    let segs = path_segments(path);
+    let Some((last, parents)) = segs.split_last() else {
+        *json = value;
+        return true;
+    };
+    let mut cur = json;
+    for seg in parents {
         match cur {
             Json::Object(map) => {
-                if let Some(v) = map.get(seg) {
-                    cur = v;
-                } else {
-                    return None;
+                cur = map
+                    .entry(seg.to_string())
+                    .or_insert_with(|| Json::Object(Default::default()));
+            }
+            _ => return false,
+        }
+    }
+    match cur {
+        Json::Object(map) => {
+            map.insert(last.to_string(), value);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Locate the key for a JSON path (`$.a.b`) in source text.
+///
+/// Returns `(line, column, width)` of the key token (1-based line/column in
+/// chars). When the full path is absent (e.g. a missing required field), the
+/// deepest existing ancestor key is located instead. Array elements count as
+/// numeric segments. Multi-document paths (`[doc N] ...`) are not resolved.
+pub fn locate_json_path(text: &str, path: &str) -> Option<(usize, usize, usize)> {
+    let p = path.trim();
+    if p.starts_with('[') {
+        return None;
+    }
+    let segs = path_segments(p);
+    (1..=segs.len())
+        .rev()
+        .find_map(|n| locate_segments(text, &segs[..n]))
+}
+
+fn locate_segments(text: &str, target: &[&str]) -> Option<(usize, usize, usize)> {
+    // Each frame: Some(key) for objects (None until a key is read), or the
+    // element index for arrays; `expect_key` tracks object key position.
+    enum Frame {
+        Obj {
+            key: Option<String>,
+            expect_key: bool,
+        },
+        Arr(usize),
+    }
+    let mut stack: Vec<Frame> = Vec::new();
+    let (mut line, mut col) = (1usize, 0usize);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        col += 1;
+        match c {
+            '\n' => {
+                line += 1;
+                col = 0;
+            }
+            '{' => stack.push(Frame::Obj {
+                key: None,
+                expect_key: true,
+            }),
+            '[' => stack.push(Frame::Arr(0)),
+            '}' | ']' => {
+                stack.pop();
+            }
+            ',' => match stack.last_mut() {
+                Some(Frame::Obj { expect_key, .. }) => *expect_key = true,
+                Some(Frame::Arr(i)) => *i += 1,
+                None => {}
+            },
+            ':' => {
+                if let Some(Frame::Obj { expect_key, .. }) = stack.last_mut() {
+                    *expect_key = false;
                 }
             }
-            _ => {
-                return None;
+            '"' => {
+                let (start_line, start_col) = (line, col);
+                let mut s = String::new();
+                let mut width = 1;
+                while let Some(n) = chars.next() {
+                    col += 1;
+                    width += 1;
+                    match n {
+                        '\\' => {
+                            if let Some(e) = chars.next() {
+                                col += 1;
+                                width += 1;
+                                s.push(e);
+                            }
+                        }
+                        '"' => break,
+                        _ => s.push(n),
+                    }
+                }
+                if let Some(Frame::Obj { key, expect_key }) = stack.last_mut() {
+                    if *expect_key {
+                        *key = Some(s);
+                        let at_target = stack.len() == target.len()
+                            && stack.iter().zip(target).all(|(f, t)| match f {
+                                Frame::Obj { key: Some(k), .. } => k == t,
+                                Frame::Obj { key: None, .. } => false,
+                                Frame::Arr(i) => i.to_string() == *t,
+                            });
+                        if at_target {
+                            return Some((start_line, start_col, width));
+                        }
+                    }
+                }
             }
+            _ => {}
         }
     }
-    Some(cur)
+    None
+}
+
+/// Whether `text` contains unresolved git merge conflict markers: a
+/// `<<<<<<<` line followed later by a `>>>>>>>` line.
+pub fn has_conflict_markers(text: &str) -> bool {
+    let is_marker = |line: &str, m: &str| {
+        line.strip_prefix(m)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\r']))
+    };
+    let mut opened = false;
+    for line in text.lines() {
+        if is_marker(line, "<<<<<<<") {
+            opened = true;
+        } else if opened && is_marker(line, ">>>>>>>") {
+            return true;
+        }
+    }
+    false
 }
 
-/// Whether colors should be used for global messages (checks NO_COLOR).
+/// Error for a target skipped because of unresolved merge conflicts.
+pub fn conflict_markers_error(path: &Path) -> RunError {
+    RunError {
+        message: format!(
+            "File contains unresolved merge conflict markers: {}",
+            path.to_string_lossy()
+        ),
+    }
+}
+
+/// Path (`$.a.b`) of the first object key that appears twice in the same
+/// object of JSON `text`, if any. `serde_json` keeps only the last value for
+/// a repeated key, so callers that rewrite files must check this first.
+/// Text that is not valid JSON yields `None`.
+pub fn find_duplicate_key(text: &str) -> Option<String> {
+    use serde::de::DeserializeSeed;
+    let found = std::cell::RefCell::new(None);
+    let mut de = serde_json::Deserializer::from_str(text);
+    let _ = DuplicateKeySeed {
+        path: "$".to_string(),
+        found: &found,
+    }
+    .deserialize(&mut de);
+    found.into_inner()
+}
+
+/// Walks a JSON value, recording the path of the first repeated object key.
+struct DuplicateKeySeed<'a> {
+    path: String,
+    found: &'a std::cell::RefCell<Option<String>>,
+}
+
+impl<'de> serde::de::DeserializeSeed<'de> for DuplicateKeySeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, d: D) -> Result<(), D::Error> {
+        d.deserialize_any(self)
+    }
+}
+
+impl<'de> serde::de::Visitor<'de> for DuplicateKeySeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut i = 0;
+        while seq
+            .next_element_seed(DuplicateKeySeed {
+                path: format!("{}[{}]", self.path, i),
+                found: self.found,
+            })?
+            .is_some()
+        {
+            i += 1;
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut seen = std::collections::HashSet::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let path = format!("{}.{}", self.path, key);
+            if !seen.insert(key) {
+                *self.found.borrow_mut() = Some(path);
+                return Err(serde::de::Error::custom("duplicate key"));
+            }
+            map.next_value_seed(DuplicateKeySeed {
+                path,
+                found: self.found,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `path` lies within `max_depth` levels of `root`.
+///
+/// Depth is counted from the repo root: `root/a.json` is depth 1,
+/// `root/pkg/a.json` is depth 2. `None` means unlimited.
+pub fn within_depth(root: &Path, path: &Path, max_depth: Option<usize>) -> bool {
+    match max_depth {
+        None => true,
+        Some(max) => {
+            let rel = path.strip_prefix(root).unwrap_or(path);
+            rel.components().count() <= max
+        }
+    }
+}
+
+/// Today's date in UTC as `YYYY-MM-DD`.
+pub fn today_utc() -> String {
+    date_utc(std::time::SystemTime::now())
+}
+
+/// A point in time as a UTC `YYYY-MM-DD` date.
+pub fn date_utc(t: std::time::SystemTime) -> String {
+    let secs = t
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    civil_date((secs / 86_400) as i64)
+}
+
+/// Convert days since 1970-01-01 to a `YYYY-MM-DD` proleptic Gregorian date.
+fn civil_date(days: i64) -> String {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// `--color` choice: 0 = auto, 1 = always, 2 = never.
+static COLOR_CHOICE: AtomicU8 = AtomicU8::new(0);
+
+/// Record the `--color` flag (`auto`, `always`, or `never`).
+pub fn set_color_choice(choice: &str) {
+    let v = match choice {
+        "always" => 1,
+        "never" => 2,
+        _ => 0,
+    };
+    COLOR_CHOICE.store(v, Ordering::Relaxed);
+}
+
+/// Whether to color output written to a stream, `is_tty` telling whether
+/// that stream is a terminal. Reads `CLICOLOR_FORCE`, `NO_COLOR`, `CLICOLOR`,
+/// and the `--color` flag; see `resolve_color` for precedence.
+pub fn color_enabled(is_tty: bool) -> bool {
+    let flag = match COLOR_CHOICE.load(Ordering::Relaxed) {
+        1 => Some(true),
+        2 => Some(false),
+        _ => None,
+    };
+    resolve_color(
+        std::env::var("CLICOLOR_FORCE").ok().as_deref(),
+        flag,
+        std::env::var_os("NO_COLOR").is_some(),
+        std::env::var("CLICOLOR").ok().as_deref(),
+        is_tty,
+    )
+}
+
+/// Color precedence: `CLICOLOR_FORCE` (set and not `0`) > `--color
+/// always|never` > `NO_COLOR` (set) > `CLICOLOR=0` > terminal detection.
+fn resolve_color(
+    force: Option<&str>,
+    flag: Option<bool>,
+    no_color: bool,
+    clicolor: Option<&str>,
+    is_tty: bool,
+) -> bool {
+    if force.is_some_and(|v| !v.is_empty() && v != "0") {
+        return true;
+    }
+    if let Some(on) = flag {
+        return on;
+    }
+    if no_color || clicolor == Some("0") {
+        return false;
+    }
+    is_tty
+}
+
+/// Whether colors should be used for global messages (written to stderr).
 pub fn use_colors_global() -> bool {
-    std::env::var_os("NO_COLOR").is_none()
+    color_enabled(std::io::stderr().is_terminal())
 }
 
 /// Standardized error prefix for human-readable output.
@@ -90,6 +480,15 @@ pub fn warn_prefix() -> String {
     }
 }
 
+/// Standardized debug prefix for `RIGRA_LOG=debug` tracing.
+pub fn debug_prefix() -> String {
+    if use_colors_global() {
+        "· ⟦debug⟧".dimmed().to_string()
+    } else {
+        "· ⟦debug⟧".to_string()
+    }
+}
+
 /// Colored severity tags without icons, controlled by caller-provided color flag.
 pub fn tag_error(use_color: bool) -> String {
     if use_color {
@@ -161,4 +560,157 @@ mod tests {
         assert!(get_json_path(&data, "nested.missing").is_none());
         assert!(get_json_path(&data, "$.nested.a.b.c").is_none());
     }
+
+    #[test]
+    fn test_get_json_path_array_indices() {
+        let data = serde_json::json!({
+            "a": [{"b": 1}, {"b": 2}],
+            "workspaces": ["packages/*"]
+        });
+        assert_eq!(get_json_path(&data, "$.a[0].b").unwrap(), &Json::from(1));
+        assert_eq!(get_json_path(&data, "a.1.b").unwrap(), &Json::from(2));
+        assert_eq!(
+            get_json_path(&data, "$.workspaces[0]").unwrap(),
+            &Json::from("packages/*")
+        );
+        assert!(get_json_path(&data, "$.a[2]").is_none());
+        assert!(get_json_path(&data, "$.a[-1]").is_none());
+        assert!(get_json_path(&data, "$.a[x].b").is_none());
+        assert!(get_json_path(&data, "$.workspaces[0].b").is_none());
+    }
+
+    #[test]
+    fn test_get_json_paths_expands_wildcards() {
+        let data = serde_json::json!({
+            "scripts": {"build": "tsc", "test": 1},
+            "contributors": [{"email": "a"}, {"name": "b"}, {"email": "c"}]
+        });
+        let paths = |p: &str| -> Vec<String> {
+            get_json_paths(&data, &path_segments(p))
+                .into_iter()
+                .map(|(at, _)| at)
+                .collect()
+        };
+        assert_eq!(
+            paths("$.scripts.*"),
+            vec!["$.scripts.build", "$.scripts.test"]
+        );
+        assert_eq!(
+            paths("contributors[*].email"),
+            vec!["$.contributors[0].email", "$.contributors[2].email"]
+        );
+        assert_eq!(
+            paths("$.contributors.1.name"),
+            vec!["$.contributors[1].name"]
+        );
+        assert!(paths("$.scripts.build.*").is_empty());
+        assert!(paths("$.missing.*").is_empty());
+    }
+
+    #[test]
+    fn test_quoted_keys_round_trip_through_paths() {
+        let data = serde_json::json!({
+            "exports": {"./utils": {"types": "u.d.ts"}, "[x]": 1, "main": 2}
+        });
+        let paths: Vec<String> = get_json_paths(&data, &["exports", "*"])
+            .into_iter()
+            .map(|(at, _)| at)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "$.exports[\"./utils\"]",
+                "$.exports[\"[x]\"]",
+                "$.exports.main"
+            ]
+        );
+        assert_eq!(
+            path_segments("$.exports[\"./utils\"].types"),
+            vec!["exports", "./utils", "types"]
+        );
+        for p in &paths {
+            assert!(get_json_path(&data, p).is_some());
+        }
+        let mut data = data;
+        assert!(set_json_path(
+            &mut data,
+            "exports[\"./utils\"].types",
+            Json::from("x")
+        ));
+        assert_eq!(data["exports"]["./utils"]["types"], "x");
+    }
+
+    #[test]
+    fn test_locate_json_path_finds_key_or_nearest_ancestor() {
+        let text = "{\n  \"name\": \"x\",\n  \"repo\": {\n    \"url\": \"u\",\n    \"dir\": \"d\"\n  },\n  \"list\": [{\"a\": 1}, {\"a\": 2}]\n}";
+        assert_eq!(locate_json_path(text, "$.name"), Some((2, 3, 6)));
+        assert_eq!(locate_json_path(text, "repo.dir"), Some((5, 5, 5)));
+        assert_eq!(locate_json_path(text, "$.list.1.a"), Some((7, 23, 3)));
+        assert_eq!(locate_json_path(text, "$.list[1].a"), Some((7, 23, 3)));
+        // Missing leaf falls back to its parent key
+        assert_eq!(locate_json_path(text, "$.repo.missing"), Some((3, 3, 6)));
+        assert_eq!(locate_json_path(text, "$.absent"), None);
+        assert_eq!(locate_json_path(text, "[doc 1] $.name"), None);
+    }
+
+    #[test]
+    fn test_has_conflict_markers_requires_open_and_close() {
+        let conflicted = "{\n<<<<<<< HEAD\n  \"a\": 1\n=======\n  \"a\": 2\n>>>>>>> main\n}\n";
+        assert!(has_conflict_markers(conflicted));
+        assert!(has_conflict_markers(&conflicted.replace('\n', "\r\n")));
+        assert!(!has_conflict_markers(
+            "{\n  \"a\": \"<<<<<<< not a marker\"\n}"
+        ));
+        assert!(!has_conflict_markers("=======\n>>>>>>> main\n"));
+    }
+
+    #[test]
+    fn test_within_depth_counts_from_root() {
+        let root = Path::new("/repo");
+        assert!(within_depth(root, Path::new("/repo/a.json"), Some(1)));
+        assert!(!within_depth(root, Path::new("/repo/pkg/a.json"), Some(1)));
+        assert!(within_depth(root, Path::new("/repo/pkg/a.json"), Some(2)));
+        assert!(within_depth(root, Path::new("/repo/x/y/z/a.json"), None));
+    }
+
+    #[test]
+    fn test_find_duplicate_key_reports_nested_path() {
+        assert_eq!(find_duplicate_key(r#"{"a": 1, "b": 2}"#), None);
+        assert_eq!(
+            find_duplicate_key(r#"{"a": [{"x": 1, "y": 2, "x": 3}]}"#).as_deref(),
+            Some("$.a[0].x")
+        );
+        // Same key in sibling objects is fine
+        assert_eq!(find_duplicate_key(r#"[{"x": 1}, {"x": 2}]"#), None);
+        assert_eq!(find_duplicate_key("{not json"), None);
+    }
+
+    #[test]
+    fn test_resolve_color_precedence() {
+        // CLICOLOR_FORCE beats everything, including --color never
+        assert!(resolve_color(
+            Some("1"),
+            Some(false),
+            true,
+            Some("0"),
+            false
+        ));
+        assert!(!resolve_color(Some("0"), None, false, None, false));
+        // --color beats NO_COLOR and CLICOLOR
+        assert!(resolve_color(None, Some(true), true, Some("0"), false));
+        assert!(!resolve_color(None, Some(false), false, None, true));
+        // NO_COLOR and CLICOLOR=0 disable colors on a terminal
+        assert!(!resolve_color(None, None, true, None, true));
+        assert!(!resolve_color(None, None, false, Some("0"), true));
+        // Otherwise follow terminal detection
+        assert!(resolve_color(None, None, false, Some("1"), true));
+        assert!(!resolve_color(None, None, false, None, false));
+    }
+
+    #[test]
+    fn test_civil_date_from_epoch_days() {
+        assert_eq!(civil_date(0), "1970-01-01");
+        assert_eq!(civil_date(11_016), "2000-02-29");
+        assert_eq!(civil_date(20_089), "2025-01-01");
+    }
 }
@@ -1,4 +1,4 @@
-use rigra::{format, lint, sync};
+use rigra::{config, conv, format, lint, output, sync};
 use std::fs;
 
 // Integration-style tests using temp dirs
@@ -50,13 +50,15 @@ meta = []
     .unwrap();
 
     // Run format preview
-    let results = format::run_format(
+    let (results, _errors) = format::run_format(
         root.to_str().unwrap(),
         &format!("{}/index.toml", conv.file_name().unwrap().to_string_lossy()),
         false,
         false,
         false,
         None,
+        true,
+        None,
         &std::collections::HashMap::new(),
         &std::collections::HashMap::new(),
         &std::collections::HashMap::new(),
@@ -112,12 +114,14 @@ top = [["name"],["version"],["license"]]
     .unwrap();
 
     // Case A: write=true (no diff/check) ⇒ file should be rewritten, no preview
-    let results_write = rigra::format::run_format(
+    let (results_write, _errors) = rigra::format::run_format(
         root.to_str().unwrap(),
         &format!("{}/index.toml", conv.file_name().unwrap().to_string_lossy()),
         true,  // write
         false, // capture_old
         false, // strict_linebreak
+        None,  // max_width
+        true,  // normalize_strings
         None,
         &std::collections::HashMap::new(),
         &std::collections::HashMap::new(),
@@ -144,13 +148,15 @@ top = [["name"],["version"],["license"]]
     .unwrap();
 
     // Case B: diff/check override write=false ⇒ preview present, file unchanged
-    let results_diff = rigra::format::run_format(
+    let (results_diff, _errors) = rigra::format::run_format(
         root.to_str().unwrap(),
         &format!("{}/index.toml", conv.file_name().unwrap().to_string_lossy()),
         false, // effective write becomes false when diff/check true
         true,  // capture_old to enable diff
         false,
         None,
+        true,
+        None,
         &std::collections::HashMap::new(),
         &std::collections::HashMap::new(),
         &std::collections::HashMap::new(),
@@ -200,11 +206,14 @@ sync = "sync.toml"
     )
     .unwrap();
 
-    let actions = sync::run_sync(
+    let (actions, _errors) = sync::run_sync(
         root.to_str().unwrap(),
         &format!("{}/index.toml", conv.file_name().unwrap().to_string_lossy()),
         "repo",
         true,
+        false,
+        false,
+        false,
     );
     assert!(actions.iter().any(|a| a.rule_id == "r1" && a.wrote));
     assert!(actions.iter().all(|a| a.rule_id != "r2"));
@@ -267,12 +276,14 @@ scripts = "keep"
     .unwrap();
 
     // Run format with strict linebreaks enabled
-    let results = format::run_format(
+    let (results, _errors) = format::run_format(
         root.to_str().unwrap(),
         &format!("{}/index.toml", conv.file_name().unwrap().to_string_lossy()),
         false,                             // write
         true,                              // capture_old for potential diffs
         true,                              // strict_linebreak
+        None,                              // max_width
+        true,                              // normalize_strings
         None,                              // lb_between_groups_override
         &std::collections::HashMap::new(), // lb_before_fields_override
         &std::collections::HashMap::new(), // lb_in_fields_override
@@ -336,10 +347,13 @@ level = "warn"
     )
     .unwrap();
 
-    let res = lint::run_lint(
+    let (res, _errors) = lint::run_lint(
         root.to_str().unwrap(),
         &format!("{}/index.toml", conv.file_name().unwrap().to_string_lossy()),
         "repo",
+        None,
+        false,
+        false,
         &std::collections::HashMap::new(),
     );
     assert!(res
@@ -396,12 +410,14 @@ license = "none"
     // Overrides: enable between_groups and force license=keep
     let mut before_over = std::collections::HashMap::new();
     before_over.insert("license".to_string(), "keep".to_string());
-    let results = format::run_format(
+    let (results, _errors) = format::run_format(
         root.to_str().unwrap(),
         &format!("{}/index.toml", conv.file_name().unwrap().to_string_lossy()),
         false,
         false,
         true,         // strict linebreaks on
+        None,         // max_width
+        true,         // normalize_strings
         Some(true),   // override between_groups
         &before_over, // override before_fields
         &std::collections::HashMap::new(),
@@ -426,3 +442,1405 @@ license = "none"
     }
     assert!(found, "license line not found");
 }
+
+#[test]
+fn lint_json_output_is_stable_across_runs() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(&conv).unwrap();
+    fs::create_dir_all(root.join("pkgs/a")).unwrap();
+    fs::create_dir_all(root.join("pkgs/b")).unwrap();
+
+    fs::write(
+        conv.join("index.toml"),
+        r#"
+[[rules]]
+id = "pkgjson"
+patterns = ["package.json", "pkgs/*/package.json"]
+policy = "policy.toml"
+"#,
+    )
+    .unwrap();
+
+    // Type fields are a HashMap; many entries make iteration order vary
+    fs::write(
+        conv.join("policy.toml"),
+        r#"
+[[checks]]
+kind = "type"
+fields = { a = "string", b = "string", c = "string", d = "string", e = "string", f = "string", g = "string", h = "string" }
+
+[[checks]]
+kind = "required"
+fields = ["name", "version"]
+"#,
+    )
+    .unwrap();
+
+    let doc = r#"{"a":1,"b":2,"c":3,"d":4,"e":5,"f":6,"g":7,"h":8}"#;
+    fs::write(root.join("package.json"), doc).unwrap();
+    fs::write(root.join("pkgs/a/package.json"), doc).unwrap();
+    fs::write(root.join("pkgs/b/package.json"), doc).unwrap();
+
+    let run = || {
+        let (res, _errors) = lint::run_lint(
+            root.to_str().unwrap(),
+            "conv/index.toml",
+            "repo",
+            None,
+            false,
+            false,
+            &std::collections::HashMap::new(),
+        );
+        serde_json::to_string(&output::compose_lint_json(&res, "lint")).unwrap()
+    };
+    let first = run();
+    for _ in 0..5 {
+        assert_eq!(first, run());
+    }
+
+    // Issues are sorted by (file, path, rule)
+    let (res, _errors) = lint::run_lint(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        "repo",
+        None,
+        false,
+        false,
+        &std::collections::HashMap::new(),
+    );
+    let keys: Vec<_> = res
+        .issues
+        .iter()
+        .map(|i| (i.file.clone(), i.path.clone(), i.rule.clone()))
+        .collect();
+    let mut sorted = keys.clone();
+    sorted.sort();
+    assert_eq!(keys, sorted);
+    assert_eq!(res.issues.len(), 3 * 10);
+}
+
+#[test]
+fn lint_multi_doc_reports_document_index() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(&conv).unwrap();
+
+    fs::write(
+        conv.join("index.toml"),
+        r#"
+[[rules]]
+id = "logs"
+patterns = ["log.ndjson", "deploy.yaml"]
+policy = "policy.toml"
+multi_doc = true
+"#,
+    )
+    .unwrap();
+    fs::write(
+        conv.join("policy.toml"),
+        r#"
+[[checks]]
+kind = "required"
+fields = ["level"]
+"#,
+    )
+    .unwrap();
+    fs::write(
+        root.join("log.ndjson"),
+        "{\"level\":\"info\"}\n{\"msg\":\"x\"}\n\n{\"level\":\"warn\"}\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("deploy.yaml"),
+        "level: debug\n---\nname: a\n---\nname: b\n",
+    )
+    .unwrap();
+
+    let (res, _errors) = lint::run_lint(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        "repo",
+        None,
+        false,
+        false,
+        &std::collections::HashMap::new(),
+    );
+    let paths: Vec<_> = res
+        .issues
+        .iter()
+        .map(|i| {
+            let name = std::path::Path::new(&i.file)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string();
+            (name, i.path.clone())
+        })
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            ("deploy.yaml".to_string(), "[doc 2] $.level".to_string()),
+            ("deploy.yaml".to_string(), "[doc 3] $.level".to_string()),
+            ("log.ndjson".to_string(), "[doc 2] $.level".to_string()),
+        ]
+    );
+    assert_eq!(res.summary.files, 2);
+}
+
+#[test]
+fn lint_cache_is_busted_by_policy_edits() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(&conv).unwrap();
+    fs::write(
+        conv.join("index.toml"),
+        r#"
+[[rules]]
+id = "cfg"
+patterns = ["cfg.json"]
+policy = "policy.toml"
+"#,
+    )
+    .unwrap();
+    let required = |field: &str| {
+        fs::write(
+            conv.join("policy.toml"),
+            format!(
+                "[[checks]]\nkind = \"required\"\nfields = [\"{}\"]\n",
+                field
+            ),
+        )
+        .unwrap();
+    };
+    required("name");
+    fs::write(root.join("cfg.json"), r#"{"version": "1.0.0"}"#).unwrap();
+
+    let run = || {
+        let (res, errors) = lint::run_lint(
+            root.to_str().unwrap(),
+            "conv/index.toml",
+            "repo",
+            None,
+            true,
+            false,
+            &std::collections::HashMap::new(),
+        );
+        assert!(errors.is_empty());
+        res.issues
+            .iter()
+            .map(|i| i.message.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let first = run();
+    assert_eq!(first.len(), 1);
+
+    // Tamper with the stored entry: an unchanged run must serve it from cache
+    let cache_path = root.join(".rigra/cache/lint.json");
+    let stored = fs::read_to_string(&cache_path).unwrap();
+    fs::write(&cache_path, stored.replace(&first[0], "from cache")).unwrap();
+    assert_eq!(run(), vec!["from cache".to_string()]);
+
+    // Editing the policy busts the entry although cfg.json is unchanged
+    required("version");
+    assert!(run().is_empty());
+}
+
+#[test]
+fn test_policy_runs_checks_against_inline_json() {
+    let tmp = tempfile::tempdir().unwrap();
+    let pol = tmp.path().join("policy.toml");
+    fs::write(
+        &pol,
+        r#"
+[[checks]]
+kind = "required"
+fields = ["name", "version"]
+
+[order]
+top = [["name", "version"]]
+"#,
+    )
+    .unwrap();
+
+    let (res, errors) = lint::run_policy_test(&pol, r#"{"version": "1", "x": 1}"#, "<input>");
+    assert!(errors.is_empty());
+    assert_eq!(res.summary.files, 1);
+    assert!(res
+        .issues
+        .iter()
+        .all(|i| i.file == "<input>" && i.path == "$.name"));
+    assert_eq!(res.issues.len(), 1);
+
+    let (_res, errors) = lint::run_policy_test(&pol, "{not json", "<input>");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].message.starts_with("Input is not valid JSON"));
+}
+
+#[test]
+fn test_policy_counts_deprecated_enum_values_as_warnings() {
+    let tmp = tempfile::tempdir().unwrap();
+    let pol = tmp.path().join("policy.toml");
+    fs::write(
+        &pol,
+        r#"
+[[checks]]
+kind = "enum"
+field = "type"
+values = ["module"]
+deprecated = ["commonjs"]
+"#,
+    )
+    .unwrap();
+
+    let (res, errors) = lint::run_policy_test(&pol, r#"{"type": "commonjs"}"#, "<input>");
+    assert!(errors.is_empty());
+    assert_eq!(res.issues[0].severity, "warn");
+    assert_eq!(res.summary.warnings, 1);
+    assert_eq!(res.summary.errors, 0);
+    assert_eq!(res.summary.infos, 0);
+}
+
+#[test]
+fn lint_pattern_regex_from_file() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(conv.join("patterns")).unwrap();
+    fs::write(
+        conv.join("index.toml"),
+        r#"
+[[rules]]
+id = "pkg"
+patterns = ["package.json"]
+policy = "policy.toml"
+
+[[rules]]
+id = "broken"
+patterns = ["package.json"]
+policy = "broken.toml"
+"#,
+    )
+    .unwrap();
+    fs::write(conv.join("patterns/name.txt"), "^@scope/[a-z-]+$\n").unwrap();
+    fs::write(
+        conv.join("policy.toml"),
+        r#"
+[[checks]]
+kind = "pattern"
+field = "name"
+regex_from = "patterns/name.txt"
+message = "{{actual}} must match {{pattern}}"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        conv.join("broken.toml"),
+        r#"
+[[checks]]
+kind = "pattern"
+field = "name"
+regex_from = "patterns/missing.txt"
+"#,
+    )
+    .unwrap();
+    fs::write(root.join("package.json"), r#"{"name": "Bad_Name"}"#).unwrap();
+
+    let (res, _errors) = lint::run_lint(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        "repo",
+        None,
+        false,
+        false,
+        &std::collections::HashMap::new(),
+    );
+    let msgs: Vec<_> = res.issues.iter().map(|i| i.message.as_str()).collect();
+    assert!(msgs.contains(&"Bad_Name must match ^@scope/[a-z-]+$"));
+    // The broken policy is a convention problem, not a target issue
+    assert_eq!(res.diagnostics.len(), 1);
+    assert_eq!(res.diagnostics[0].kind, "regex-file");
+    assert!(res.diagnostics[0]
+        .message
+        .starts_with("regex_from file not found:"));
+    assert!(res.diagnostics[0].message.contains("missing.txt"));
+    assert!(!res.passed);
+
+    // A pattern without any regex is a rule misconfiguration
+    fs::write(
+        conv.join("broken.toml"),
+        r#"
+[[checks]]
+kind = "pattern"
+field = "name"
+"#,
+    )
+    .unwrap();
+    let (res, _errors) = lint::run_lint(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        "repo",
+        None,
+        false,
+        false,
+        &std::collections::HashMap::new(),
+    );
+    assert_eq!(res.diagnostics.len(), 1);
+    assert_eq!(res.diagnostics[0].kind, "rule-config");
+    assert_eq!(
+        res.diagnostics[0].message,
+        "Pattern check for 'name' sets neither regex nor regex_from"
+    );
+}
+
+#[test]
+fn sync_dry_run_json_reports_would_write() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(conv.join("templates")).unwrap();
+    fs::write(conv.join("templates/t.txt"), b"hello").unwrap();
+    fs::write(
+        conv.join("sync.toml"),
+        r#"
+[[sync]]
+id = "new"
+source = "templates/t.txt"
+target = "out/new.txt"
+when = "repo"
+
+[[sync]]
+id = "same"
+source = "templates/t.txt"
+target = "out/same.txt"
+when = "repo"
+"#,
+    )
+    .unwrap();
+    fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+    fs::create_dir_all(root.join("out")).unwrap();
+    fs::write(root.join("out/same.txt"), b"hello").unwrap();
+
+    let (actions, errors) = sync::run_sync(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        "repo",
+        false,
+        true,
+        false,
+        false,
+    );
+    let out = output::compose_sync_json(&actions, &errors);
+    let results = out["results"].as_array().unwrap();
+    let by_rule = |id: &str| results.iter().find(|r| r["rule"] == id).unwrap().clone();
+
+    let new = by_rule("new");
+    assert_eq!(new["wouldWrite"], true);
+    assert_eq!(new["wrote"], false);
+    assert_eq!(new["skipped"], false);
+    let same = by_rule("same");
+    assert_eq!(same["wouldWrite"], false);
+    assert_eq!(same["skipped"], true);
+    assert_eq!(new["merged"], false);
+    assert_eq!(new["bytes"], 5);
+    assert_eq!(out["summary"]["wouldWrite"], 1);
+    assert_eq!(out["summary"]["skipped"], 1);
+    assert_eq!(out["summary"]["files"], 1);
+    assert_eq!(out["summary"]["bytes"], 5);
+    let diff = new["diff"].as_array().unwrap();
+    assert_eq!(diff.len(), 1);
+    assert!(diff[0]["target"].as_str().unwrap().ends_with("out/new.txt"));
+    let lines = &diff[0]["hunks"][0]["lines"];
+    assert_eq!(lines[0]["op"], "insert");
+    assert_eq!(lines[0]["text"], "hello");
+    assert!(same["diff"].is_null());
+    assert!(!root.join("out/new.txt").exists());
+}
+
+#[test]
+fn lint_fail_fast_reports_only_first_failing_file() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(&conv).unwrap();
+    fs::create_dir_all(root.join("pkgs")).unwrap();
+    fs::write(
+        conv.join("index.toml"),
+        r#"
+[[rules]]
+id = "pkg"
+patterns = ["pkgs/*.json"]
+policy = "policy.toml"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        conv.join("policy.toml"),
+        r#"
+[[checks]]
+kind = "required"
+fields = ["name"]
+
+[[checks]]
+kind = "required"
+fields = ["description"]
+level = "warning"
+"#,
+    )
+    .unwrap();
+    for (f, body) in [
+        ("a.json", r#"{"name": "a"}"#),
+        ("b.json", r#"{}"#),
+        ("c.json", r#"{}"#),
+    ] {
+        fs::write(root.join("pkgs").join(f), body).unwrap();
+    }
+
+    let run = |fail_fast: bool| {
+        lint::run_lint(
+            root.to_str().unwrap(),
+            "conv/index.toml",
+            "repo",
+            None,
+            false,
+            fail_fast,
+            &std::collections::HashMap::new(),
+        )
+        .0
+    };
+    let full = run(false);
+    assert_eq!(full.summary.errors, 2);
+
+    let fast = run(true);
+    assert_eq!(fast.summary.errors, 1);
+    assert_eq!(fast.summary.files, 1);
+    let file = &fast.issues[0].file;
+    assert!(file.ends_with("b.json") || file.ends_with("c.json"));
+    assert!(fast.issues.iter().all(|i| &i.file == file));
+
+    // Warnings alone never trip fail-fast
+    fs::write(root.join("pkgs/b.json"), r#"{"name": "b"}"#).unwrap();
+    fs::write(root.join("pkgs/c.json"), r#"{"name": "c"}"#).unwrap();
+    let warn_only = run(true);
+    assert_eq!(warn_only.summary.errors, 0);
+    assert_eq!(warn_only.summary.warnings, 3);
+}
+
+#[test]
+fn lint_fail_fast_keeps_order_and_check_issues_of_the_same_file() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(&conv).unwrap();
+    fs::create_dir_all(root.join("pkgs")).unwrap();
+    fs::write(
+        conv.join("index.toml"),
+        r#"
+[[rules]]
+id = "pkg"
+patterns = ["pkgs/*.json"]
+policy = "policy.toml"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        conv.join("policy.toml"),
+        r#"
+[order]
+top = [["name"],["version"]]
+
+[[checks]]
+kind = "required"
+fields = ["name"]
+"#,
+    )
+    .unwrap();
+    // a.json fails both the order and the check; b.json only the order
+    fs::write(root.join("pkgs/a.json"), r#"{"x": 1, "version": "1"}"#).unwrap();
+    fs::write(root.join("pkgs/b.json"), r#"{"version": "1", "name": "b"}"#).unwrap();
+
+    let (fast, _errors) = lint::run_lint(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        "repo",
+        None,
+        false,
+        true,
+        &std::collections::HashMap::new(),
+    );
+    assert_eq!(fast.summary.files, 1);
+    assert_eq!(fast.issues.len(), 2);
+    let file = &fast.issues[0].file;
+    assert!(file.ends_with("a.json"));
+    assert!(fast.issues.iter().all(|i| &i.file == file));
+}
+
+#[test]
+fn format_jsonc_applies_trailing_comma_policy() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(&conv).unwrap();
+    fs::write(
+        conv.join("index.toml"),
+        r#"
+[[rules]]
+id = "tsconfig"
+patterns = ["tsconfig.jsonc", "plain.json"]
+policy = "policy.toml"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        conv.join("policy.toml"),
+        r#"
+trailing_comma = "multiline"
+
+[order]
+top = [["extends", "compilerOptions"]]
+"#,
+    )
+    .unwrap();
+    // Existing trailing commas are tolerated on JSONC input
+    fs::write(
+        root.join("tsconfig.jsonc"),
+        "{\"compilerOptions\": {\"strict\": true,}, \"extends\": \"base\",}",
+    )
+    .unwrap();
+    fs::write(root.join("plain.json"), "{\"compilerOptions\": {}}").unwrap();
+
+    let (_results, errors) = format::run_format(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        true,
+        false,
+        false,
+        None,
+        true,
+        None,
+        &std::collections::HashMap::new(),
+        &std::collections::HashMap::new(),
+        &std::collections::HashMap::new(),
+    );
+    assert!(errors.is_empty());
+    let jsonc = fs::read_to_string(root.join("tsconfig.jsonc")).unwrap();
+    assert_eq!(
+        jsonc,
+        "{\n  \"extends\": \"base\",\n  \"compilerOptions\": {\n    \"strict\": true,\n  },\n}"
+    );
+    // Plain JSON never gets trailing commas
+    let plain = fs::read_to_string(root.join("plain.json")).unwrap();
+    assert!(!plain.contains(",\n}"));
+
+    // Comments cannot be kept, so commented files are reported, not rewritten
+    let commented = "{\n  // base config\n  \"compilerOptions\": {},\n  \"extends\": \"base\"\n}\n";
+    fs::write(root.join("tsconfig.jsonc"), commented).unwrap();
+    let (_results, errors) = format::run_format(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        true,
+        false,
+        false,
+        None,
+        true,
+        None,
+        &std::collections::HashMap::new(),
+        &std::collections::HashMap::new(),
+        &std::collections::HashMap::new(),
+    );
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0]
+        .message
+        .starts_with("Comments are not supported in"));
+    assert_eq!(
+        fs::read_to_string(root.join("tsconfig.jsonc")).unwrap(),
+        commented
+    );
+
+    // Ignore markers would need the comments kept too; say so explicitly
+    let marked = "{\n  // rigra-ignore\n  \"compilerOptions\": {},\n  \"extends\": \"base\"\n}\n";
+    fs::write(root.join("tsconfig.jsonc"), marked).unwrap();
+    let (_results, errors) = format::run_format(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        true,
+        false,
+        false,
+        None,
+        true,
+        None,
+        &std::collections::HashMap::new(),
+        &std::collections::HashMap::new(),
+        &std::collections::HashMap::new(),
+    );
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0]
+        .message
+        .starts_with("rigra-ignore markers are not supported in"));
+    assert_eq!(
+        fs::read_to_string(root.join("tsconfig.jsonc")).unwrap(),
+        marked
+    );
+}
+
+#[test]
+fn format_and_lint_skip_files_with_conflict_markers() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(&conv).unwrap();
+    fs::write(
+        conv.join("index.toml"),
+        r#"
+[[rules]]
+id = "pkg"
+patterns = ["package.json"]
+policy = "policy.toml"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        conv.join("policy.toml"),
+        r#"
+[order]
+top = [["name", "version"]]
+"#,
+    )
+    .unwrap();
+    let conflicted = "{\n<<<<<<< HEAD\n  \"version\": \"1.0.0\",\n=======\n  \"version\": \"1.1.0\",\n>>>>>>> main\n  \"name\": \"x\"\n}\n";
+    fs::write(root.join("package.json"), conflicted).unwrap();
+
+    let (results, errors) = format::run_format(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        true,
+        false,
+        false,
+        None,
+        true,
+        None,
+        &std::collections::HashMap::new(),
+        &std::collections::HashMap::new(),
+        &std::collections::HashMap::new(),
+    );
+    assert!(results.iter().all(|r| !r.changed));
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0]
+        .message
+        .starts_with("File contains unresolved merge conflict markers"));
+    // The file is left untouched
+    assert_eq!(
+        fs::read_to_string(root.join("package.json")).unwrap(),
+        conflicted
+    );
+
+    let (res, errors) = lint::run_lint(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        "repo",
+        None,
+        false,
+        false,
+        &std::collections::HashMap::new(),
+    );
+    assert_eq!(res.summary.files, 0);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].message.contains("package.json"));
+}
+
+#[test]
+fn format_and_lint_report_invalid_preserve_matching_regex() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(&conv).unwrap();
+    fs::write(
+        conv.join("index.toml"),
+        r#"
+[[rules]]
+id = "pkg"
+patterns = ["package.json"]
+policy = "policy.toml"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        conv.join("policy.toml"),
+        r#"
+[order]
+top = [["name"], ["version"]]
+preserve_matching = ["^x-("]
+"#,
+    )
+    .unwrap();
+    let original = "{\n  \"x-extra\": 1,\n  \"version\": \"1.0.0\",\n  \"name\": \"x\"\n}\n";
+    fs::write(root.join("package.json"), original).unwrap();
+
+    let (results, errors) = format::run_format(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        true,
+        false,
+        false,
+        None,
+        true,
+        None,
+        &std::collections::HashMap::new(),
+        &std::collections::HashMap::new(),
+        &std::collections::HashMap::new(),
+    );
+    assert!(results.is_empty());
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0]
+        .message
+        .starts_with("Rule 'pkg': Invalid order.preserve_matching regex '^x-('"));
+    assert_eq!(
+        fs::read_to_string(root.join("package.json")).unwrap(),
+        original
+    );
+
+    let (res, _errors) = lint::run_lint(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        "repo",
+        None,
+        false,
+        false,
+        &std::collections::HashMap::new(),
+    );
+    assert!(res.issues.is_empty());
+    assert_eq!(res.diagnostics.len(), 1);
+    assert_eq!(res.diagnostics[0].kind, "rule-config");
+}
+
+#[test]
+fn format_refuses_to_rewrite_files_with_duplicate_keys() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(&conv).unwrap();
+    fs::write(
+        conv.join("index.toml"),
+        r#"
+[[rules]]
+id = "pkg"
+patterns = ["package.json"]
+policy = "policy.toml"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        conv.join("policy.toml"),
+        r#"
+[order]
+top = [["name", "version"]]
+"#,
+    )
+    .unwrap();
+    let dup = "{\n  \"version\": \"1.0.0\",\n  \"name\": \"x\",\n  \"version\": \"2.0.0\"\n}\n";
+    fs::write(root.join("package.json"), dup).unwrap();
+
+    let (results, errors) = format::run_format(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        true,
+        false,
+        false,
+        None,
+        true,
+        None,
+        &std::collections::HashMap::new(),
+        &std::collections::HashMap::new(),
+        &std::collections::HashMap::new(),
+    );
+    assert!(results.iter().all(|r| !r.changed));
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].message.starts_with("Duplicate key $.version in"));
+    assert_eq!(fs::read_to_string(root.join("package.json")).unwrap(), dup);
+}
+
+#[test]
+fn sync_overwrite_false_only_scaffolds_missing_targets() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(conv.join("templates")).unwrap();
+    fs::write(conv.join("templates/t.txt"), b"template").unwrap();
+    fs::write(
+        conv.join("sync.toml"),
+        r#"
+[[sync]]
+id = "existing"
+source = "templates/t.txt"
+target = "out/existing.txt"
+when = "repo"
+overwrite = false
+
+[[sync]]
+id = "missing"
+source = "templates/t.txt"
+target = "out/missing.txt"
+when = "repo"
+overwrite = false
+
+[[sync]]
+id = "default"
+source = "templates/t.txt"
+target = "out/default.txt"
+when = "repo"
+"#,
+    )
+    .unwrap();
+    fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+    fs::create_dir_all(root.join("out")).unwrap();
+    fs::write(root.join("out/existing.txt"), b"customized").unwrap();
+    fs::write(root.join("out/default.txt"), b"customized").unwrap();
+
+    let (actions, errors) = sync::run_sync(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        "repo",
+        true,
+        false,
+        false,
+        false,
+    );
+    assert!(errors.is_empty());
+    let out = output::compose_sync_json(&actions, &errors);
+    let results = out["results"].as_array().unwrap();
+    let by_rule = |id: &str| results.iter().find(|r| r["rule"] == id).unwrap().clone();
+    assert_eq!(by_rule("existing")["skipped"], true);
+    assert_eq!(by_rule("missing")["wrote"], true);
+    assert_eq!(by_rule("default")["wrote"], true);
+    let read = |p: &str| fs::read_to_string(root.join(p)).unwrap();
+    assert_eq!(read("out/existing.txt"), "customized");
+    assert_eq!(read("out/missing.txt"), "template");
+    assert_eq!(read("out/default.txt"), "template");
+}
+
+#[test]
+fn conv_index_resolves_policies_inside_convention_cache() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    // Package a convention: index in a subpath, policies beside and above it
+    let pkg = root.join("build/conv-pkg");
+    fs::create_dir_all(pkg.join("sub")).unwrap();
+    fs::create_dir_all(pkg.join("shared")).unwrap();
+    fs::write(
+        pkg.join("sub/index.toml"),
+        r#"
+[[rules]]
+id = "pkg"
+patterns = ["package.json"]
+policy = "policy.toml"
+
+[[rules]]
+id = "pkg.order"
+patterns = ["package.json"]
+policy = "../shared/order.toml"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        pkg.join("sub/policy.toml"),
+        r#"
+[[checks]]
+kind = "required"
+fields = ["license"]
+"#,
+    )
+    .unwrap();
+    fs::write(
+        pkg.join("shared/order.toml"),
+        r#"
+[order]
+top = [["name", "version"]]
+"#,
+    )
+    .unwrap();
+    let tarball = root.join("build/conv.tar.gz");
+    let st = std::process::Command::new("tar")
+        .arg("-czf")
+        .arg(&tarball)
+        .arg("-C")
+        .arg(root.join("build"))
+        .arg("conv-pkg")
+        .status()
+        .unwrap();
+    assert!(st.success());
+    conv::install(
+        root,
+        "demo@v1.0.0",
+        &format!("file:{}", tarball.to_string_lossy()),
+        None,
+    )
+    .unwrap();
+
+    fs::write(
+        root.join("rigra.toml"),
+        "index = \"conv:demo@v1.0.0:sub/index.toml\"\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("package.json"),
+        "{\"version\": \"1.0.0\", \"name\": \"x\"}",
+    )
+    .unwrap();
+
+    let eff = config::resolve_effective(
+        Some(root.to_str().unwrap()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    assert!(eff.index.contains(".rigra/conv/"));
+    let root_str = eff.repo_root.to_string_lossy().to_string();
+    let (res, errors) = lint::run_lint(
+        &root_str,
+        &eff.index,
+        "repo",
+        None,
+        false,
+        false,
+        &eff.pattern_overrides,
+    );
+    assert!(errors.is_empty());
+    let rules: Vec<&str> = res.issues.iter().map(|i| i.rule.as_str()).collect();
+    assert!(rules.contains(&"pkg"), "rules: {:?}", rules);
+    assert!(rules.contains(&"pkg.order"), "rules: {:?}", rules);
+    assert!(res
+        .issues
+        .iter()
+        .all(|i| !i.message.contains("Policy file")));
+
+    let (results, errors) = format::run_format(
+        &root_str,
+        &eff.index,
+        false,
+        false,
+        false,
+        None,
+        true,
+        None,
+        &std::collections::HashMap::new(),
+        &std::collections::HashMap::new(),
+        &eff.pattern_overrides,
+    );
+    assert!(errors.is_empty());
+    assert!(results.iter().any(|r| r.changed));
+}
+
+#[test]
+fn lint_bundle_uses_inline_policies() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    fs::write(
+        root.join("package.json"),
+        "{\"version\": \"1\", \"name\": \"x\"}",
+    )
+    .unwrap();
+    let bundle = r#"
+[[rules]]
+id = "pkg"
+patterns = ["package.json"]
+policy = "pkg"
+
+[policies.pkg]
+[[policies.pkg.checks]]
+kind = "required"
+fields = ["license"]
+
+[policies.pkg.order]
+top = [["name", "version"]]
+"#;
+    let (res, errors) = lint::run_lint_bundle(
+        root.to_str().unwrap(),
+        bundle,
+        "repo",
+        None,
+        false,
+        false,
+        &std::collections::HashMap::new(),
+    );
+    assert!(errors.is_empty());
+    assert_eq!(res.summary.files, 1);
+    let msgs: Vec<&str> = res.issues.iter().map(|i| i.message.as_str()).collect();
+    assert!(msgs.iter().any(|m| m.contains("license")), "{:?}", msgs);
+    assert!(res.issues.iter().any(|i| i.rule == "pkg" && i.fixable));
+
+    // The same bundle saved as an index file also drives format
+    fs::write(root.join("bundle.toml"), bundle).unwrap();
+    let (results, errors) = format::run_format(
+        root.to_str().unwrap(),
+        "bundle.toml",
+        false,
+        false,
+        false,
+        None,
+        true,
+        None,
+        &std::collections::HashMap::new(),
+        &std::collections::HashMap::new(),
+        &std::collections::HashMap::new(),
+    );
+    assert!(errors.is_empty());
+    assert!(results[0].changed);
+}
+
+#[test]
+fn lint_max_issues_budget_demotes_until_exceeded() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(&conv).unwrap();
+    fs::create_dir_all(root.join("pkgs/a")).unwrap();
+    fs::create_dir_all(root.join("pkgs/b")).unwrap();
+    fs::write(
+        conv.join("policy.toml"),
+        r#"
+[[checks]]
+kind = "required"
+fields = ["description"]
+"#,
+    )
+    .unwrap();
+    fs::write(root.join("pkgs/a/package.json"), "{}").unwrap();
+    fs::write(root.join("pkgs/b/package.json"), "{}").unwrap();
+    let run = |max: usize| {
+        fs::write(
+            conv.join("index.toml"),
+            format!(
+                r#"
+[[rules]]
+id = "pkg.description"
+patterns = ["pkgs/*/package.json"]
+policy = "policy.toml"
+max_issues = {}
+"#,
+                max
+            ),
+        )
+        .unwrap();
+        lint::run_lint(
+            root.to_str().unwrap(),
+            "conv/index.toml",
+            "repo",
+            None,
+            false,
+            false,
+            &std::collections::HashMap::new(),
+        )
+        .0
+    };
+
+    let within = run(2);
+    assert_eq!(within.issues.len(), 2);
+    assert_eq!(within.summary.errors, 0);
+    assert_eq!(within.summary.infos, 2);
+
+    let over = run(1);
+    assert_eq!(over.summary.errors, 2);
+    assert_eq!(over.summary.infos, 0);
+}
+
+#[test]
+fn lint_escalate_after_switches_warn_to_error() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(&conv).unwrap();
+    fs::write(
+        conv.join("policy.toml"),
+        r#"
+[[checks]]
+kind = "required"
+fields = ["description"]
+
+[[checks]]
+kind = "required"
+fields = ["license"]
+level = "info"
+"#,
+    )
+    .unwrap();
+    fs::write(root.join("package.json"), "{}").unwrap();
+    let run = |date: &str| {
+        fs::write(
+            conv.join("index.toml"),
+            format!(
+                r#"
+[[rules]]
+id = "pkg.description"
+patterns = ["package.json"]
+policy = "policy.toml"
+escalate_after = "{}"
+"#,
+                date
+            ),
+        )
+        .unwrap();
+        lint::run_lint(
+            root.to_str().unwrap(),
+            "conv/index.toml",
+            "repo",
+            None,
+            false,
+            false,
+            &std::collections::HashMap::new(),
+        )
+        .0
+    };
+
+    // The explicitly leveled `license` check keeps `info` throughout
+    let grace = run("9999-12-31");
+    assert_eq!(grace.issues[0].path, "$.description");
+    assert_eq!(grace.issues[0].severity, "warn");
+    assert_eq!(grace.issues[1].severity, "info");
+    assert_eq!(grace.summary.errors, 0);
+    assert_eq!(grace.summary.warnings, 1);
+    assert_eq!(grace.summary.infos, 1);
+
+    let past = run("2000-01-01");
+    assert_eq!(past.issues[0].severity, "error");
+    assert_eq!(past.issues[1].severity, "info");
+    assert_eq!(past.summary.errors, 1);
+    assert_eq!(past.summary.warnings, 0);
+    assert_eq!(past.summary.infos, 1);
+
+    let bad = run("2025-02-30");
+    assert!(bad.issues.is_empty());
+    assert_eq!(bad.diagnostics[0].kind, "rule-config");
+    assert!(bad.diagnostics[0]
+        .message
+        .contains("Invalid escalate_after '2025-02-30'"));
+}
+
+#[test]
+fn sync_rules_respect_if_exists_and_unless_exists() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(conv.join("templates")).unwrap();
+    fs::write(conv.join("templates/t.txt"), b"template").unwrap();
+    fs::write(
+        conv.join("sync.toml"),
+        r#"
+[[sync]]
+id = "jest"
+source = "templates/t.txt"
+target = "jest.config.js"
+when = "repo"
+if_exists = "package.json"
+unless_exists = "jest.config.ts"
+
+[[sync]]
+id = "cargo"
+source = "templates/t.txt"
+target = "rustfmt.toml"
+when = "repo"
+if_exists = "Cargo.toml"
+"#,
+    )
+    .unwrap();
+    fs::write(conv.join("index.toml"), "sync = \"sync.toml\"\n").unwrap();
+    fs::write(root.join("package.json"), "{}").unwrap();
+
+    let run = || {
+        let (actions, errors) = sync::run_sync(
+            root.to_str().unwrap(),
+            "conv/index.toml",
+            "repo",
+            false,
+            false,
+            false,
+            false,
+        );
+        assert!(errors.is_empty());
+        actions.into_iter().map(|a| a.rule_id).collect::<Vec<_>>()
+    };
+    assert_eq!(run(), vec!["jest"]);
+    fs::write(root.join("jest.config.ts"), "").unwrap();
+    assert!(run().is_empty());
+}
+
+#[test]
+fn lint_consistent_field_reports_cross_file_outliers() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(&conv).unwrap();
+    for (dir, license) in [("a", "MIT"), ("b", "Apache-2.0"), ("c", "MIT")] {
+        fs::create_dir_all(root.join("pkgs").join(dir)).unwrap();
+        fs::write(
+            root.join("pkgs").join(dir).join("package.json"),
+            format!(r#"{{"license": "{}"}}"#, license),
+        )
+        .unwrap();
+    }
+    fs::write(
+        conv.join("policy.toml"),
+        r#"
+[[checks]]
+kind = "consistentField"
+field = "license"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        conv.join("index.toml"),
+        r#"
+[[rules]]
+id = "pkg.license"
+patterns = ["pkgs/*/package.json"]
+policy = "policy.toml"
+"#,
+    )
+    .unwrap();
+    // Cached runs still evaluate the cross-file check
+    for _ in 0..2 {
+        let (res, errors) = lint::run_lint(
+            root.to_str().unwrap(),
+            "conv/index.toml",
+            "repo",
+            None,
+            true,
+            false,
+            &std::collections::HashMap::new(),
+        );
+        assert!(errors.is_empty());
+        assert_eq!(res.issues.len(), 1);
+        assert!(res.issues[0].file.ends_with("pkgs/b/package.json"));
+        assert_eq!(res.summary.errors, 1);
+    }
+}
+
+#[test]
+fn lint_result_reports_passed_and_per_rule_status() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(&conv).unwrap();
+    fs::write(root.join("package.json"), r#"{"name": "x"}"#).unwrap();
+    fs::write(
+        conv.join("name.toml"),
+        "[[checks]]\nkind = \"required\"\nfields = [\"name\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        conv.join("license.toml"),
+        "[[checks]]\nkind = \"required\"\nfields = [\"license\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        conv.join("index.toml"),
+        r#"
+[[rules]]
+id = "pkg.name"
+patterns = ["package.json"]
+policy = "name.toml"
+
+[[rules]]
+id = "pkg.license"
+patterns = ["package.json"]
+policy = "license.toml"
+"#,
+    )
+    .unwrap();
+    let (res, _errors) = lint::run_lint(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        "repo",
+        None,
+        false,
+        false,
+        &std::collections::HashMap::new(),
+    );
+    let out = output::compose_lint_json(&res, "lint");
+    assert_eq!(out["passed"], false);
+    assert_eq!(out["rules"]["pkg.name"], "pass");
+    assert_eq!(out["rules"]["pkg.license"], "fail");
+}
+
+#[test]
+fn lint_fix_writes_formatted_output_and_skips_unlinted_files() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path();
+    let conv = root.join("conv");
+    fs::create_dir_all(&conv).unwrap();
+    fs::write(
+        conv.join("index.toml"),
+        r#"
+[[rules]]
+id = "pkg"
+patterns = ["package.json", "conflicted/package.json", "a/b/package.json"]
+policy = "policy.toml"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        conv.join("policy.toml"),
+        r#"
+[[checks]]
+kind = "const"
+field = "license"
+value = "MIT"
+
+[order]
+top = [["name"], ["license", "version"]]
+
+[linebreak]
+between_groups = true
+"#,
+    )
+    .unwrap();
+    fs::write(
+        root.join("package.json"),
+        "{\"version\": \"1\", \"name\": \"x\"}\n",
+    )
+    .unwrap();
+    let conflicted = "{\n<<<<<<< HEAD\n  \"license\": \"ISC\"\n=======\n  \"license\": \"0BSD\"\n>>>>>>> main\n}\n";
+    fs::create_dir_all(root.join("conflicted")).unwrap();
+    fs::write(root.join("conflicted/package.json"), conflicted).unwrap();
+    fs::create_dir_all(root.join("a/b")).unwrap();
+    fs::write(root.join("a/b/package.json"), "{}").unwrap();
+
+    let layout = format::Layout {
+        strict_linebreak: true,
+        max_width: None,
+        normalize_strings: true,
+        between_groups: None,
+        before_fields: &std::collections::HashMap::new(),
+        in_fields: &std::collections::HashMap::new(),
+    };
+    let (fixed, errors) = rigra::fix::run_fix(
+        root.to_str().unwrap(),
+        "conv/index.toml",
+        true,
+        Some(2),
+        &layout,
+        &std::collections::HashMap::new(),
+    );
+    assert!(errors.is_empty());
+    assert_eq!(fixed.len(), 1);
+    assert!(fixed[0].wrote);
+    assert_eq!(
+        fs::read_to_string(root.join("package.json")).unwrap(),
+        "{\n  \"name\": \"x\",\n\n  \"license\": \"MIT\",\n  \"version\": \"1\"\n}\n"
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("conflicted/package.json")).unwrap(),
+        conflicted
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("a/b/package.json")).unwrap(),
+        "{}"
+    );
+}